@@ -1,6 +1,9 @@
 use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use crate::core::quantity::format_number;
+use crate::core::locale::{fill, text, Text};
 
-#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Amount {
     Gain(i64),
     Spend(i64),
@@ -9,18 +12,50 @@ pub enum Amount {
     SpendX(i64),
     CatalystX(i64),
     Set(i64),
+    // Restore this many units of a resource that wears out to full durability
+    Repair(i64),
+    // Require that no more than this much of a resource is held
+    AtMost(i64),
+    // Require that none of a resource is held
+    Forbid,
+    // Gain this much with the given percent chance, or nothing
+    Chance(i64, i64),
+    // Gain anything from the first number to the second, both included
+    Between(i64, i64),
 }
 
 impl Amount {
+    // Doing a job several times at once scales what it gains and spends, but not the limits on what may be held.
+    // Saturates, since the factor can be a held count that is already at the largest number.
     pub fn multiply(self, factor: i64) -> Self {
         match self {
-            Amount::Gain(delta) => Amount::Gain(delta * factor),
-            Amount::Spend(delta) => Amount::Spend(delta * factor),
-            Amount::Catalyst(delta) => Amount::Catalyst(delta * factor),
-            Amount::GainX(delta) => Amount::GainX(delta * factor),
-            Amount::SpendX(delta) => Amount::SpendX(delta * factor),
-            Amount::CatalystX(delta) => Amount::CatalystX(delta * factor),
-            Amount::Set(target) => Amount::Set(target * factor),
+            Amount::Gain(delta) => Amount::Gain(delta.saturating_mul(factor)),
+            Amount::Spend(delta) => Amount::Spend(delta.saturating_mul(factor)),
+            Amount::Catalyst(delta) => Amount::Catalyst(delta.saturating_mul(factor)),
+            Amount::GainX(delta) => Amount::GainX(delta.saturating_mul(factor)),
+            Amount::SpendX(delta) => Amount::SpendX(delta.saturating_mul(factor)),
+            Amount::CatalystX(delta) => Amount::CatalystX(delta.saturating_mul(factor)),
+            Amount::Set(target) => Amount::Set(target.saturating_mul(factor)),
+            Amount::Repair(units) => Amount::Repair(units.saturating_mul(factor)),
+            Amount::AtMost(limit) => Amount::AtMost(limit),
+            Amount::Forbid => Amount::Forbid,
+            // A multiplied chance is still rolled once, which keeps the expected value right
+            Amount::Chance(delta, percent) => Amount::Chance(delta.saturating_mul(factor), percent),
+            Amount::Between(min, max) => Amount::Between(min.saturating_mul(factor), max.saturating_mul(factor)),
+        }
+    }
+
+    // Expected gain with the smallest and largest possible gain, for amounts that are rolled
+    pub fn spread(&self) -> Option<(f64, i64, i64)> {
+        match self {
+            Amount::Chance(delta, percent) => {
+                let percent = (*percent).clamp(0, 100);
+                let min = if percent >= 100 { *delta } else { 0 };
+                let max = if percent <= 0 { 0 } else { *delta };
+                Some((*delta as f64 * percent as f64 / 100.0, min, max))
+            }
+            Amount::Between(min, max) => Some(((*min + *max) as f64 / 2.0, *min, (*max).max(*min))),
+            _ => None,
         }
     }
 }
@@ -28,13 +63,37 @@ impl Amount {
 impl Display for Amount {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Amount::Gain(number) => f.write_fmt(format_args!("+{}", *number)),
-            Amount::Spend(number) => f.write_fmt(format_args!("-{}", *number)),
-            Amount::Catalyst(number) => f.write_fmt(format_args!("require {}", *number)),
-            Amount::GainX(number) => f.write_fmt(format_args!("+{}X", *number)),
-            Amount::SpendX(number) => f.write_fmt(format_args!("-{}X", *number)),
-            Amount::CatalystX(number) => f.write_fmt(format_args!("require {}X", *number)),
-            Amount::Set(number) => f.write_fmt(format_args!("set to {}", *number)),
+            Amount::Gain(number) => f.write_fmt(format_args!("+{}", format_number(*number))),
+            Amount::Spend(number) => f.write_fmt(format_args!("-{}", format_number(*number))),
+            Amount::Catalyst(number) => f.write_str(&fill(Text::Require, &[&format_number(*number)])),
+            Amount::GainX(number) => f.write_fmt(format_args!("+{}X", format_number(*number))),
+            Amount::SpendX(number) => f.write_fmt(format_args!("-{}X", format_number(*number))),
+            Amount::CatalystX(number) => f.write_str(&fill(Text::Require, &[&format!("{}X", format_number(*number))])),
+            Amount::Set(number) => f.write_str(&fill(Text::SetTo, &[&format_number(*number)])),
+            Amount::Repair(number) => f.write_str(&fill(Text::Repair, &[&format_number(*number)])),
+            Amount::AtMost(number) => f.write_str(&fill(Text::AtMost, &[&format_number(*number)])),
+            Amount::Forbid => f.write_str(text(Text::Forbid)),
+            Amount::Chance(number, percent) => f.write_str(&fill(Text::Chance, &[&format_number(*number), percent])),
+            Amount::Between(min, max) => f.write_str(&fill(Text::Between, &[&format_number(*min), &format_number(*max)])),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_are_not_multiplied() {
+        assert_eq!(Amount::AtMost(2).multiply(3), Amount::AtMost(2));
+        assert_eq!(Amount::Forbid.multiply(3), Amount::Forbid);
+        assert_eq!(Amount::Spend(2).multiply(3), Amount::Spend(6));
+        assert_eq!(Amount::Between(1, 2).multiply(3), Amount::Between(3, 6));
+    }
+
+    #[test]
+    fn multiplying_saturates() {
+        assert_eq!(Amount::Spend(2).multiply(i64::MAX), Amount::Spend(i64::MAX));
+        assert_eq!(Amount::Chance(3, 50).multiply(i64::MAX), Amount::Chance(i64::MAX, 50));
+    }
+}
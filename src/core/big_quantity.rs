@@ -0,0 +1,239 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+// Arbitrary precision integer for held amounts, used with the `big-numbers` feature.
+// Stored as a sign and base 10^9 limbs, least significant first, which keeps printing simple.
+const BASE: u64 = 1_000_000_000;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigQuantity {
+    negative: bool,
+    // No trailing zero limbs, and zero has no limbs and isn't negative
+    limbs: Vec<u32>,
+}
+
+fn compare_magnitude(first: &[u32], second: &[u32]) -> Ordering {
+    first.len().cmp(&second.len())
+        .then_with(|| first.iter().rev().cmp(second.iter().rev()))
+}
+
+fn add_magnitude(first: &[u32], second: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(first.len().max(second.len()) + 1);
+    let mut carry = 0;
+    for index in 0..first.len().max(second.len()) {
+        let sum = *first.get(index).unwrap_or(&0) as u64 + *second.get(index).unwrap_or(&0) as u64 + carry;
+        limbs.push((sum % BASE) as u32);
+        carry = sum / BASE;
+    }
+    if carry > 0 {
+        limbs.push(carry as u32);
+    }
+    limbs
+}
+
+// The first magnitude has to be at least as large as the second
+fn subtract_magnitude(first: &[u32], second: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(first.len());
+    let mut borrow = 0;
+    for (index, limb) in first.iter().enumerate() {
+        let subtrahend = *second.get(index).unwrap_or(&0) as i64 + borrow;
+        let mut difference = *limb as i64 - subtrahend;
+        if difference < 0 {
+            difference += BASE as i64;
+            borrow = 1;
+        } else {
+            borrow = 0;
+        }
+        limbs.push(difference as u32);
+    }
+    limbs
+}
+
+impl BigQuantity {
+    fn new(negative: bool, mut limbs: Vec<u32>) -> BigQuantity {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigQuantity {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+
+    pub fn from_i64(value: i64) -> BigQuantity {
+        let mut magnitude = value.unsigned_abs();
+        let mut limbs = Vec::new();
+        while magnitude > 0 {
+            limbs.push((magnitude % BASE) as u32);
+            magnitude /= BASE;
+        }
+        BigQuantity::new(value < 0, limbs)
+    }
+
+    pub fn negate(&self) -> BigQuantity {
+        BigQuantity::new(!self.negative, self.limbs.clone())
+    }
+
+    pub fn add(&self, other: &BigQuantity) -> BigQuantity {
+        if self.negative == other.negative {
+            return BigQuantity::new(self.negative, add_magnitude(&self.limbs, &other.limbs));
+        }
+        match compare_magnitude(&self.limbs, &other.limbs) {
+            Ordering::Less => BigQuantity::new(other.negative, subtract_magnitude(&other.limbs, &self.limbs)),
+            Ordering::Equal => BigQuantity::default(),
+            Ordering::Greater => BigQuantity::new(self.negative, subtract_magnitude(&self.limbs, &other.limbs)),
+        }
+    }
+
+    pub fn subtract(&self, other: &BigQuantity) -> BigQuantity {
+        self.add(&other.negate())
+    }
+
+    pub fn multiply(&self, factor: i64) -> BigQuantity {
+        let factor_magnitude = factor.unsigned_abs() as u128;
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 3);
+        let mut carry = 0u128;
+        for limb in self.limbs.iter() {
+            let product = *limb as u128 * factor_magnitude + carry;
+            limbs.push((product % BASE as u128) as u32);
+            carry = product / BASE as u128;
+        }
+        while carry > 0 {
+            limbs.push((carry % BASE as u128) as u32);
+            carry /= BASE as u128;
+        }
+        BigQuantity::new(self.negative != (factor < 0), limbs)
+    }
+
+    // Rounds towards zero like integer division, and is None when dividing by zero
+    pub fn divide(&self, divisor: i64) -> Option<BigQuantity> {
+        if divisor == 0 {
+            return None;
+        }
+        let divisor_magnitude = divisor.unsigned_abs() as u128;
+        let mut limbs = vec![0; self.limbs.len()];
+        let mut remainder = 0u128;
+        for index in (0..self.limbs.len()).rev() {
+            let current = remainder * BASE as u128 + self.limbs[index] as u128;
+            limbs[index] = (current / divisor_magnitude) as u32;
+            remainder = current % divisor_magnitude;
+        }
+        Some(BigQuantity::new(self.negative != (divisor < 0), limbs))
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self.limbs.iter().rev().fold(0.0, |total, limb| total * BASE as f64 + *limb as f64);
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    pub fn to_i64_saturating(&self) -> i64 {
+        let mut magnitude: i128 = 0;
+        for limb in self.limbs.iter().rev() {
+            magnitude = magnitude * BASE as i128 + *limb as i128;
+            if magnitude > i64::MAX as i128 {
+                return if self.negative { i64::MIN } else { i64::MAX };
+            }
+        }
+        if self.negative { -(magnitude as i64) } else { magnitude as i64 }
+    }
+}
+
+impl Ord for BigQuantity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.limbs, &other.limbs),
+            (true, true) => compare_magnitude(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigQuantity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigQuantity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut limbs = self.limbs.iter().rev();
+        match limbs.next() {
+            Some(first) => {
+                if self.negative {
+                    f.write_str("-")?;
+                }
+                f.write_fmt(format_args!("{}", first))?;
+                for limb in limbs {
+                    f.write_fmt(format_args!("{:09}", limb))?;
+                }
+                Ok(())
+            }
+            None => f.write_str("0"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(value: i64) -> BigQuantity {
+        BigQuantity::from_i64(value)
+    }
+
+    #[test]
+    fn adding_carries_into_the_next_limb() {
+        assert_eq!(big(999_999_999).add(&big(1)), big(1_000_000_000));
+        assert_eq!(big(999_999_999_999_999_999).add(&big(1)).to_string(), "1000000000000000000");
+        assert_eq!(big(i64::MAX).add(&big(i64::MAX)).to_string(), "18446744073709551614");
+    }
+
+    #[test]
+    fn subtracting_borrows_from_the_next_limb() {
+        assert_eq!(big(1_000_000_000).subtract(&big(1)), big(999_999_999));
+        assert_eq!(big(1_000_000_000_000_000_000).subtract(&big(1)), big(999_999_999_999_999_999));
+        // Borrowing empties the top limb, which is dropped so the result compares equal
+        assert_eq!(big(1_000_000_000).subtract(&big(999_999_999)).limbs, vec![1]);
+    }
+
+    #[test]
+    fn results_can_be_negative() {
+        assert_eq!(big(5).subtract(&big(7)), big(-2));
+        assert_eq!(big(-3).add(&big(10)), big(7));
+        assert_eq!(big(1).subtract(&big(1_000_000_001)), big(-1_000_000_000));
+        assert_eq!(big(-4).multiply(-3), big(12));
+        assert_eq!(big(-4).add(&big(4)), BigQuantity::default());
+        assert_eq!(big(-1_000_000_000).to_string(), "-1000000000");
+        assert!(big(-2) < big(-1));
+        assert!(big(-1_000_000_000) < big(1));
+    }
+
+    #[test]
+    fn multiplying_carries_into_new_limbs() {
+        assert_eq!(big(999_999_999).multiply(1_000), big(999_999_999_000));
+        assert_eq!(big(i64::MAX).multiply(i64::MAX).to_string(), "85070591730234615847396907784232501249");
+        assert_eq!(big(7).multiply(0), BigQuantity::default());
+    }
+
+    #[test]
+    fn dividing_rounds_towards_zero() {
+        assert_eq!(big(7).divide(2), Some(big(3)));
+        assert_eq!(big(-7).divide(2), Some(big(-3)));
+        assert_eq!(big(7).divide(-2), Some(big(-3)));
+        // The remainder of the top limb carries down into the next one
+        assert_eq!(big(1_000_000_001).divide(3), Some(big(333_333_333)));
+        assert_eq!(big(5_000_000_000_000_000_000).divide(7), Some(big(5_000_000_000_000_000_000 / 7)));
+        assert_eq!(big(1).divide(0), None);
+    }
+
+    #[test]
+    fn converting_back_saturates_at_the_edges() {
+        assert_eq!(big(i64::MAX).to_i64_saturating(), i64::MAX);
+        assert_eq!(big(i64::MIN).to_i64_saturating(), i64::MIN);
+        assert_eq!(big(i64::MIN + 1).to_i64_saturating(), i64::MIN + 1);
+        assert_eq!(big(i64::MAX).add(&big(1)).to_i64_saturating(), i64::MAX);
+        assert_eq!(big(i64::MIN).subtract(&big(1)).to_i64_saturating(), i64::MIN);
+        assert_eq!(big(i64::MAX).multiply(1_000).to_i64_saturating(), i64::MAX);
+    }
+}
@@ -0,0 +1,272 @@
+use crate::core::amount::Amount;
+use crate::core::effect::EffectId;
+use crate::core::job::{starting_resources, Job, JobId};
+use crate::core::locale::Localised;
+use crate::core::resource::Resource;
+use crate::core::story::FacilityLog;
+use crate::core::upgrade::JobModifier;
+
+// The jobs of the facility itself. Scenario files and content packs bring their own, see `core::custom`.
+
+fn new_job(id: JobId, short_text: &'static str, long_text: &'static str, end_deltas: Vec<Vec<(Resource, Amount)>>) -> Job {
+    Job {
+        short_text,
+        long_text,
+        start_deltas: vec![],
+        end_deltas,
+        saved: true,
+        total_time_slots: 1,
+        id,
+        chosen_alternative: None,
+        modifiers: vec![],
+        effects: vec![],
+    }
+}
+
+fn new_effect_job(id: JobId, short_text: &'static str, long_text: &'static str, end_deltas: Vec<Vec<(Resource, Amount)>>, effects: Vec<(EffectId, usize)>) -> Job {
+    Job {
+        effects,
+        ..new_job(id, short_text, long_text, end_deltas)
+    }
+}
+
+fn new_upgrade(id: JobId, short_text: &'static str, long_text: &'static str, end_deltas: Vec<Vec<(Resource, Amount)>>, modifiers: Vec<JobModifier>) -> Job {
+    Job {
+        modifiers,
+        ..new_job(id, short_text, long_text, end_deltas)
+    }
+}
+
+pub fn all_job_ids() -> Vec<JobId> {
+    vec![
+        JobId::FragmentCatch,
+        JobId::DayDreamCraft,
+        JobId::DayDreamSell,
+        JobId::BottleBuy,
+        JobId::DreamCraft,
+        JobId::DreamSell,
+        JobId::DreamUse,
+        JobId::ComfortDreamCraft,
+        JobId::ComfortDreamSell,
+        JobId::ComfortDreamUse,
+        JobId::NightmareCraft,
+        JobId::NightmareSell,
+        JobId::BottleRepair,
+        JobId::NetUpgrade,
+        JobId::BottleRackUpgrade,
+        JobId::Retire,
+    ]
+}
+
+pub fn create_job(job_id: JobId) -> Job {
+    match job_id {
+        JobId::FragmentCatch => new_job(job_id, "Catch fragment", "Reach into the haze and catch a drifting fragment. Sometimes a scary one comes along.", vec![
+            vec![(Resource::Fragment, Amount::Gain(1)), (Resource::ScaryFragment, Amount::Chance(1, 30))],
+        ]),
+        JobId::DayDreamCraft => new_job(job_id, "Craft day dream", "Weave fragments together into a day dream.", vec![
+            vec![(Resource::Fragment, Amount::Spend(3)), (Resource::DayDream, Amount::Gain(1))],
+        ]),
+        JobId::DayDreamSell => new_job(job_id, "Sell day dream", "Someone at the counter pays for day dreams.", vec![
+            vec![(Resource::DayDream, Amount::Spend(1)), (Resource::Coin, Amount::Gain(2))],
+        ]),
+        JobId::BottleBuy => new_job(job_id, "Buy glass bottle", "A bottle to keep a dream from evaporating.", vec![
+            vec![(Resource::Coin, Amount::Spend(3)), (Resource::GlassBottle, Amount::Gain(1))],
+        ]),
+        JobId::DreamCraft => new_job(job_id, "Craft dream", "Distill day dreams into a proper dream.", vec![
+            vec![(Resource::DayDream, Amount::Spend(2)), (Resource::GlassBottle, Amount::Catalyst(1)), (Resource::Dream, Amount::Gain(1))],
+            vec![(Resource::DayDream, Amount::Spend(4)), (Resource::Dream, Amount::Gain(1))],
+        ]),
+        JobId::DreamSell => new_job(job_id, "Sell dream", "Dreams fetch a good price.", vec![
+            vec![(Resource::Dream, Amount::Spend(1)), (Resource::Coin, Amount::Gain(6))],
+        ]),
+        JobId::DreamUse => new_job(job_id, "Use dream", "Sleep through a dream yourself. It leaves something behind.", vec![
+            vec![(Resource::Dream, Amount::Spend(1)), (Resource::SoothingMemory, Amount::Gain(1)), (Resource::ScaryFragment, Amount::Gain(1))],
+        ]),
+        JobId::ComfortDreamCraft => new_job(job_id, "Craft comfort dream", "Soothe a dream with memories.", vec![
+            vec![(Resource::Dream, Amount::Spend(1)), (Resource::SoothingMemory, Amount::Spend(2)), (Resource::ComfortDream, Amount::Gain(1))],
+        ]),
+        JobId::ComfortDreamSell => new_job(job_id, "Sell comfort dream", "Comfort is always in demand.", vec![
+            vec![(Resource::ComfortDream, Amount::Spend(1)), (Resource::Coin, Amount::Gain(20))],
+        ]),
+        JobId::ComfortDreamUse => new_effect_job(job_id, "Sleep on comfort dream", "Rest on a comfort dream. For three days, every dream you craft comes out as two.", vec![
+            vec![(Resource::ComfortDream, Amount::Spend(1))],
+        ], vec![
+            (EffectId::ComfortDream, 3),
+        ]),
+        JobId::NightmareCraft => new_effect_job(job_id, "Craft nightmare", "Feed scary fragments to a dream. It haunts you for three days, and your hands shake when you catch fragments.", vec![
+            vec![(Resource::Dream, Amount::Spend(1)), (Resource::ScaryFragment, Amount::Spend(3)), (Resource::Nightmare, Amount::Gain(1))],
+        ], vec![
+            (EffectId::Nightmare, 3),
+        ]),
+        JobId::NightmareSell => new_job(job_id, "Sell nightmare", "Some collectors pay well for nightmares.", vec![
+            vec![(Resource::Nightmare, Amount::Spend(1)), (Resource::Coin, Amount::Gain(15))],
+        ]),
+        JobId::BottleRepair => new_job(job_id, "Repair glass bottle", "Patch up a cracked bottle.", vec![
+            vec![(Resource::Coin, Amount::Spend(1)), (Resource::GlassBottle, Amount::Repair(1))],
+        ]),
+        JobId::NetUpgrade => new_upgrade(job_id, "Weave a finer net", "Fragments stop slipping through. Every catch brings in one more fragment.", vec![
+            vec![(Resource::Coin, Amount::Spend(4))],
+        ], vec![
+            JobModifier::Amount(JobId::FragmentCatch, Resource::Fragment, 1),
+        ]),
+        JobId::BottleRackUpgrade => new_upgrade(job_id, "Build a bottle rack", "Dreams settle on the rack while they are distilled, so crafting one needs one glass bottle less.", vec![
+            vec![(Resource::Coin, Amount::Spend(12))],
+        ], vec![
+            JobModifier::Amount(JobId::DreamCraft, Resource::GlassBottle, -1),
+        ]),
+        JobId::Retire => new_job(job_id, "Retire", "Buy your way out of the facility, if you can leave the nightmares behind.", vec![
+            vec![(Resource::Coin, Amount::Spend(100)), (Resource::Nightmare, Amount::Forbid)],
+        ]),
+        JobId::StartingResources => starting_resources(),
+        // Jobs from scenario files are kept in their scenario, so only the id is known here
+        JobId::Custom(id) => new_job(job_id, id.0, "", vec![]),
+    }
+}
+
+pub const WIN_JOB_ID: JobId = JobId::Retire;
+
+// Identifies this set of jobs on leaderboards. Bump the version whenever a change to the jobs changes how runs play out.
+pub const SCENARIO_NAME: &str = "facility";
+pub const SCENARIO_VERSION: u32 = 10;
+
+// Shown in the journal the first time a job is done. Jobs from scenario files don't have any story.
+pub fn discovery(job_id: JobId) -> Option<Localised> {
+    Some(match job_id {
+        JobId::FragmentCatch => Localised {
+            english: "The haze in the collection room never settles. If you hold your hand still, something catches on your fingers.",
+            swedish: "Diset i insamlingsrummet lägger sig aldrig. Om du håller handen stilla fastnar något på fingrarna.",
+        },
+        JobId::DayDreamCraft => Localised {
+            english: "The fragments hum when they touch. Twisted together, they play a scene you almost remember.",
+            swedish: "Fragmenten surrar när de rör vid varandra. Hoptvinnade spelar de upp en scen du nästan minns.",
+        },
+        JobId::DayDreamSell => Localised {
+            english: "The counter has a bell nobody rings. The buyer was already waiting.",
+            swedish: "Disken har en klocka som ingen ringer i. Köparen väntade redan.",
+        },
+        JobId::BottleBuy => Localised {
+            english: "The supply cupboard sells bottles by the piece. The label says they are good for three dreams, give or take.",
+            swedish: "Förrådsskåpet säljer flaskor styckvis. Etiketten säger att de räcker till tre drömmar, ungefär.",
+        },
+        JobId::DreamCraft => Localised {
+            english: "Day dreams thicken when you pour them together. What comes out has a weight to it.",
+            swedish: "Dagdrömmar tjocknar när man häller ihop dem. Det som kommer ut har en tyngd.",
+        },
+        JobId::DreamSell => Localised {
+            english: "The buyer holds the dream up to the light before paying. They seem pleased.",
+            swedish: "Köparen håller upp drömmen mot ljuset innan hen betalar. Hen verkar nöjd.",
+        },
+        JobId::DreamUse => Localised {
+            english: "You sleep at your desk. When you wake up, your pockets are full of memories that aren't yours.",
+            swedish: "Du somnar vid skrivbordet. När du vaknar är fickorna fulla av minnen som inte är dina.",
+        },
+        JobId::ComfortDreamCraft => Localised {
+            english: "A dream wrapped in borrowed memories stops trembling.",
+            swedish: "En dröm som lindats in i lånade minnen slutar darra.",
+        },
+        JobId::ComfortDreamSell => Localised {
+            english: "Someone came in tired and left smiling.",
+            swedish: "Någon kom in trött och gick ut leende.",
+        },
+        JobId::ComfortDreamUse => Localised {
+            english: "You sleep on a comfort dream. For days afterwards, everything you distill comes out whole.",
+            swedish: "Du sover på en tröstdröm. I flera dagar efteråt blir allt du destillerar helt.",
+        },
+        JobId::NightmareCraft => Localised {
+            english: "The scary fragments find the dream on their own. You only have to let go.",
+            swedish: "De skrämmande fragmenten hittar drömmen på egen hand. Du behöver bara släppa taget.",
+        },
+        JobId::NightmareSell => Localised {
+            english: "The collector doesn't say what they want nightmares for. You decide not to ask.",
+            swedish: "Samlaren säger inte vad hen vill ha mardrömmar till. Du bestämmer dig för att inte fråga.",
+        },
+        JobId::BottleRepair => Localised {
+            english: "A little wax and the crack holds. For now.",
+            swedish: "Lite vax och sprickan håller. För tillfället.",
+        },
+        JobId::NetUpgrade => Localised {
+            english: "The new net is finer than hair. The haze doesn't seem to mind.",
+            swedish: "Det nya nätet är finare än hår. Diset verkar inte bry sig.",
+        },
+        JobId::BottleRackUpgrade => Localised {
+            english: "The rack hums quietly at night. The dreams on it don't spill.",
+            swedish: "Stället surrar tyst om nätterna. Drömmarna på det spiller inte.",
+        },
+        JobId::Retire => Localised {
+            english: "The door at the end of the hall was never locked. You just couldn't afford to leave.",
+            swedish: "Dörren i slutet av korridoren var aldrig låst. Du hade bara inte råd att gå.",
+        },
+        JobId::StartingResources | JobId::Custom(_) => return None,
+    })
+}
+
+// Unlocked the first time the player holds enough of a resource, see `core::story`
+pub fn facility_logs() -> Vec<FacilityLog> {
+    vec![
+        FacilityLog {
+            id: "first-wages",
+            title: Localised {
+                english: "Log 1: Wages",
+                swedish: "Logg 1: Lön",
+            },
+            text: Localised {
+                english: "Staff are paid by the piece. Staff are reminded that the facility has no closing time.",
+                swedish: "Personalen får betalt per styck. Personalen påminns om att anläggningen inte har någon stängningstid.",
+            },
+            resource: Resource::Coin,
+            amount: 10,
+        },
+        FacilityLog {
+            id: "fragment-pile",
+            title: Localised {
+                english: "Log 2: Storage",
+                swedish: "Logg 2: Förvaring",
+            },
+            text: Localised {
+                english: "Loose fragments must not be stored in piles. Piles have been observed dreaming on their own.",
+                swedish: "Lösa fragment får inte förvaras i högar. Högar har setts drömma på egen hand.",
+            },
+            resource: Resource::Fragment,
+            amount: 10,
+        },
+        FacilityLog {
+            id: "memories",
+            title: Localised {
+                english: "Log 3: Lost property",
+                swedish: "Logg 3: Hittegods",
+            },
+            text: Localised {
+                english: "Memories left behind by sleeping staff belong to the facility. Please hand them in.",
+                swedish: "Minnen som sovande personal lämnar efter sig tillhör anläggningen. Lämna in dem.",
+            },
+            resource: Resource::SoothingMemory,
+            amount: 3,
+        },
+        FacilityLog {
+            id: "nightmare",
+            title: Localised {
+                english: "Log 4: Containment",
+                swedish: "Logg 4: Inneslutning",
+            },
+            text: Localised {
+                english: "Nightmares are to be sold on the day they are made. Nobody retires while holding one.",
+                swedish: "Mardrömmar ska säljas samma dag som de skapas. Ingen går i pension med en mardröm i fickan.",
+            },
+            resource: Resource::Nightmare,
+            amount: 1,
+        },
+        FacilityLog {
+            id: "savings",
+            title: Localised {
+                english: "Log 5: Severance",
+                swedish: "Logg 5: Avgångsvederlag",
+            },
+            text: Localised {
+                english: "The cost of leaving is posted by the door. It has not changed in living memory.",
+                swedish: "Priset för att lämna sitter uppsatt vid dörren. Det har inte ändrats i mannaminne.",
+            },
+            resource: Resource::Coin,
+            amount: 25,
+        },
+    ]
+}
@@ -0,0 +1,163 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::core::amount::Amount;
+use crate::core::effect::EffectId;
+use crate::core::job::{Job, JobId};
+use crate::core::resource::{cache_attributes, Resource, ResourceAttributes};
+use crate::core::upgrade::JobModifier;
+
+// Resources and jobs that are defined as data instead of in the built in enums, such as generated scenarios.
+// Their ids are namespaced strings like `generated-42:resource-3`, so they can't collide with each other or the enums.
+// Ids and texts are interned, which keeps `Resource` and `JobId` small and `Copy` like the built in ones.
+// Interned strings are never freed, so drafts in the editor only intern ids once they are set, and never their texts.
+
+thread_local! {
+    static INTERNED: RefCell<BTreeSet<&'static str>> = const { RefCell::new(BTreeSet::new()) };
+    // Definitions of the resources in the scenario being played
+    static DEFINED_RESOURCES: RefCell<BTreeMap<Resource, ResourceDefinition>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+// The same text always gives back the same leaked string, so loading a scenario again doesn't leak more
+pub fn intern(text: &str) -> &'static str {
+    INTERNED.with(|interned| {
+        let mut interned = interned.borrow_mut();
+        match interned.get(text) {
+            Some(existing) => existing,
+            None => {
+                let leaked: &'static str = Box::leak(text.to_string().into_boxed_str());
+                interned.insert(leaked);
+                leaked
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+pub fn is_interned(text: &str) -> bool {
+    INTERNED.with(|interned| interned.borrow().contains(text))
+}
+
+// The id of a resource or job defined as data
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct CustomId(pub &'static str);
+
+impl CustomId {
+    pub fn new(id: &str) -> CustomId {
+        CustomId(intern(id))
+    }
+}
+
+impl Serialize for CustomId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        Ok(CustomId::new(&id))
+    }
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+fn default_time_slots() -> usize {
+    1
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResourceDefinition {
+    pub id: Resource,
+    pub name: String,
+    // Used for every count but one
+    pub plural_name: String,
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+    #[serde(default)]
+    pub display_as_name: bool,
+    #[serde(default)]
+    pub row: usize,
+    #[serde(default)]
+    pub durability: Option<i64>,
+    #[serde(default)]
+    pub upkeep: Vec<Vec<(Resource, Amount)>>,
+}
+
+impl ResourceDefinition {
+    pub fn attributes(&self) -> ResourceAttributes {
+        ResourceAttributes {
+            upkeep: self.upkeep.clone(),
+            visible: self.visible,
+            display_as_name: self.display_as_name,
+            row: self.row,
+            durability: self.durability,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JobDefinition {
+    pub id: JobId,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub end_deltas: Vec<Vec<(Resource, Amount)>>,
+    #[serde(default = "default_time_slots")]
+    pub time_slots: usize,
+    // Makes the job an upgrade, see `core::upgrade`
+    #[serde(default)]
+    pub modifiers: Vec<JobModifier>,
+    // Effects the job sets off, with how many time slots they last, see `core::effect`
+    #[serde(default)]
+    pub effects: Vec<(EffectId, usize)>,
+}
+
+impl JobDefinition {
+    pub fn to_job(&self) -> Job {
+        Job {
+            short_text: intern(&self.name),
+            long_text: intern(&self.description),
+            start_deltas: vec![],
+            end_deltas: self.end_deltas.clone(),
+            saved: true,
+            total_time_slots: self.time_slots,
+            id: self.id,
+            chosen_alternative: None,
+            modifiers: self.modifiers.clone(),
+            effects: self.effects.clone(),
+        }
+    }
+
+    pub fn from_job(job: &Job) -> JobDefinition {
+        JobDefinition {
+            id: job.id,
+            name: job.short_text.to_string(),
+            description: job.long_text.to_string(),
+            end_deltas: job.end_deltas.clone(),
+            time_slots: job.total_time_slots,
+            modifiers: job.modifiers.clone(),
+            effects: job.effects.clone(),
+        }
+    }
+}
+
+// Replace the defined resources with the ones of the scenario about to be played.
+// A definition of a built in resource overrides its attributes, but the name still comes from the translations.
+pub fn define_resources(definitions: &[ResourceDefinition]) {
+    DEFINED_RESOURCES.with(|defined| {
+        let mut defined = defined.borrow_mut();
+        defined.clear();
+        for definition in definitions.iter() {
+            defined.insert(definition.id, definition.clone());
+        }
+    });
+    cache_attributes(definitions);
+}
+
+pub fn resource_definition(resource: &Resource) -> Option<ResourceDefinition> {
+    DEFINED_RESOURCES.with(|defined| defined.borrow().get(resource).cloned())
+}
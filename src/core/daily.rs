@@ -0,0 +1,156 @@
+use crate::core::amount::Amount;
+use crate::core::job::JobId;
+use crate::core::locale::{fill, Text};
+use crate::core::random::Rng;
+use crate::core::resource::Resource;
+use crate::core::scenario::{facility, Scenario};
+use crate::core::validate::reachable_jobs;
+
+// Daily challenges are named after the facility and the date, such as `facility-daily-2024-05-17`,
+// so every day gets its own leaderboard and the variant can be rebuilt from the name alone.
+const DAILY_PREFIX: &str = "facility-daily-";
+
+// Jobs the facility can still be won without, so that banning one never makes the day unwinnable.
+// Jobs that need what a banned job makes go with it, so a job comes before the ones that need it, see `banned_job`.
+// Buying bottles isn't here, since without bottles the bottle rack and the bottled way of crafting dreams are left over.
+// Check with `strange-facility daily <date>` after changing the jobs.
+const BANNABLE_JOBS: [JobId; 7] = [
+    JobId::DayDreamSell,
+    JobId::DreamUse,
+    JobId::ComfortDreamCraft,
+    JobId::ComfortDreamSell,
+    JobId::NightmareCraft,
+    JobId::NightmareSell,
+    JobId::BottleRepair,
+];
+
+// Prices are scaled by a whole percentage in this range
+const PRICE_PERCENT_MIN: i64 = 80;
+const PRICE_PERCENT_MAX: i64 = 120;
+
+// Dates are written as YYYY-MM-DD, in UTC so that everyone gets the same day
+pub fn check_date(date: &str) -> Result<(), String> {
+    let parts = date.split('-').collect::<Vec<_>>();
+    let well_formed = parts.len() == 3 &&
+        parts.iter().zip([4, 2, 2]).all(|(part, length)| part.len() == length && part.chars().all(|character| character.is_ascii_digit()));
+    if !well_formed {
+        return Err(fill(Text::NotADate, &[&date]));
+    }
+    let year = parts[0].parse::<u32>().unwrap_or(0);
+    let month = parts[1].parse::<u32>().unwrap_or(0);
+    let day = parts[2].parse::<u32>().unwrap_or(0);
+    if !(1..=days_in_month(year, month)).contains(&day) {
+        return Err(fill(Text::NotARealDate, &[&date]));
+    }
+    Ok(())
+}
+
+// Zero for a month that doesn't exist
+fn days_in_month(year: u32, month: u32) -> u32 {
+    let leap_year = (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400);
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap_year => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+// FNV-1a of the date, so the seed doesn't depend on anything but the text of the date
+pub fn daily_seed(date: &str) -> u64 {
+    let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+    for byte in DAILY_PREFIX.bytes().chain(date.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+    }
+    hash
+}
+
+pub fn daily_name(date: &str) -> String {
+    format!("{}{}", DAILY_PREFIX, date)
+}
+
+// The date of a daily challenge scenario name, or None for any other scenario
+pub fn daily_date(name: &str) -> Option<&str> {
+    name.strip_prefix(DAILY_PREFIX)
+}
+
+fn scale_price(amount: &Amount, percent: i64) -> Amount {
+    let scale = |number: i64| ((number * percent + 50) / 100).max(1);
+    match amount {
+        Amount::Gain(number) => Amount::Gain(scale(*number)),
+        Amount::Spend(number) => Amount::Spend(scale(*number)),
+        other => *other,
+    }
+}
+
+// The facility with the day's changes: a few extra starting resources, different prices and one banned job.
+// Everything is rolled from the date, so every player and the verifier build the same variant.
+pub fn daily_scenario(date: &str) -> Result<Scenario, String> {
+    check_date(date)?;
+    let mut rng = Rng::new(daily_seed(date));
+    let mut scenario = facility();
+    scenario.name = daily_name(date);
+
+    let banned = BANNABLE_JOBS[rng.below(BANNABLE_JOBS.len() as u64) as usize];
+    scenario.jobs.retain(|job| job.id != banned);
+    let reachable = reachable_jobs(&scenario.jobs, &scenario.starting_resources);
+    scenario.jobs.retain(|job| reachable.contains(&job.id));
+
+    for job in scenario.jobs.iter_mut() {
+        let percent = PRICE_PERCENT_MIN + rng.below((PRICE_PERCENT_MAX - PRICE_PERCENT_MIN + 1) as u64) as i64;
+        for delta in job.end_deltas.iter_mut() {
+            for (resource, amount) in delta.iter_mut() {
+                if *resource == Resource::Coin {
+                    *amount = scale_price(amount, percent);
+                }
+            }
+        }
+    }
+
+    let starting_fragments = rng.below(5) as i64;
+    let starting_coins = rng.below(6) as i64;
+    let mut starting_delta = Vec::new();
+    if starting_fragments > 0 {
+        starting_delta.push((Resource::Fragment, Amount::Gain(starting_fragments)));
+    }
+    if starting_coins > 0 {
+        starting_delta.push((Resource::Coin, Amount::Gain(starting_coins)));
+    }
+    if !starting_delta.is_empty() {
+        scenario.starting_resources.end_deltas.push(starting_delta);
+    }
+    Ok(scenario)
+}
+
+// The job the day's variant leaves out. Jobs that went with it come later in `BANNABLE_JOBS`.
+pub fn banned_job(scenario: &Scenario) -> Option<JobId> {
+    BANNABLE_JOBS.into_iter().find(|job_id| scenario.job(*job_id).is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::solver::{solve, DEFAULT_MAX_STATES};
+
+    #[test]
+    fn only_real_dates_are_days() {
+        assert!(check_date("2024-02-29").is_ok());
+        assert!(check_date("2000-02-29").is_ok());
+        assert!(check_date("2026-12-31").is_ok());
+        assert!(check_date("2026-02-29").is_err());
+        assert!(check_date("1900-02-29").is_err());
+        assert!(check_date("2026-04-31").is_err());
+        assert!(check_date("2026-13-01").is_err());
+        assert!(check_date("2026-01-00").is_err());
+        assert!(check_date("2026-1-01").is_err());
+    }
+
+    #[test]
+    fn daily_can_be_won() {
+        let scenario = daily_scenario("2026-10-19").unwrap();
+        scenario.activate();
+        assert!(solve(&scenario, DEFAULT_MAX_STATES).unwrap().is_some());
+    }
+}
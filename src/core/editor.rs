@@ -0,0 +1,575 @@
+use std::collections::BTreeSet;
+use crate::core::amount::Amount;
+use crate::core::custom::{CustomId, JobDefinition, ResourceDefinition};
+use crate::core::job::JobId;
+use crate::core::locale::{effect_name, fill, text, Text};
+use crate::core::resource::{all_resources, Resource};
+use crate::core::scenario::{Scenario, ScenarioFile};
+use crate::core::upgrade::JobModifier;
+use crate::core::validate::validate_scenario;
+
+// Changes the scenario editor makes to a draft scenario file.
+// Everything is addressed by index, since ids are being edited along with everything else.
+
+// Amount variants as they are named in scenario files
+pub const AMOUNT_KINDS: [&str; 12] = [
+    "Gain", "Spend", "Catalyst", "GainX", "SpendX", "CatalystX", "Set", "Repair", "AtMost", "Forbid", "Chance", "Between",
+];
+
+// Modifier variants as they are named in scenario files
+pub const MODIFIER_KINDS: [&str; 2] = ["Amount", "TimeSlots"];
+
+// A draft changes without its version being bumped, so its playtests are named apart from the scenario it may be a copy of
+const PLAYTEST_PREFIX: &str = "editor-";
+
+pub fn playtest_name(name: &str) -> String {
+    format!("{}{}", PLAYTEST_PREFIX, name)
+}
+
+pub fn is_playtest(name: &str) -> bool {
+    name.starts_with(PLAYTEST_PREFIX)
+}
+
+// Where a list of alternative deltas lives in the draft
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeltaOwner {
+    Upkeep(usize),
+    Job(usize),
+    StartingResources,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScenarioEdit {
+    SetName(String),
+    AddResource(),
+    RemoveResource(usize),
+    SetResourceId(usize, String),
+    SetResourceName(usize, String),
+    SetResourcePluralName(usize, String),
+    SetResourceVisible(usize, bool),
+    SetResourceRow(usize, String),
+    AddJob(),
+    RemoveJob(usize),
+    SetJobId(usize, String),
+    SetJobName(usize, String),
+    SetJobDescription(usize, String),
+    SetJobTimeSlots(usize, String),
+    SetWinJob(usize),
+    AddAlternative(DeltaOwner),
+    RemoveAlternative(DeltaOwner, usize),
+    // Alternative index, then entry index
+    AddEntry(DeltaOwner, usize),
+    RemoveEntry(DeltaOwner, usize, usize),
+    SetEntryResource(DeltaOwner, usize, usize, Resource),
+    SetEntryKind(DeltaOwner, usize, usize, usize),
+    // The first or second number of the amount, for the amounts that have two
+    SetEntryNumber(DeltaOwner, usize, usize, usize, String),
+    // Job index, then modifier index
+    AddModifier(usize),
+    RemoveModifier(usize, usize),
+    SetModifierKind(usize, usize, usize),
+    SetModifierJob(usize, usize, JobId),
+    SetModifierResource(usize, usize, Resource),
+    SetModifierChange(usize, usize, String),
+}
+
+// The index of the amount's variant in `AMOUNT_KINDS`
+pub fn amount_kind(amount: &Amount) -> usize {
+    match amount {
+        Amount::Gain(_) => 0,
+        Amount::Spend(_) => 1,
+        Amount::Catalyst(_) => 2,
+        Amount::GainX(_) => 3,
+        Amount::SpendX(_) => 4,
+        Amount::CatalystX(_) => 5,
+        Amount::Set(_) => 6,
+        Amount::Repair(_) => 7,
+        Amount::AtMost(_) => 8,
+        Amount::Forbid => 9,
+        Amount::Chance(_, _) => 10,
+        Amount::Between(_, _) => 11,
+    }
+}
+
+// How many numbers the kind of amount is written with
+pub fn kind_numbers(kind: usize) -> usize {
+    match kind {
+        9 => 0,
+        10 | 11 => 2,
+        _ => 1,
+    }
+}
+
+pub fn amount_numbers(amount: &Amount) -> (i64, i64) {
+    match amount {
+        Amount::Gain(number) |
+        Amount::Spend(number) |
+        Amount::Catalyst(number) |
+        Amount::GainX(number) |
+        Amount::SpendX(number) |
+        Amount::CatalystX(number) |
+        Amount::Set(number) |
+        Amount::Repair(number) |
+        Amount::AtMost(number) => (*number, 0),
+        Amount::Forbid => (0, 0),
+        Amount::Chance(number, percent) => (*number, *percent),
+        Amount::Between(min, max) => (*min, *max),
+    }
+}
+
+pub fn amount_of_kind(kind: usize, first: i64, second: i64) -> Amount {
+    match kind {
+        0 => Amount::Gain(first),
+        1 => Amount::Spend(first),
+        2 => Amount::Catalyst(first),
+        3 => Amount::GainX(first),
+        4 => Amount::SpendX(first),
+        5 => Amount::CatalystX(first),
+        6 => Amount::Set(first),
+        7 => Amount::Repair(first),
+        8 => Amount::AtMost(first),
+        9 => Amount::Forbid,
+        10 => Amount::Chance(first, second),
+        _ => Amount::Between(first, second),
+    }
+}
+
+// The index of the modifier's variant in `MODIFIER_KINDS`
+pub fn modifier_kind(modifier: &JobModifier) -> usize {
+    match modifier {
+        JobModifier::Amount(_job, _resource, _change) => 0,
+        JobModifier::TimeSlots(_job, _change) => 1,
+    }
+}
+
+pub fn modifier_change(modifier: &JobModifier) -> i64 {
+    match modifier {
+        JobModifier::Amount(_job, _, change) | JobModifier::TimeSlots(_job, change) => *change,
+    }
+}
+
+// Job ids are edited as text. The built in ones are shown by their name in the code.
+pub fn job_id_text(id: &JobId) -> String {
+    match id {
+        JobId::Custom(id) => id.0.to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+pub fn resource_id_text(resource: &Resource) -> String {
+    match resource {
+        Resource::Custom(id) => id.0.to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+// Every resource a delta in the draft can use: the built in ones and the ones the draft defines
+pub fn draft_resources(file: &ScenarioFile) -> Vec<Resource> {
+    let mut resources = all_resources();
+    for definition in file.resources.iter() {
+        if !resources.contains(&definition.id) {
+            resources.push(definition.id);
+        }
+    }
+    resources
+}
+
+// A small scenario that is already winnable, to start a new one from
+pub fn new_scenario_file() -> ScenarioFile {
+    let resource = Resource::Custom(CustomId::new("my-scenario:resource-1"));
+    let gather = JobId::Custom(CustomId::new("my-scenario:gather"));
+    let finish = JobId::Custom(CustomId::new("my-scenario:finish"));
+    ScenarioFile {
+        name: "my-scenario".to_string(),
+        // Designers bump it themselves, whenever a change would make old runs play out differently
+        version: 1,
+        resources: vec![ResourceDefinition {
+            id: resource,
+            name: "Pebble".to_string(),
+            plural_name: "Pebbles".to_string(),
+            visible: true,
+            display_as_name: false,
+            row: 0,
+            durability: None,
+            upkeep: vec![],
+        }],
+        jobs: vec![
+            JobDefinition {
+                id: gather,
+                name: "Gather pebbles".to_string(),
+                description: String::new(),
+                end_deltas: vec![vec![(resource, Amount::Gain(1))]],
+                time_slots: 1,
+                modifiers: vec![],
+                effects: vec![],
+            },
+            JobDefinition {
+                id: finish,
+                name: "Finish".to_string(),
+                description: String::new(),
+                end_deltas: vec![vec![(resource, Amount::Spend(10))]],
+                time_slots: 1,
+                modifiers: vec![],
+                effects: vec![],
+            },
+        ],
+        starting_resources: vec![],
+        win_job: finish,
+    }
+}
+
+fn deltas_mut(file: &mut ScenarioFile, owner: DeltaOwner) -> Option<&mut Vec<Vec<(Resource, Amount)>>> {
+    match owner {
+        DeltaOwner::Upkeep(index) => file.resources.get_mut(index).map(|definition| &mut definition.upkeep),
+        DeltaOwner::Job(index) => file.jobs.get_mut(index).map(|job| &mut job.end_deltas),
+        DeltaOwner::StartingResources => Some(&mut file.starting_resources),
+    }
+}
+
+fn entry_mut(file: &mut ScenarioFile, owner: DeltaOwner, alternative: usize, entry: usize) -> Option<&mut (Resource, Amount)> {
+    deltas_mut(file, owner)?.get_mut(alternative)?.get_mut(entry)
+}
+
+fn modifier_mut(file: &mut ScenarioFile, job: usize, modifier: usize) -> Option<&mut JobModifier> {
+    file.jobs.get_mut(job)?.modifiers.get_mut(modifier)
+}
+
+// A resource the job uses, so that a new amount modifier passes validation
+fn used_resource(file: &ScenarioFile, job: JobId) -> Resource {
+    file.jobs.iter()
+        .find(|definition| definition.id == job)
+        .and_then(|definition| definition.end_deltas.iter().flatten().next())
+        .map(|(resource, _amount)| *resource)
+        .unwrap_or(Resource::Coin)
+}
+
+// Replace every use of a resource, so renaming its id doesn't leave deltas pointing at nothing
+fn rename_resource(file: &mut ScenarioFile, from: Resource, to: Resource) {
+    let deltas = file.jobs.iter_mut().map(|job| &mut job.end_deltas)
+        .chain(file.resources.iter_mut().map(|definition| &mut definition.upkeep))
+        .chain(std::iter::once(&mut file.starting_resources));
+    for alternatives in deltas {
+        for (resource, _amount) in alternatives.iter_mut().flatten() {
+            if *resource == from {
+                *resource = to;
+            }
+        }
+    }
+}
+
+// Numbers that don't parse are left as they were, so a half typed number doesn't wipe the amount
+fn parse_number(text: &str) -> Option<i64> {
+    text.trim().parse::<i64>().ok()
+}
+
+fn unused_number(taken: &[String], prefix: &str) -> String {
+    (1..).map(|number| format!("{}-{}", prefix, number))
+        .find(|id| !taken.contains(id))
+        .unwrap_or_default()
+}
+
+pub fn apply_edit(file: &mut ScenarioFile, edit: ScenarioEdit) {
+    match edit {
+        ScenarioEdit::SetName(name) => file.name = name,
+        ScenarioEdit::AddResource() => {
+            let taken = file.resources.iter().map(|definition| resource_id_text(&definition.id)).collect::<Vec<_>>();
+            let id = unused_number(&taken, &format!("{}:resource", file.name));
+            file.resources.push(ResourceDefinition {
+                id: Resource::Custom(CustomId::new(&id)),
+                name: String::new(),
+                plural_name: String::new(),
+                visible: true,
+                display_as_name: false,
+                row: 0,
+                durability: None,
+                upkeep: vec![],
+            });
+        }
+        ScenarioEdit::RemoveResource(index) => {
+            if index < file.resources.len() {
+                file.resources.remove(index);
+            }
+        }
+        ScenarioEdit::SetResourceId(index, id) => {
+            if let Some(definition) = file.resources.get(index) {
+                let from = definition.id;
+                let to = Resource::Custom(CustomId::new(id.trim()));
+                file.resources[index].id = to;
+                rename_resource(file, from, to);
+            }
+        }
+        ScenarioEdit::SetResourceName(index, name) => {
+            if let Some(definition) = file.resources.get_mut(index) {
+                definition.name = name;
+            }
+        }
+        ScenarioEdit::SetResourcePluralName(index, name) => {
+            if let Some(definition) = file.resources.get_mut(index) {
+                definition.plural_name = name;
+            }
+        }
+        ScenarioEdit::SetResourceVisible(index, visible) => {
+            if let Some(definition) = file.resources.get_mut(index) {
+                definition.visible = visible;
+            }
+        }
+        ScenarioEdit::SetResourceRow(index, row) => {
+            if let (Some(definition), Ok(row)) = (file.resources.get_mut(index), row.trim().parse::<usize>()) {
+                definition.row = row;
+            }
+        }
+        ScenarioEdit::AddJob() => {
+            let taken = file.jobs.iter().map(|job| job_id_text(&job.id)).collect::<Vec<_>>();
+            let id = unused_number(&taken, &format!("{}:job", file.name));
+            file.jobs.push(JobDefinition {
+                id: JobId::Custom(CustomId::new(&id)),
+                name: String::new(),
+                description: String::new(),
+                end_deltas: vec![vec![]],
+                time_slots: 1,
+                modifiers: vec![],
+                effects: vec![],
+            });
+        }
+        ScenarioEdit::RemoveJob(index) => {
+            if index < file.jobs.len() {
+                file.jobs.remove(index);
+            }
+        }
+        ScenarioEdit::SetJobId(index, id) => {
+            if let Some(job) = file.jobs.get_mut(index) {
+                let from = job.id;
+                let to = JobId::Custom(CustomId::new(id.trim()));
+                job.id = to;
+                if file.win_job == from {
+                    file.win_job = to;
+                }
+                // Upgrades keep changing the job under its new id
+                for modifier in file.jobs.iter_mut().flat_map(|job| job.modifiers.iter_mut()) {
+                    match modifier {
+                        JobModifier::Amount(job, _, _change) | JobModifier::TimeSlots(job, _change) if *job == from => *job = to,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        ScenarioEdit::SetJobName(index, name) => {
+            if let Some(job) = file.jobs.get_mut(index) {
+                job.name = name;
+            }
+        }
+        ScenarioEdit::SetJobDescription(index, description) => {
+            if let Some(job) = file.jobs.get_mut(index) {
+                job.description = description;
+            }
+        }
+        ScenarioEdit::SetJobTimeSlots(index, time_slots) => {
+            if let (Some(job), Ok(time_slots)) = (file.jobs.get_mut(index), time_slots.trim().parse::<usize>()) {
+                job.time_slots = time_slots;
+            }
+        }
+        ScenarioEdit::SetWinJob(index) => {
+            if let Some(job) = file.jobs.get(index) {
+                file.win_job = job.id;
+            }
+        }
+        ScenarioEdit::AddAlternative(owner) => {
+            if let Some(deltas) = deltas_mut(file, owner) {
+                deltas.push(vec![]);
+            }
+        }
+        ScenarioEdit::RemoveAlternative(owner, alternative) => {
+            if let Some(deltas) = deltas_mut(file, owner) {
+                if alternative < deltas.len() {
+                    deltas.remove(alternative);
+                }
+            }
+        }
+        ScenarioEdit::AddEntry(owner, alternative) => {
+            let resource = draft_resources(file).last().cloned().unwrap_or(Resource::Coin);
+            if let Some(delta) = deltas_mut(file, owner).and_then(|deltas| deltas.get_mut(alternative)) {
+                delta.push((resource, Amount::Gain(1)));
+            }
+        }
+        ScenarioEdit::RemoveEntry(owner, alternative, entry) => {
+            if let Some(delta) = deltas_mut(file, owner).and_then(|deltas| deltas.get_mut(alternative)) {
+                if entry < delta.len() {
+                    delta.remove(entry);
+                }
+            }
+        }
+        ScenarioEdit::SetEntryResource(owner, alternative, entry, resource) => {
+            if let Some(entry) = entry_mut(file, owner, alternative, entry) {
+                entry.0 = resource;
+            }
+        }
+        ScenarioEdit::SetEntryKind(owner, alternative, entry, kind) => {
+            if let Some(entry) = entry_mut(file, owner, alternative, entry) {
+                let (first, second) = amount_numbers(&entry.1);
+                // Keep the numbers that still make sense, and give new ones something that passes validation
+                let first = if first > 0 { first } else { 1 };
+                let second = match kind {
+                    10 if !(1..=100).contains(&second) => 50,
+                    11 if second < first => first,
+                    _ => second,
+                };
+                entry.1 = amount_of_kind(kind, first, second);
+            }
+        }
+        ScenarioEdit::SetEntryNumber(owner, alternative, entry, which, text) => {
+            if let (Some(entry), Some(number)) = (entry_mut(file, owner, alternative, entry), parse_number(&text)) {
+                let (first, second) = amount_numbers(&entry.1);
+                let (first, second) = if which == 0 { (number, second) } else { (first, number) };
+                entry.1 = amount_of_kind(amount_kind(&entry.1), first, second);
+            }
+        }
+        ScenarioEdit::AddModifier(index) => {
+            // Making the first job quicker is always valid, so it is where a new modifier starts
+            let target = file.jobs.first().map(|job| job.id);
+            if let (Some(job), Some(target)) = (file.jobs.get_mut(index), target) {
+                job.modifiers.push(JobModifier::TimeSlots(target, -1));
+            }
+        }
+        ScenarioEdit::RemoveModifier(index, modifier) => {
+            if let Some(job) = file.jobs.get_mut(index) {
+                if modifier < job.modifiers.len() {
+                    job.modifiers.remove(modifier);
+                }
+            }
+        }
+        ScenarioEdit::SetModifierKind(index, modifier, kind) => {
+            if let Some(current) = modifier_mut(file, index, modifier).copied() {
+                let changed = match kind {
+                    0 => JobModifier::Amount(current.job(), used_resource(file, current.job()), modifier_change(&current)),
+                    _ => JobModifier::TimeSlots(current.job(), modifier_change(&current)),
+                };
+                if let Some(modifier) = modifier_mut(file, index, modifier) {
+                    *modifier = changed;
+                }
+            }
+        }
+        ScenarioEdit::SetModifierJob(index, modifier, target) => {
+            let resource = used_resource(file, target);
+            if let Some(modifier) = modifier_mut(file, index, modifier) {
+                *modifier = match *modifier {
+                    JobModifier::Amount(_job, _resource, change) => JobModifier::Amount(target, resource, change),
+                    JobModifier::TimeSlots(_job, change) => JobModifier::TimeSlots(target, change),
+                };
+            }
+        }
+        ScenarioEdit::SetModifierResource(index, modifier, resource) => {
+            if let Some(JobModifier::Amount(_job, modified, _change)) = modifier_mut(file, index, modifier) {
+                *modified = resource;
+            }
+        }
+        ScenarioEdit::SetModifierChange(index, modifier, text) => {
+            if let (Some(modifier), Some(number)) = (modifier_mut(file, index, modifier), parse_number(&text)) {
+                *modifier = match *modifier {
+                    JobModifier::Amount(job, resource, _change) => JobModifier::Amount(job, resource, number),
+                    JobModifier::TimeSlots(job, _change) => JobModifier::TimeSlots(job, number),
+                };
+            }
+        }
+    }
+}
+
+// Everything wrong with the draft: mistakes in the file itself first, then what the catalogue validation finds
+pub fn check_scenario_file(file: &ScenarioFile) -> Vec<String> {
+    let mut problems = Vec::new();
+    if file.name.trim().is_empty() {
+        problems.push(text(Text::ScenarioNeedsName).to_string());
+    }
+    let mut resource_ids = BTreeSet::new();
+    for (index, definition) in file.resources.iter().enumerate() {
+        let id = resource_id_text(&definition.id);
+        if id.is_empty() {
+            problems.push(fill(Text::ResourceHasNoId, &[&(index + 1)]));
+        } else if !resource_ids.insert(definition.id) {
+            problems.push(fill(Text::DuplicateResourceId, &[&id]));
+        }
+        if definition.name.trim().is_empty() || definition.plural_name.trim().is_empty() {
+            problems.push(fill(Text::ResourceNeedsNames, &[&id]));
+        }
+    }
+    if file.jobs.is_empty() {
+        problems.push(text(Text::ScenarioHasNoJobs).to_string());
+    }
+    let mut job_ids = BTreeSet::new();
+    for (index, job) in file.jobs.iter().enumerate() {
+        let id = job_id_text(&job.id);
+        if id.is_empty() {
+            problems.push(fill(Text::JobHasNoId, &[&(index + 1)]));
+        } else if !job_ids.insert(job.id) {
+            problems.push(fill(Text::DuplicateJobId, &[&id]));
+        }
+        if job.name.trim().is_empty() {
+            problems.push(fill(Text::JobNeedsName, &[&id]));
+        }
+        if job.time_slots == 0 {
+            problems.push(fill(Text::JobTakesNoTime, &[&id]));
+        }
+        if job.end_deltas.is_empty() || job.end_deltas.iter().any(|delta| delta.is_empty()) {
+            problems.push(fill(Text::AlternativeDoesNothing, &[&id]));
+        }
+        for (effect, time_slots) in job.effects.iter() {
+            if *time_slots == 0 {
+                problems.push(fill(Text::EffectForNoTime, &[&id, &effect_name(*effect)]));
+            }
+        }
+    }
+    if !job_ids.contains(&file.win_job) {
+        problems.push(fill(Text::WinJobNotAJob, &[&job_id_text(&file.win_job)]));
+    }
+    let deltas = file.jobs.iter().flat_map(|job| job.end_deltas.iter())
+        .chain(file.resources.iter().flat_map(|definition| definition.upkeep.iter()))
+        .chain(file.starting_resources.iter());
+    let mut undefined = BTreeSet::new();
+    for (resource, _amount) in deltas.flatten() {
+        if let Resource::Custom(id) = resource {
+            if !resource_ids.contains(resource) && undefined.insert(*resource) {
+                problems.push(fill(Text::ResourceNeverDefined, &[&id.0]));
+            }
+        }
+    }
+    // Validation doesn't read names, and interning every half typed one would keep them all around
+    let mut unnamed = file.clone();
+    for job in unnamed.jobs.iter_mut() {
+        job.name.clear();
+        job.description.clear();
+    }
+    problems.extend(validate_scenario(&Scenario::from_file(&unnamed)));
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::custom::is_interned;
+
+    #[test]
+    fn checking_a_draft_doesnt_intern_its_names() {
+        let mut file = new_scenario_file();
+        for name in ["G", "Ga", "Gat", "Gath"] {
+            apply_edit(&mut file, ScenarioEdit::SetJobName(0, name.to_string()));
+            apply_edit(&mut file, ScenarioEdit::SetJobDescription(0, format!("{} description", name)));
+            assert_eq!(check_scenario_file(&file), Vec::<String>::new());
+        }
+        assert!(!is_interned("Gath"));
+        assert!(!is_interned("Gath description"));
+
+        // Playing it is what interns them
+        Scenario::from_file(&file);
+        assert!(is_interned("Gath"));
+    }
+
+    #[test]
+    fn renaming_a_job_keeps_upgrades_on_it() {
+        let mut file = new_scenario_file();
+        apply_edit(&mut file, ScenarioEdit::AddModifier(1));
+        apply_edit(&mut file, ScenarioEdit::SetJobId(0, "my-scenario:collect".to_string()));
+        let collect = JobId::Custom(CustomId::new("my-scenario:collect"));
+        assert_eq!(file.jobs[0].id, collect);
+        assert_eq!(file.jobs[1].modifiers, vec![JobModifier::TimeSlots(collect, -1)]);
+        assert_eq!(check_scenario_file(&file), Vec::<String>::new());
+    }
+}
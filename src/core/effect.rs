@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::core::amount::Amount;
+use crate::core::job::JobId;
+use crate::core::resource::Resource;
+
+// Effects are left behind by some jobs and change how much of a resource another job yields while they last.
+// Like durability, they are part of what a run carries from one job to the next.
+
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+pub enum EffectId {
+    // Sleeping on a comfort dream doubles the dreams you craft
+    ComfortDream,
+    // Crafting a nightmare halves the fragments you catch
+    Nightmare,
+}
+
+// Time slots left of every active effect. Every job the player does takes its time slots off each effect.
+pub type EffectSet = BTreeMap<EffectId, usize>;
+
+// Which job the effect changes, which of its yields, and what percent of the usual yield it gives
+pub fn effect_rule(effect: EffectId) -> (JobId, Resource, i64) {
+    match effect {
+        EffectId::ComfortDream => (JobId::DreamCraft, Resource::Dream, 200),
+        EffectId::Nightmare => (JobId::FragmentCatch, Resource::Fragment, 50),
+    }
+}
+
+// Only yields change, so an effect never makes a job cost more or less.
+// Yields are rounded down, so halving a single fragment leaves nothing.
+fn scale_yield(amount: &Amount, percent: i64) -> Amount {
+    let scale = |number: i64| if number > 0 { number.saturating_mul(percent) / 100 } else { number };
+    match amount {
+        Amount::Gain(number) => Amount::Gain(scale(*number)),
+        Amount::GainX(number) => Amount::GainX(scale(*number)),
+        Amount::Chance(number, chance) => Amount::Chance(scale(*number), *chance),
+        Amount::Between(min, max) => Amount::Between(scale(*min), scale(*max)),
+        _ => *amount,
+    }
+}
+
+// The amount of the resource in the job with every active effect on it applied
+pub fn affected_amount(effects: &EffectSet, job: JobId, resource: &Resource, amount: &Amount) -> Amount {
+    effects.keys()
+        .map(|effect| effect_rule(*effect))
+        .filter(|(affected_job, affected_resource, _percent)| *affected_job == job && affected_resource == resource)
+        .fold(*amount, |amount, (_job, _resource, percent)| scale_yield(&amount, percent))
+}
+
+// The job's deltas with every active effect on it applied
+pub fn affected_deltas(effects: &EffectSet, job: JobId, deltas: &[Vec<(Resource, Amount)>]) -> Vec<Vec<(Resource, Amount)>> {
+    deltas.iter()
+        .map(|delta| {
+            delta.iter()
+                .map(|(resource, amount)| (*resource, affected_amount(effects, job, resource, amount)))
+                .collect()
+        })
+        .collect()
+}
+
+// Take the time a job took off every effect, dropping the ones that run out
+pub fn tick(effects: &mut EffectSet, time_slots: usize) {
+    for remaining in effects.values_mut() {
+        *remaining = remaining.saturating_sub(time_slots);
+    }
+    effects.retain(|_effect, remaining| *remaining > 0);
+}
+
+// Setting off an effect that is already active doesn't stack, it only lasts longer if the new one is longer
+pub fn grant(effects: &mut EffectSet, granted: &[(EffectId, usize)]) {
+    for (effect, time_slots) in granted.iter() {
+        let remaining = effects.entry(*effect).or_insert(0);
+        *remaining = (*remaining).max(*time_slots);
+    }
+    effects.retain(|_effect, remaining| *remaining > 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halving_rounds_down() {
+        let effects = EffectSet::from([(EffectId::Nightmare, 3)]);
+        let catch = |amount: Amount| affected_amount(&effects, JobId::FragmentCatch, &Resource::Fragment, &amount);
+        assert_eq!(catch(Amount::Gain(1)), Amount::Gain(0));
+        assert_eq!(catch(Amount::Gain(3)), Amount::Gain(1));
+        assert_eq!(catch(Amount::Between(1, 4)), Amount::Between(0, 2));
+        assert_eq!(catch(Amount::Chance(1, 30)), Amount::Chance(0, 30));
+        assert_eq!(catch(Amount::Spend(3)), Amount::Spend(3));
+        // Other jobs, and other yields of the job, are left alone
+        assert_eq!(affected_amount(&effects, JobId::DayDreamSell, &Resource::Fragment, &Amount::Gain(2)), Amount::Gain(2));
+        assert_eq!(affected_amount(&effects, JobId::FragmentCatch, &Resource::ScaryFragment, &Amount::Gain(2)), Amount::Gain(2));
+    }
+
+    #[test]
+    fn doubling_scales_every_yield() {
+        let effects = EffectSet::from([(EffectId::ComfortDream, 3)]);
+        assert_eq!(affected_amount(&effects, JobId::DreamCraft, &Resource::Dream, &Amount::Gain(1)), Amount::Gain(2));
+        assert_eq!(affected_amount(&effects, JobId::DreamCraft, &Resource::Dream, &Amount::Between(1, 2)), Amount::Between(2, 4));
+        // Dreams used up elsewhere aren't crafted, so they stay as they are
+        assert_eq!(affected_amount(&effects, JobId::DreamSell, &Resource::Dream, &Amount::Gain(1)), Amount::Gain(1));
+    }
+}
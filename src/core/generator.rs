@@ -0,0 +1,250 @@
+use crate::core::amount::Amount;
+use crate::core::custom::{CustomId, JobDefinition, ResourceDefinition};
+use crate::core::job::{starting_resources, JobId};
+use crate::core::locale::{fill, text, Text};
+use crate::core::random::Rng;
+use crate::core::resource::Resource;
+use crate::core::scenario::Scenario;
+use crate::core::solver::{solve, DEFAULT_MAX_STATES};
+use crate::core::validate::validate_scenario;
+
+// Generated scenarios are named after their settings, such as `generated-42-3x2`,
+// so that a run on one can be verified by generating the scenario again.
+const GENERATED_PREFIX: &str = "generated-";
+// Generated scenarios change separately from the facility. Bump this whenever the same settings would generate a different scenario.
+pub const GENERATOR_VERSION: u32 = 1;
+
+pub const DEFAULT_DEPTH: usize = 3;
+pub const DEFAULT_WIDTH: usize = 2;
+pub const MAX_DEPTH: usize = 4;
+pub const MAX_WIDTH: usize = 3;
+// The solver has to search every combination of held resources, which grows too fast past this many
+const MAX_RESOURCES: usize = 10;
+// Seeds are stepped this many times looking for a winnable scenario before giving up
+const MAX_ATTEMPTS: usize = 20;
+
+const SYLLABLES: [&str; 24] = [
+    "ash", "bel", "cor", "dun", "el", "fen", "gal", "hol", "ir", "jun", "kel", "lum",
+    "mor", "nim", "or", "pel", "quin", "ros", "sil", "tam", "ul", "ven", "wyr", "zor",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GeneratorSettings {
+    pub seed: u64,
+    // How many crafting steps there are between gathering and winning
+    pub depth: usize,
+    // How many resources there are at every step
+    pub width: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Brutal,
+}
+
+pub struct Generated {
+    pub scenario: Scenario,
+    // The fewest days the scenario can be won in
+    pub par: usize,
+    pub difficulty: Difficulty,
+}
+
+// Rated by how many days the best possible run takes
+pub fn difficulty(par: usize) -> Difficulty {
+    match par {
+        0..=29 => Difficulty::Easy,
+        30..=59 => Difficulty::Normal,
+        60..=99 => Difficulty::Hard,
+        _ => Difficulty::Brutal,
+    }
+}
+
+impl Difficulty {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => text(Text::DifficultyEasy),
+            Difficulty::Normal => text(Text::DifficultyNormal),
+            Difficulty::Hard => text(Text::DifficultyHard),
+            Difficulty::Brutal => text(Text::DifficultyBrutal),
+        }
+    }
+}
+
+pub fn generated_name(settings: &GeneratorSettings) -> String {
+    format!("{}{}-{}x{}", GENERATED_PREFIX, settings.seed, settings.depth, settings.width)
+}
+
+// The settings a generated scenario name was made from, or None for any other scenario
+pub fn generated_settings(name: &str) -> Option<GeneratorSettings> {
+    let rest = name.strip_prefix(GENERATED_PREFIX)?;
+    let (seed, size) = rest.split_once('-')?;
+    let (depth, width) = size.split_once('x')?;
+    Some(GeneratorSettings {
+        seed: seed.parse().ok()?,
+        depth: depth.parse().ok()?,
+        width: width.parse().ok()?,
+    })
+}
+
+fn check_settings(settings: &GeneratorSettings) -> Result<(), String> {
+    if settings.depth < 1 || settings.depth > MAX_DEPTH {
+        return Err(fill(Text::DepthOutOfRange, &[&MAX_DEPTH, &settings.depth]));
+    }
+    if settings.width < 1 || settings.width > MAX_WIDTH {
+        return Err(fill(Text::WidthOutOfRange, &[&MAX_WIDTH, &settings.width]));
+    }
+    if (settings.depth + 1) * settings.width > MAX_RESOURCES {
+        return Err(fill(Text::TooManyResources, &[&settings.depth, &settings.width, &MAX_RESOURCES]));
+    }
+    Ok(())
+}
+
+fn roll(rng: &mut Rng, min: i64, max: i64) -> i64 {
+    min + rng.below((max - min + 1) as u64) as i64
+}
+
+fn pick<T: Copy>(rng: &mut Rng, items: &[T]) -> T {
+    items[rng.below(items.len() as u64) as usize]
+}
+
+// A made up name that isn't taken yet
+fn new_name(rng: &mut Rng, taken: &[String]) -> String {
+    loop {
+        let name = format!("{}{}", pick(rng, &SYLLABLES), pick(rng, &SYLLABLES));
+        let mut characters = name.chars();
+        let name = match characters.next() {
+            Some(first) => first.to_uppercase().chain(characters).collect::<String>(),
+            None => name,
+        };
+        if !taken.contains(&name) {
+            return name;
+        }
+    }
+}
+
+// Tiers of resources where every resource is gathered or made from the tier before it,
+// and the winning job needs one of everything in the last tier
+fn build(settings: &GeneratorSettings, rng: &mut Rng) -> Scenario {
+    let namespace = format!("{}{}", GENERATED_PREFIX, settings.seed);
+    let mut names = Vec::new();
+    let mut resources = Vec::new();
+    let mut jobs = Vec::new();
+    let mut tiers: Vec<Vec<Resource>> = Vec::new();
+    for tier in 0..=settings.depth {
+        let mut tier_resources = Vec::new();
+        for index in 0..settings.width {
+            let name = new_name(rng, &names);
+            names.push(name.clone());
+            // Made up words take the plural of the language they are generated in
+            let plural_name = fill(Text::GeneratedPlural, &[&name]);
+            let resource = Resource::Custom(CustomId::new(&format!("{}:resource-{}-{}", namespace, tier, index)));
+            resources.push(ResourceDefinition {
+                id: resource,
+                name: name.clone(),
+                plural_name: plural_name.clone(),
+                visible: true,
+                display_as_name: false,
+                row: 0,
+                durability: None,
+                upkeep: vec![],
+            });
+            let delta = match tiers.last() {
+                // Gathered from nothing
+                None => vec![(resource, Amount::Gain(roll(rng, 1, 2)))],
+                // Made mostly from its counterpart in the tier before, so every resource there is needed
+                Some(previous_tier) => {
+                    let main_input = previous_tier[index % previous_tier.len()];
+                    let mut delta = vec![(main_input, Amount::Spend(roll(rng, 2, 3)))];
+                    let extra_input = pick(rng, previous_tier);
+                    if extra_input != main_input && rng.below(2) == 0 {
+                        delta.push((extra_input, Amount::Spend(1)));
+                    }
+                    delta.push((resource, Amount::Gain(1)));
+                    delta
+                }
+            };
+            let (job_name, description) = if tier == 0 {
+                (fill(Text::GatherJob, &[&name.to_lowercase()]), fill(Text::GatherDescription, &[&plural_name.to_lowercase()]))
+            } else {
+                (fill(Text::MakeJob, &[&name.to_lowercase()]), fill(Text::MakeDescription, &[&name.to_lowercase()]))
+            };
+            jobs.push(JobDefinition {
+                id: JobId::Custom(CustomId::new(&format!("{}:job-{}-{}", namespace, tier, index))),
+                name: job_name,
+                description,
+                end_deltas: vec![delta],
+                time_slots: 1,
+                modifiers: vec![],
+                effects: vec![],
+            }.to_job());
+            tier_resources.push(resource);
+        }
+        tiers.push(tier_resources);
+    }
+    let win_job = JobId::Custom(CustomId::new(&format!("{}:finish", namespace)));
+    let finish_delta = tiers.last().cloned().unwrap_or_default().into_iter()
+        .map(|resource| (resource, Amount::Spend(1)))
+        .collect::<Vec<_>>();
+    jobs.push(JobDefinition {
+        id: win_job,
+        name: text(Text::FinishJob).to_string(),
+        description: text(Text::FinishDescription).to_string(),
+        end_deltas: vec![finish_delta],
+        time_slots: 1,
+        modifiers: vec![],
+        effects: vec![],
+    }.to_job());
+    Scenario {
+        name: generated_name(settings),
+        version: GENERATOR_VERSION,
+        resources,
+        jobs,
+        starting_resources: starting_resources(),
+        win_job,
+        packs: vec![],
+    }
+}
+
+// Build a random scenario and make sure it can be won, rating it by the fewest days it can be won in.
+// The same settings always give the same scenario. The generated scenario is left active.
+pub fn generate(settings: &GeneratorSettings) -> Result<Generated, String> {
+    check_settings(settings)?;
+    let mut rng = Rng::new(settings.seed);
+    for _attempt in 0..MAX_ATTEMPTS {
+        let scenario = build(settings, &mut rng);
+        scenario.activate();
+        if !validate_scenario(&scenario).is_empty() {
+            continue;
+        }
+        if let Some(plan) = solve(&scenario, DEFAULT_MAX_STATES)? {
+            let par = plan.len();
+            return Ok(Generated {
+                scenario,
+                par,
+                difficulty: difficulty(par),
+            });
+        }
+    }
+    Err(fill(Text::NoWinnableScenario, &[&settings.seed, &MAX_ATTEMPTS]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::locale::{set_locale, Locale};
+
+    #[test]
+    fn generated_texts_are_in_the_chosen_language() {
+        set_locale(Locale::Swedish);
+        let generated = generate(&GeneratorSettings { seed: 3, depth: 1, width: 1 });
+        set_locale(Locale::English);
+        let scenario = generated.unwrap().scenario;
+        assert_eq!(scenario.job(scenario.win_job).unwrap().short_text, "Avsluta");
+        let gathered = &scenario.resources[0];
+        assert_eq!(gathered.plural_name, format!("{}er", gathered.name));
+        assert_eq!(scenario.jobs[0].short_text, format!("Samla {}", gathered.name.to_lowercase()));
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+use crate::core::record::{JobRecord, StepRecord};
+use crate::core::replay::{count_days, expand_history, run_jobs};
+use crate::core::quantity::{quantity, subtract, Quantity};
+use crate::core::resource::{Resource, ResourceSet};
+use crate::core::scenario::Scenario;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GhostStatus {
+    Even,
+    // At least as much of everything as the ghost, and more of something
+    Ahead,
+    // At most as much of everything as the ghost, and less of something
+    Behind,
+    // More of some resources and less of others
+    Mixed,
+}
+
+pub struct GhostDay {
+    pub day: usize,
+    // How much more of each resource the current run has than the ghost, leaving out the equal ones
+    pub differences: Vec<(Resource, Quantity)>,
+    pub status: GhostStatus,
+}
+
+// Resources at the end of every day, starting with the starting resources on day 0
+fn resources_by_day(steps: &[StepRecord], seed: u64, scenario: &Scenario) -> Result<Vec<ResourceSet>, String> {
+    let jobs = expand_history(steps, scenario).jobs;
+    let outputs = run_jobs(&jobs, seed)?;
+    let day_count = count_days(&jobs, &outputs, scenario.win_job);
+    let mut by_day = vec![BTreeMap::new(); day_count.total_days + 1];
+    let mut resources = BTreeMap::new();
+    for (job_index, output) in outputs.iter().enumerate() {
+        if output.is_ok() {
+            resources = output.resources_after.clone();
+        }
+        by_day[day_count.days_after[job_index]] = resources.clone();
+    }
+    Ok(by_day)
+}
+
+// A solver plan written as history steps, so that it can be replayed like any other run
+pub fn plan_to_steps(plan: &[JobRecord]) -> Vec<StepRecord> {
+    plan.iter()
+        .map(|record| StepRecord::Job(record.clone()))
+        .collect()
+}
+
+// Replay both runs through the rules engine and compare them day by day.
+// Once one of the runs has ended, its last day is compared against the rest of the other one.
+// Each run rolls with its own seed, so the ghost plays out exactly as it did when it was recorded.
+pub fn compare_runs(current: &[StepRecord], current_seed: u64, ghost: &[StepRecord], ghost_seed: u64, scenario: &Scenario) -> Result<Vec<GhostDay>, String> {
+    let current_days = resources_by_day(current, current_seed, scenario)?;
+    let ghost_days = resources_by_day(ghost, ghost_seed, scenario)?;
+    let empty = BTreeMap::new();
+    let days = current_days.len().max(ghost_days.len());
+    let mut comparison = Vec::new();
+    for day in 0..days {
+        let current_resources = current_days.get(day).or(current_days.last()).unwrap_or(&empty);
+        let ghost_resources = ghost_days.get(day).or(ghost_days.last()).unwrap_or(&empty);
+        let mut differences = Vec::new();
+        for resource in current_resources.keys().chain(ghost_resources.keys()) {
+            if differences.iter().any(|(seen, _difference)| seen == resource) {
+                continue;
+            }
+            let difference = subtract(&current_resources.get(resource).cloned().unwrap_or_default(), &ghost_resources.get(resource).cloned().unwrap_or_default());
+            if difference != quantity(0) {
+                differences.push((*resource, difference));
+            }
+        }
+        differences.sort();
+        let more = differences.iter().any(|(_resource, difference)| *difference > quantity(0));
+        let less = differences.iter().any(|(_resource, difference)| *difference < quantity(0));
+        let status = match (more, less) {
+            (false, false) => GhostStatus::Even,
+            (true, false) => GhostStatus::Ahead,
+            (false, true) => GhostStatus::Behind,
+            (true, true) => GhostStatus::Mixed,
+        };
+        comparison.push(GhostDay {
+            day,
+            differences,
+            status,
+        });
+    }
+    Ok(comparison)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::job::JobId;
+    use crate::core::scenario::facility;
+
+    fn record(id: JobId) -> JobRecord {
+        JobRecord {
+            id,
+            chosen_alternative: None,
+        }
+    }
+
+    #[test]
+    fn racing_a_plan_on_the_facility() {
+        let scenario = facility();
+        scenario.activate();
+        let ghost = plan_to_steps(&[record(JobId::FragmentCatch), record(JobId::FragmentCatch), record(JobId::FragmentCatch), record(JobId::DayDreamCraft)]);
+
+        // The same jobs on the same seed stay even all the way
+        let comparison = compare_runs(&ghost, 1, &ghost, 1, &scenario).unwrap();
+        assert_eq!(comparison.len(), 5);
+        assert!(comparison.iter().all(|day| day.status == GhostStatus::Even && day.differences.is_empty()));
+
+        // Crafting one catch too early fails, which leaves the run behind and then short of the ghost's day dream
+        let current = vec![StepRecord::Repeat(record(JobId::FragmentCatch), 2), StepRecord::Job(record(JobId::DayDreamCraft))];
+        let comparison = compare_runs(&current, 1, &ghost, 1, &scenario).unwrap();
+        let statuses = comparison.iter().map(|day| day.status).collect::<Vec<_>>();
+        assert_eq!(statuses, vec![GhostStatus::Even, GhostStatus::Even, GhostStatus::Even, GhostStatus::Behind, GhostStatus::Mixed]);
+        assert_eq!(comparison[4].differences, vec![(Resource::Fragment, quantity(2)), (Resource::DayDream, quantity(-1))]);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::core::amount::Amount;
+use crate::core::locale::job_name;
+use crate::core::job::{Job, JobId};
+use crate::core::resource::Resource;
+
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq)]
+pub enum GraphNode {
+    Resource(Resource),
+    Job(JobId),
+}
+
+pub struct GraphEdge {
+    pub from: GraphNode,
+    pub to: GraphNode,
+    pub label: String,
+}
+
+// Resources and jobs as nodes, with an edge for every resource a job uses or produces
+pub struct RecipeGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl GraphNode {
+    pub fn label(&self, jobs: &[Job]) -> String {
+        match self {
+            GraphNode::Resource(resource) => resource.to_string(),
+            GraphNode::Job(job_id) => jobs.iter()
+                .find(|job| job.id == *job_id)
+                .map(|job| job_name(job).to_string())
+                .unwrap_or_else(|| format!("{:?}", job_id)),
+        }
+    }
+    // Identifier that is safe to use in Graphviz
+    pub fn dot_id(&self) -> String {
+        match self {
+            GraphNode::Resource(resource) => format!("resource_{:?}", resource),
+            GraphNode::Job(job_id) => format!("job_{:?}", job_id),
+        }
+    }
+}
+
+// Does the resource flow into the job, rather than out of it?
+fn is_input(amount: &Amount) -> bool {
+    match amount {
+        Amount::Gain(_) => false,
+        Amount::GainX(_) => false,
+        Amount::Set(_) => false,
+        Amount::Chance(_, _) => false,
+        Amount::Between(_, _) => false,
+        Amount::Spend(_) => true,
+        Amount::SpendX(_) => true,
+        Amount::Catalyst(_) => true,
+        Amount::CatalystX(_) => true,
+        Amount::Repair(_) => true,
+        Amount::AtMost(_) => true,
+        Amount::Forbid => true,
+    }
+}
+
+pub fn recipe_graph(jobs: &[Job]) -> RecipeGraph {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    for job in jobs.iter() {
+        let job_node = GraphNode::Job(job.id);
+        if !nodes.contains(&job_node) {
+            nodes.push(job_node);
+        }
+        let alternatives = job.start_deltas.len().max(job.end_deltas.len());
+        for deltas in [&job.start_deltas, &job.end_deltas] {
+            for (alternative_index, delta) in deltas.iter().enumerate() {
+                for (resource, amount) in delta.iter() {
+                    let resource_node = GraphNode::Resource(*resource);
+                    if !nodes.contains(&resource_node) {
+                        nodes.push(resource_node);
+                    }
+                    let label = if alternatives > 1 {
+                        format!("{} (alt {})", amount, alternative_index + 1)
+                    } else {
+                        amount.to_string()
+                    };
+                    let (from, to) = if is_input(amount) {
+                        (resource_node, job_node)
+                    } else {
+                        (job_node, resource_node)
+                    };
+                    edges.push(GraphEdge {
+                        from,
+                        to,
+                        label,
+                    });
+                }
+            }
+        }
+    }
+    nodes.sort();
+    RecipeGraph {
+        nodes,
+        edges,
+    }
+}
+
+impl RecipeGraph {
+    pub fn to_dot(&self, jobs: &[Job]) -> String {
+        let mut dot = String::from("digraph recipes {\n    rankdir=LR;\n");
+        for node in self.nodes.iter() {
+            let shape = match node {
+                GraphNode::Resource(_) => "ellipse",
+                GraphNode::Job(_) => "box",
+            };
+            dot.push_str(&format!("    {} [label=\"{}\", shape={}];\n", node.dot_id(), node.label(jobs).replace('"', "\\\""), shape));
+        }
+        for edge in self.edges.iter() {
+            dot.push_str(&format!("    {} -> {} [label=\"{}\"];\n", edge.from.dot_id(), edge.to.dot_id(), edge.label.replace('"', "\\\"")));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+use crate::core::quantity::to_count;
+use crate::core::resource::{attributes, Resource, ResourceSet};
+
+// Remaining uses for every held unit of a resource that wears out, sorted so the most worn unit is first
+pub type DurabilitySet = BTreeMap<Resource, Vec<i64>>;
+
+// Make sure there is exactly one durability entry per held unit of each resource that wears out.
+// New units come in at full durability, and units that are spent are taken from the most worn end.
+pub fn sync_units(durability: &mut DurabilitySet, resources: &ResourceSet) {
+    for (resource, attribute) in attributes().iter() {
+        if let Some(full_durability) = attribute.durability {
+            let held = resources.get(resource).map(|amount| to_count(amount).max(0) as usize).unwrap_or(0);
+            let units = durability.entry(*resource).or_default();
+            units.sort();
+            if units.len() > held {
+                let surplus = units.len() - held;
+                units.drain(0..surplus);
+            }
+            while units.len() < held {
+                units.push(full_durability);
+            }
+        }
+    }
+    durability.retain(|_resource, units| !units.is_empty());
+}
+
+// Use up one use on `count` units, starting with the most worn ones.
+// Units that run out of uses break and are removed. Returns how many units broke.
+pub fn wear(durability: &mut DurabilitySet, resource: &Resource, count: i64) -> i64 {
+    let mut broken = 0;
+    if let Some(units) = durability.get_mut(resource) {
+        for unit in units.iter_mut().take(count.max(0) as usize) {
+            *unit -= 1;
+            if *unit <= 0 {
+                broken += 1;
+            }
+        }
+        units.retain(|unit| *unit > 0);
+        units.sort();
+    }
+    broken
+}
+
+// Whether any unit of the resource has been used since it was new or repaired
+pub fn is_worn(durability: &DurabilitySet, resource: &Resource) -> bool {
+    let full_durability = match attributes().get(resource).and_then(|attribute| attribute.durability) {
+        Some(full_durability) => full_durability,
+        None => return false,
+    };
+    durability.get(resource).map(|units| units.iter().any(|unit| *unit < full_durability)).unwrap_or(false)
+}
+
+// Restore `count` units to full durability, starting with the most worn ones.
+// Returns true if any unit was actually repaired.
+pub fn repair(durability: &mut DurabilitySet, resource: &Resource, count: i64) -> bool {
+    let full_durability = match attributes().get(resource).and_then(|attribute| attribute.durability) {
+        Some(full_durability) => full_durability,
+        None => return false,
+    };
+    let mut repaired = false;
+    if let Some(units) = durability.get_mut(resource) {
+        for unit in units.iter_mut().take(count.max(0) as usize) {
+            if *unit < full_durability {
+                *unit = full_durability;
+                repaired = true;
+            }
+        }
+        units.sort();
+    }
+    repaired
+}
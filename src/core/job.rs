@@ -1,5 +1,12 @@
-use strange_facility::jobs::{DeltaOutput, DeltaOutputStatus, ResourceSet};
+use serde::{Deserialize, Serialize};
 use crate::core::amount::Amount;
+use crate::core::custom::CustomId;
+use crate::core::effect::{affected_deltas, grant, tick, EffectId, EffectSet};
+use crate::core::locale::{fill, resource_name, Text};
+use crate::core::quantity::{add, divide, format_number, quantity, scale, subtract, to_count};
+use crate::core::inventory::{is_worn, repair, sync_units, wear, DurabilitySet};
+use crate::core::random::Roller;
+use crate::core::upgrade::JobModifier;
 use crate::core::resource::{attributes, DeltaOutput, DeltaOutputStatus, Resource, ResourceSet};
 
 #[derive(Clone, Debug)]
@@ -18,12 +25,41 @@ pub struct Job {
     pub total_time_slots: usize,
     // Other jobs with the same id are considered to be the exact same
     pub id: JobId,
+    // Which alternative of the end deltas the player pinned. None picks the first one whose requirements are met
+    pub chosen_alternative: Option<usize>,
+    // What doing this job changes about other jobs for the rest of the run. A job with any is an upgrade.
+    pub modifiers: Vec<JobModifier>,
+    // Effects that doing this job sets off, with how many time slots they last
+    pub effects: Vec<(EffectId, usize)>,
+}
+
+impl Job {
+    pub fn is_upgrade(&self) -> bool {
+        !self.modifiers.is_empty()
+    }
+
+    // Step the pinned alternative through every alternative and then back to picking automatically
+    pub fn cycle_alternative(&mut self) {
+        let alternatives = self.end_deltas.len();
+        if alternatives < 2 {
+            self.chosen_alternative = None;
+            return;
+        }
+        self.chosen_alternative = match self.chosen_alternative {
+            None => Some(0),
+            Some(index) if index + 1 < alternatives => Some(index + 1),
+            Some(_) => None,
+        };
+    }
 }
 
 pub struct JobOutput {
     pub main_output: DeltaOutput,
     pub upkeep_outputs: Vec<(Resource, DeltaOutput)>,
     pub resources_after: ResourceSet,
+    pub durability_after: DurabilitySet,
+    // Effects still active once the job is done
+    pub effects_after: EffectSet,
 }
 
 impl JobOutput {
@@ -41,19 +77,8 @@ impl JobOutput {
         }
         true
     }
-    pub fn delta_index(&self) -> usize {
-        self.main_output.delta_index()
-    }
-
-    pub fn failing_resources(&self) -> Vec<Resource> {
-        let mut failing_resources = self.main_output.failing_resources();
-        for (_upkeep_resource, upkeep_output) in self.upkeep_outputs.iter() {
-            failing_resources.append(&mut upkeep_output.failing_resources());
-        }
-        failing_resources
-    }
     pub fn is_mergeable(&self, other: &Self) -> bool {
-        return (self.is_ok() && other.is_ok()) || (!self.is_ok() && !other.is_ok());
+        (self.is_ok() && other.is_ok()) || (!self.is_ok() && !other.is_ok())
     }
     pub fn get_changed_resources(&self) -> Vec<Resource> {
         let mut total = Vec::new();
@@ -65,39 +90,52 @@ impl JobOutput {
     }
 }
 
-pub fn apply_job(orig_resources: ResourceSet, job: &Job) -> Result<JobOutput, String> {
+// The roller decides how any uncertain amounts turn out.
+// The effects active when the job starts change what it yields, and the job's own effects start once it is done.
+pub fn apply_job(orig_resources: ResourceSet, orig_durability: DurabilitySet, orig_effects: EffectSet, job: &Job, roller: &mut Roller) -> Result<JobOutput, String> {
     let mut upkeep_outputs = Vec::new();
-    let main_output = apply_deltas(orig_resources, &job.end_deltas, 1)?;
+    let end_deltas = affected_deltas(&orig_effects, job.id, &job.end_deltas);
+    let main_output = apply_deltas(orig_resources, orig_durability, &end_deltas, 1, job.chosen_alternative, roller)?;
     let mut resources = main_output.resources_after.clone();
-    for (current_resource, attribute) in attributes() {
-        if let Some(num) = resources.get(&current_resource) {
-            let num = *num;
-            if num > 0 {
-                let delta_output = apply_deltas(resources, &attribute.upkeep, num * 1)?;
+    let mut durability = main_output.durability_after.clone();
+    for (current_resource, attribute) in attributes().iter() {
+        if let Some(num) = resources.get(current_resource) {
+            let num = to_count(num);
+            if num > 0 && !attribute.upkeep.is_empty() {
+                let delta_output = apply_deltas(resources, durability, &attribute.upkeep, num, None, roller)?;
                 resources = delta_output.resources_after.clone();
-                upkeep_outputs.push((current_resource, delta_output));
+                durability = delta_output.durability_after.clone();
+                upkeep_outputs.push((*current_resource, delta_output));
             }
         }
     }
-    Ok(JobOutput {
+    let mut output = JobOutput {
         main_output,
         upkeep_outputs,
         resources_after: resources,
-    })
+        durability_after: durability,
+        effects_after: orig_effects,
+    };
+    // A failed job takes no time, so its effects stay as they were
+    if output.is_ok() {
+        tick(&mut output.effects_after, job.total_time_slots);
+        grant(&mut output.effects_after, &job.effects);
+    }
+    Ok(output)
 }
 
-pub fn apply_deltas(mut resources: ResourceSet, deltas: &Vec<Vec<(Resource, Amount)>>, multiplier: i64) -> Result<DeltaOutput, String> {
+pub fn apply_deltas(mut resources: ResourceSet, mut durability: DurabilitySet, deltas: &[Vec<(Resource, Amount)>], multiplier: i64, chosen_alternative: Option<usize>, roller: &mut Roller) -> Result<DeltaOutput, String> {
     if multiplier == 0 {
         return Err("Zero multiplier".to_string());
     }
     if multiplier < 0 {
         return Err("Negative multiplier".to_string());
     }
-    let deltas = deltas.into_iter()
+    let deltas = deltas.iter()
         .map(|delta| {
-            delta.into_iter()
+            delta.iter()
                 .map(|(delta_resource, delta_amount)| {
-                    (delta_resource.clone(), delta_amount.multiply((multiplier).clone()))
+                    (*delta_resource, delta_amount.multiply(multiplier))
                 })
                 .collect::<Vec<_>>()
         })
@@ -109,29 +147,39 @@ pub fn apply_deltas(mut resources: ResourceSet, deltas: &Vec<Vec<(Resource, Amou
     let mut changed_resources = Vec::new();
     let mut delta_index = 0;
     let mut delta = None;
-    for (new_delta_index, current_delta) in deltas.iter().enumerate() {
+    if let Some(chosen_index) = chosen_alternative {
+        if chosen_index >= deltas.len() {
+            errors.push(fill(Text::MissingAlternative, &[&(chosen_index + 1), &deltas.len()]));
+        }
+    }
+    let candidates = deltas.iter()
+        .enumerate()
+        .filter(|(index, _delta)| chosen_alternative.map(|chosen_index| chosen_index == *index).unwrap_or(true));
+    for (new_delta_index, current_delta) in candidates {
         errors.clear();
         failing_resources.clear();
         delta_index = new_delta_index;
         delta = Some(current_delta);
         for (resource, amount) in current_delta.iter() {
-            let current_amount = resources.entry(resource.clone()).or_insert(0);
+            let current_amount = resources.entry(*resource).or_default();
             match amount {
                 Amount::Gain(_) => {}
                 Amount::GainX(_) => {}
+                Amount::Chance(_, _) => {}
+                Amount::Between(_, _) => {}
                 Amount::Spend(delta) |
                 Amount::Catalyst(delta) => {
-                    if delta > current_amount {
-                        errors.push(format!("Not enough {}", resource));
-                        failing_resources.push(resource.clone())
+                    if quantity(*delta) > *current_amount {
+                        errors.push(fill(Text::NotEnough, &[&resource_name(resource, 0)]));
+                        failing_resources.push(*resource)
                     }
                 }
                 Amount::SpendX(delta) |
                 Amount::CatalystX(delta) => {
-                    if *current_amount < *delta {
-                        failing_resources.push(resource.clone());
+                    if *current_amount < quantity(*delta) {
+                        failing_resources.push(*resource);
                     }
-                    if let Some(new_x) = current_amount.checked_div(delta.clone()) {
+                    if let Some(new_x) = divide(current_amount, *delta) {
                         match &x {
                             Some(current_x) => {
                                 if new_x < *current_x {
@@ -143,52 +191,117 @@ pub fn apply_deltas(mut resources: ResourceSet, deltas: &Vec<Vec<(Resource, Amou
                             }
                         }
                     } else {
-                        if delta < current_amount {
+                        if quantity(*delta) < *current_amount {
                             errors.push(format!("This button is configured incorrectly, with a zero quantity for resource {:?}.", current_amount));
                         }
                     }
                 }
+                Amount::AtMost(limit) => {
+                    if *current_amount > quantity(*limit) {
+                        errors.push(fill(Text::TooMany, &[&resource_name(resource, 0), &format_number(*limit)]));
+                        failing_resources.push(*resource)
+                    }
+                }
+                Amount::Forbid => {
+                    if *current_amount > quantity(0) {
+                        errors.push(fill(Text::NoneAllowed, &[&resource_name(resource, 0)]));
+                        failing_resources.push(*resource)
+                    }
+                }
                 Amount::Set(_) => {}
+                // Repairing nothing would still take the day and whatever else the alternative spends
+                Amount::Repair(_) => {
+                    if !is_worn(&durability, resource) {
+                        errors.push(fill(Text::NothingToRepair, &[&resource_name(resource, 0)]));
+                        failing_resources.push(*resource)
+                    }
+                }
             }
         }
         if errors.is_empty() {
             break;
         }
     }
+    sync_units(&mut durability, &resources);
     if let Some(delta) = delta {
-        for (resource, amount) in delta.into_iter() {
-            let current_resource = resources.entry(resource.clone()).or_insert(0);
+        // Catalysts only wear out once the whole delta is known to apply
+        let mut worn_catalysts = Vec::new();
+        for (resource, amount) in delta.iter() {
+            let current_resource = resources.entry(*resource).or_default();
             match amount {
                 Amount::Gain(delta) => {
                     if *delta != 0 {
-                        changed_resources.push(resource.clone());
+                        changed_resources.push(*resource);
                     }
-                    *current_resource += delta;
+                    *current_resource = add(current_resource, &quantity(*delta));
                 }
                 Amount::Spend(delta) => {
                     if *delta != 0 {
-                        changed_resources.push(resource.clone());
+                        changed_resources.push(*resource);
+                    }
+                    *current_resource = subtract(current_resource, &quantity(*delta));
+                }
+                // Only an alternative that applies rolls, so a failed attempt leaves the rolls after it alone
+                Amount::Chance(delta, percent) => {
+                    if errors.is_empty() && roller.chance(*percent) && *delta != 0 {
+                        changed_resources.push(*resource);
+                        *current_resource = add(current_resource, &quantity(*delta));
+                    }
+                }
+                Amount::Between(min, max) => {
+                    if errors.is_empty() {
+                        let delta = roller.between(*min, *max);
+                        if delta != 0 {
+                            changed_resources.push(*resource);
+                        }
+                        *current_resource = add(current_resource, &quantity(delta));
+                    }
+                }
+                Amount::Catalyst(delta) => {
+                    if errors.is_empty() {
+                        worn_catalysts.push((*resource, quantity(*delta)));
                     }
-                    *current_resource -= delta;
                 }
-                Amount::Catalyst(_) => {}
                 Amount::GainX(delta_per) => {
                     if *delta_per != 0 {
-                        changed_resources.push(resource.clone());
+                        changed_resources.push(*resource);
                     }
-                    *current_resource += delta_per * x.unwrap_or(0);
+                    *current_resource = add(current_resource, &scale(&x.clone().unwrap_or_default(), *delta_per));
                 }
                 Amount::SpendX(delta_per) => {
                     if *delta_per != 0 {
-                        changed_resources.push(resource.clone());
+                        changed_resources.push(*resource);
+                    }
+                    *current_resource = subtract(current_resource, &scale(&x.clone().unwrap_or_default(), *delta_per));
+                }
+                Amount::CatalystX(delta_per) => {
+                    if errors.is_empty() {
+                        worn_catalysts.push((*resource, scale(&x.clone().unwrap_or_default(), *delta_per)));
                     }
-                    *current_resource -= delta_per * x.unwrap_or(0);
                 }
-                Amount::CatalystX(_delta_per) => {}
                 Amount::Set(target) => {
-                    changed_resources.push(resource.clone());
-                    *current_resource -= target;
+                    changed_resources.push(*resource);
+                    *current_resource = subtract(current_resource, &quantity(*target));
                 }
+                Amount::Repair(_) => {}
+                Amount::AtMost(_) => {}
+                Amount::Forbid => {}
+            }
+        }
+        sync_units(&mut durability, &resources);
+        for (resource, amount) in delta.iter() {
+            if let Amount::Repair(units) = amount {
+                if errors.is_empty() && repair(&mut durability, resource, *units) {
+                    changed_resources.push(*resource);
+                }
+            }
+        }
+        for (resource, units) in worn_catalysts.into_iter() {
+            let broken = wear(&mut durability, &resource, to_count(&units));
+            if broken > 0 {
+                let current_resource = resources.entry(resource).or_default();
+                *current_resource = subtract(current_resource, &quantity(broken));
+                changed_resources.push(resource);
             }
         }
     }
@@ -216,10 +329,11 @@ pub fn apply_deltas(mut resources: ResourceSet, deltas: &Vec<Vec<(Resource, Amou
         status,
         changed_resources,
         resources_after: resources,
+        durability_after: durability,
     })
 }
 
-#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
 pub enum JobId {
     FragmentCatch,
     DayDreamCraft,
@@ -230,9 +344,17 @@ pub enum JobId {
     DreamUse,
     ComfortDreamCraft,
     ComfortDreamSell,
+    ComfortDreamUse,
     NightmareCraft,
     NightmareSell,
+    BottleRepair,
+    NetUpgrade,
+    BottleRackUpgrade,
     Retire,
+    // What the player starts with, which every scenario has as its first job
+    StartingResources,
+    // Defined by a scenario file rather than in `core::catalogue`, see `core::custom`
+    Custom(CustomId),
 }
 
 pub fn starting_resources() -> Job{
@@ -243,42 +365,61 @@ pub fn starting_resources() -> Job{
         end_deltas: vec![],
         saved: false,
         total_time_slots: 0,
-        id: JobId::FragmentCatch,
+        id: JobId::StartingResources,
+        chosen_alternative: None,
+        modifiers: vec![],
+        effects: vec![],
     }
 }
 
-pub fn create_job(job_id: JobId) -> Job {
-    Job {
-        short_text: "",
-        long_text: "",
-        start_deltas: vec![],
-        end_deltas: vec![],
-        saved: false,
-        total_time_slots: 0,
-        id: JobId::FragmentCatch,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::random::Rng;
+    use crate::core::scenario::facility;
+
+    #[test]
+    fn failed_alternative_doesnt_roll() {
+        let deltas = vec![
+            vec![(Resource::Coin, Amount::Spend(5)), (Resource::Fragment, Amount::Between(1, 100)), (Resource::ScaryFragment, Amount::Chance(1, 50))],
+        ];
+        let mut roller = Roller::Seeded(Rng::new(3));
+        let output = apply_deltas(ResourceSet::new(), DurabilitySet::new(), &deltas, 1, None, &mut roller).unwrap();
+        assert!(!output.is_ok());
+
+        // The roller is where it started, so whatever rolls next isn't shifted by the failure
+        let mut untouched = Roller::Seeded(Rng::new(3));
+        assert_eq!(roller.between(0, 1 << 40), untouched.between(0, 1 << 40));
     }
-    // match job_id {
-    //     JobId::FragmentCatch => {Job {
-    //         short_text: "",
-    //         long_text: "",
-    //         start_deltas: vec![],
-    //         end_deltas: vec![],
-    //         saved: false,
-    //         total_time_slots: 0,
-    //         id: JobId::FragmentCatch,
-    //     };}
-    //     JobId::DayDreamCraft => {}
-    //     JobId::DayDreamSell => {}
-    //     JobId::BottleBuy => {}
-    //     JobId::DreamCraft => {}
-    //     JobId::DreamSell => {}
-    //     JobId::DreamUse => {}
-    //     JobId::ComfortDreamCraft => {}
-    //     JobId::ComfortDreamSell => {}
-    //     JobId::NightmareCraft => {}
-    //     JobId::NightmareSell => {}
-    //     JobId::Retire => {}
-    // }
-}
 
-pub const WIN_JOB_ID: JobId = JobId::Retire;
+    #[test]
+    fn failed_alternative_gains_nothing_uncertain() {
+        let deltas = vec![
+            vec![(Resource::Coin, Amount::Spend(5)), (Resource::Fragment, Amount::Between(2, 4)), (Resource::ScaryFragment, Amount::Chance(1, 100))],
+        ];
+        let output = apply_deltas(ResourceSet::new(), DurabilitySet::new(), &deltas, 1, None, &mut Roller::Lowest).unwrap();
+        assert!(!output.is_ok());
+        assert_eq!(output.resources_after.get(&Resource::Fragment).cloned().unwrap_or_default(), quantity(0));
+        assert_eq!(output.resources_after.get(&Resource::ScaryFragment).cloned().unwrap_or_default(), quantity(0));
+        assert!(!output.changed_resources.contains(&Resource::Fragment));
+    }
+
+    #[test]
+    fn repairing_needs_a_worn_unit() {
+        let scenario = facility();
+        scenario.activate();
+        let repair_job = scenario.job(JobId::BottleRepair).unwrap();
+        let resources = ResourceSet::from([(Resource::Coin, quantity(5)), (Resource::GlassBottle, quantity(1))]);
+        let mut durability = DurabilitySet::new();
+        sync_units(&mut durability, &resources);
+
+        // A new bottle has nothing to repair, so the job fails rather than take a coin for it
+        let output = apply_job(resources.clone(), durability.clone(), EffectSet::new(), &repair_job, &mut Roller::Lowest).unwrap();
+        assert!(!output.is_ok());
+
+        wear(&mut durability, &Resource::GlassBottle, 1);
+        let output = apply_job(resources, durability, EffectSet::new(), &repair_job, &mut Roller::Lowest).unwrap();
+        assert!(output.is_ok());
+        assert_eq!(output.resources_after.get(&Resource::Coin), Some(&quantity(4)));
+    }
+}
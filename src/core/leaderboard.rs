@@ -0,0 +1,364 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use serde::{Deserialize, Serialize};
+use crate::core::daily::daily_date;
+use crate::core::editor::is_playtest;
+use crate::core::locale::{fill, Text};
+use crate::core::record::RunRecord;
+
+// How many runs are kept for each scenario
+pub const KEPT_RUNS: usize = 10;
+
+pub type ScoreFuture<T> = Pin<Box<dyn Future<Output = Result<T, String>>>>;
+
+// Somewhere finished runs can be submitted to, and the best ones read back from
+pub trait ScoreBackend {
+    // The seed for a new run. A leaderboard only takes runs with a seed it handed out, so players can't pick one with lucky rolls.
+    fn issue_seed(&self, scenario: &str) -> ScoreFuture<u64>;
+    fn submit(&self, run: RunRecord) -> ScoreFuture<()>;
+    // The best runs for a scenario, fewest days first
+    fn best_runs(&self, scenario: &str) -> ScoreFuture<Vec<RunRecord>>;
+}
+
+// Everyone plays a daily challenge on the same seed, and playtests are never submitted,
+// so only other runs need a seed the leaderboard handed out
+pub fn needs_issued_seed(scenario: &str) -> bool {
+    daily_date(scenario).is_none() && !is_playtest(scenario)
+}
+
+// Fewest days first. Runs with the same days keep the order they came in, so the first to get there stays ahead.
+pub fn sort_runs(runs: &mut Vec<RunRecord>) {
+    runs.sort_by_key(|run| run.days);
+    runs.truncate(KEPT_RUNS);
+}
+
+// The best runs of every scenario, ranked separately
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Leaderboard {
+    pub runs: BTreeMap<String, Vec<RunRecord>>,
+}
+
+impl Leaderboard {
+    pub fn add_run(&mut self, run: RunRecord) {
+        let runs = self.runs.entry(run.scenario.clone()).or_default();
+        runs.push(run);
+        sort_runs(runs);
+    }
+
+    pub fn best_runs(&self, scenario: &str) -> Vec<RunRecord> {
+        self.runs.get(scenario).cloned().unwrap_or_default()
+    }
+}
+
+// How an HttpBackend reaches its server. The browser sends requests with fetch, and tests answer them in process.
+pub trait HttpClient {
+    fn send(&self, request: HttpRequest) -> ScoreFuture<HttpResponse>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    pub query: Vec<(String, String)>,
+    // JSON, for the requests that carry any
+    pub body: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn ok(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+// Sends runs to a leaderboard server, which is expected to re-verify the history before accepting them.
+// Seeds come from POSTing to `{endpoint}/seeds?scenario=...`, and the server rejects runs with a seed it didn't hand out.
+// Runs are POSTed as JSON to `{endpoint}/runs`, and read back from `{endpoint}/runs?scenario=...`.
+pub struct HttpBackend<C: HttpClient> {
+    pub endpoint: String,
+    pub client: C,
+}
+
+impl<C: HttpClient> HttpBackend<C> {
+    fn request(&self, method: HttpMethod, path: &str, scenario: Option<&str>, body: Option<String>) -> ScoreFuture<HttpResponse> {
+        self.client.send(HttpRequest {
+            method,
+            url: format!("{}/{}", self.endpoint.trim_end_matches('/'), path),
+            query: scenario.map(|scenario| ("scenario".to_string(), scenario.to_string())).into_iter().collect(),
+            body,
+        })
+    }
+}
+
+impl<C: HttpClient> ScoreBackend for HttpBackend<C> {
+    fn issue_seed(&self, scenario: &str) -> ScoreFuture<u64> {
+        let response = self.request(HttpMethod::Post, "seeds", Some(scenario), None);
+        Box::pin(async move {
+            let response = response.await?;
+            if !response.ok() {
+                return Err(fill(Text::SeedRequestFailed, &[&response.status]));
+            }
+            serde_json::from_str::<u64>(&response.body).map_err(|error| error.to_string())
+        })
+    }
+    fn submit(&self, run: RunRecord) -> ScoreFuture<()> {
+        let body = match serde_json::to_string(&run) {
+            Ok(body) => body,
+            Err(error) => return Box::pin(async move { Err(error.to_string()) }),
+        };
+        let response = self.request(HttpMethod::Post, "runs", None, Some(body));
+        Box::pin(async move {
+            let response = response.await?;
+            if response.ok() {
+                Ok(())
+            } else {
+                Err(fill(Text::RunRejected, &[&response.status, &response.body]))
+            }
+        })
+    }
+    fn best_runs(&self, scenario: &str) -> ScoreFuture<Vec<RunRecord>> {
+        let response = self.request(HttpMethod::Get, "runs", Some(scenario), None);
+        Box::pin(async move {
+            let response = response.await?;
+            if !response.ok() {
+                return Err(fill(Text::LeaderboardReadFailed, &[&response.status]));
+            }
+            let mut runs = serde_json::from_str::<Vec<RunRecord>>(&response.body).map_err(|error| error.to_string())?;
+            sort_runs(&mut runs);
+            Ok(runs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::task::{Context, Poll, Waker};
+    use crate::core::random::Rng;
+
+    // Keeps the leaderboard for as long as it lives, which is what a server does apart from storing it
+    pub struct MemoryBackend {
+        pub leaderboard: RefCell<Leaderboard>,
+        // Seeds handed out for each scenario that no run has used yet
+        pub issued_seeds: RefCell<BTreeMap<String, Vec<u64>>>,
+        rng: RefCell<Rng>,
+    }
+
+    impl MemoryBackend {
+        pub fn new(seed: u64) -> MemoryBackend {
+            MemoryBackend {
+                leaderboard: RefCell::new(Leaderboard::default()),
+                issued_seeds: RefCell::new(BTreeMap::new()),
+                rng: RefCell::new(Rng::new(seed)),
+            }
+        }
+
+        // Each seed is good for one run
+        fn take_seed(&self, run: &RunRecord) -> Result<(), String> {
+            if !needs_issued_seed(&run.scenario) {
+                return Ok(());
+            }
+            let mut issued_seeds = self.issued_seeds.borrow_mut();
+            let seeds = issued_seeds.entry(run.scenario.clone()).or_default();
+            match seeds.iter().position(|seed| *seed == run.seed) {
+                Some(index) => {
+                    seeds.remove(index);
+                    Ok(())
+                }
+                None => Err(format!("The seed {} wasn't handed out for {}, or was used already", run.seed, run.scenario)),
+            }
+        }
+    }
+
+    impl ScoreBackend for MemoryBackend {
+        fn issue_seed(&self, scenario: &str) -> ScoreFuture<u64> {
+            let seed = self.rng.borrow_mut().next_u64();
+            self.issued_seeds.borrow_mut().entry(scenario.to_string()).or_default().push(seed);
+            Box::pin(async move {
+                Ok(seed)
+            })
+        }
+        fn submit(&self, run: RunRecord) -> ScoreFuture<()> {
+            let result = self.take_seed(&run).map(|()| self.leaderboard.borrow_mut().add_run(run));
+            Box::pin(async move {
+                result
+            })
+        }
+        fn best_runs(&self, scenario: &str) -> ScoreFuture<Vec<RunRecord>> {
+            let runs = self.leaderboard.borrow().best_runs(scenario);
+            Box::pin(async move {
+                Ok(runs)
+            })
+        }
+    }
+
+    // The backends here never wait on anything, so one poll finishes them
+    fn finish<T>(mut future: ScoreFuture<T>) -> Result<T, String> {
+        match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(result) => result,
+            Poll::Pending => panic!("The backend is waiting on something"),
+        }
+    }
+
+    fn run(scenario: &str, player: &str, days: usize, seed: u64) -> RunRecord {
+        RunRecord {
+            scenario: scenario.to_string(),
+            scenario_version: 1,
+            player: player.to_string(),
+            days,
+            history: vec![],
+            seed,
+            packs: vec![],
+        }
+    }
+
+    // Play a run on a seed from the backend and submit it
+    fn submit(backend: &MemoryBackend, scenario: &str, player: &str, days: usize) {
+        let seed = finish(backend.issue_seed(scenario)).unwrap();
+        finish(backend.submit(run(scenario, player, days, seed))).unwrap();
+    }
+
+    fn players(runs: &[RunRecord]) -> Vec<&str> {
+        runs.iter().map(|run| run.player.as_str()).collect()
+    }
+
+    const SERVER: &str = "http://leaderboard.test/api";
+
+    // Answers requests the way a leaderboard server would, and keeps them to look at
+    struct StandInServer {
+        backend: MemoryBackend,
+        requests: RefCell<Vec<HttpRequest>>,
+    }
+
+    impl StandInServer {
+        fn answer(&self, request: &HttpRequest) -> HttpResponse {
+            let respond = |result: Result<String, String>| match result {
+                Ok(body) => HttpResponse { status: 200, body },
+                Err(reason) => HttpResponse { status: 409, body: reason },
+            };
+            let scenario = request.query.iter().find(|(key, _value)| key == "scenario").map(|(_key, value)| value.as_str());
+            let path = request.url.strip_prefix(SERVER);
+            match (request.method, path, scenario, &request.body) {
+                (HttpMethod::Post, Some("/seeds"), Some(scenario), None) => {
+                    respond(finish(self.backend.issue_seed(scenario)).map(|seed| seed.to_string()))
+                }
+                (HttpMethod::Post, Some("/runs"), None, Some(body)) => match serde_json::from_str::<RunRecord>(body) {
+                    Ok(run) => respond(finish(self.backend.submit(run)).map(|()| String::new())),
+                    Err(error) => HttpResponse { status: 400, body: error.to_string() },
+                },
+                (HttpMethod::Get, Some("/runs"), Some(scenario), None) => {
+                    respond(finish(self.backend.best_runs(scenario)).map(|runs| serde_json::to_string(&runs).unwrap()))
+                }
+                _ => HttpResponse { status: 404, body: String::new() },
+            }
+        }
+    }
+
+    impl HttpClient for &StandInServer {
+        fn send(&self, request: HttpRequest) -> ScoreFuture<HttpResponse> {
+            let response = self.answer(&request);
+            self.requests.borrow_mut().push(request);
+            Box::pin(async move {
+                Ok(response)
+            })
+        }
+    }
+
+    #[test]
+    fn fewest_days_first_and_ties_keep_their_order() {
+        let backend = MemoryBackend::new(1);
+        for (player, days) in [("b", 40), ("a", 30), ("c", 40), ("d", 25)] {
+            submit(&backend, "facility", player, days);
+        }
+        assert_eq!(players(&finish(backend.best_runs("facility")).unwrap()), vec!["d", "a", "b", "c"]);
+    }
+
+    #[test]
+    fn only_the_best_runs_are_kept() {
+        let backend = MemoryBackend::new(1);
+        for days in (1..=KEPT_RUNS + 2).rev() {
+            submit(&backend, "facility", &days.to_string(), days);
+        }
+        let runs = finish(backend.best_runs("facility")).unwrap();
+        assert_eq!(runs.len(), KEPT_RUNS);
+        assert_eq!(runs.iter().map(|run| run.days).collect::<Vec<_>>(), (1..=KEPT_RUNS).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn scenarios_are_ranked_separately() {
+        let backend = MemoryBackend::new(1);
+        submit(&backend, "facility", "a", 50);
+        submit(&backend, "generated-1-3x3", "b", 20);
+        submit(&backend, "facility", "c", 45);
+        assert_eq!(players(&finish(backend.best_runs("facility")).unwrap()), vec!["c", "a"]);
+        assert_eq!(players(&finish(backend.best_runs("generated-1-3x3")).unwrap()), vec!["b"]);
+        assert!(finish(backend.best_runs("facility-daily-2026-10-19")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_seeds_the_backend_handed_out_count() {
+        let backend = MemoryBackend::new(1);
+        let seed = finish(backend.issue_seed("facility")).unwrap();
+        let other_seed = finish(backend.issue_seed("generated-1-3x3")).unwrap();
+        // A seed the player picked, one for another scenario, and one that was used already
+        assert!(finish(backend.submit(run("facility", "a", 30, seed ^ 1))).is_err());
+        assert!(finish(backend.submit(run("facility", "a", 30, other_seed))).is_err());
+        assert!(finish(backend.submit(run("facility", "a", 30, seed))).is_ok());
+        assert!(finish(backend.submit(run("facility", "a", 29, seed))).is_err());
+        // Everyone plays a daily challenge on the same seed
+        assert!(finish(backend.submit(run("facility-daily-2026-10-19", "b", 20, 12345))).is_ok());
+        assert_eq!(players(&finish(backend.best_runs("facility")).unwrap()), vec!["a"]);
+    }
+
+    #[test]
+    fn http_backend_submits_and_reads_back_from_a_server() {
+        let server = StandInServer {
+            backend: MemoryBackend::new(1),
+            requests: RefCell::new(vec![]),
+        };
+        let backend = HttpBackend {
+            endpoint: format!("{}/", SERVER),
+            client: &server,
+        };
+        let seed = finish(backend.issue_seed("facility")).unwrap();
+        finish(backend.submit(run("facility", "a", 30, seed))).unwrap();
+        // The server's reason for turning a run down reaches the player
+        assert_eq!(
+            finish(backend.submit(run("facility", "b", 20, seed))),
+            Err(fill(Text::RunRejected, &[&409, &format!("The seed {} wasn't handed out for facility, or was used already", seed)])),
+        );
+        assert_eq!(players(&finish(backend.best_runs("facility")).unwrap()), vec!["a"]);
+        let sent = server.requests.borrow().iter()
+            .map(|request| (request.method, request.url.clone(), request.query.clone()))
+            .collect::<Vec<_>>();
+        let scenario = vec![("scenario".to_string(), "facility".to_string())];
+        assert_eq!(sent, vec![
+            (HttpMethod::Post, format!("{}/seeds", SERVER), scenario.clone()),
+            (HttpMethod::Post, format!("{}/runs", SERVER), vec![]),
+            (HttpMethod::Post, format!("{}/runs", SERVER), vec![]),
+            (HttpMethod::Get, format!("{}/runs", SERVER), scenario),
+        ]);
+
+        // A server that doesn't know the paths fails every request
+        let elsewhere = HttpBackend {
+            endpoint: "http://leaderboard.test/other".to_string(),
+            client: &server,
+        };
+        assert_eq!(finish(elsewhere.issue_seed("facility")), Err(fill(Text::SeedRequestFailed, &[&404])));
+        assert_eq!(finish(elsewhere.best_runs("facility")), Err(fill(Text::LeaderboardReadFailed, &[&404])));
+    }
+}
@@ -0,0 +1,914 @@
+use std::cell::Cell;
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use crate::core::custom::{intern, resource_definition};
+use crate::core::effect::EffectId;
+use crate::core::job::{Job, JobId};
+use crate::core::quantity::{format_quantity, to_count, Quantity};
+use crate::core::resource::Resource;
+
+// Every piece of text the player sees goes through here, so that it can be shown in the chosen language.
+// Texts with `{}` placeholders are filled in order with `fill`.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    English,
+    Swedish,
+}
+
+thread_local! {
+    static CURRENT_LOCALE: Cell<Locale> = const { Cell::new(Locale::English) };
+}
+
+pub fn all_locales() -> Vec<Locale> {
+    vec![
+        Locale::English,
+        Locale::Swedish,
+    ]
+}
+
+pub fn current_locale() -> Locale {
+    CURRENT_LOCALE.with(|locale| locale.get())
+}
+
+pub fn set_locale(locale: Locale) {
+    CURRENT_LOCALE.with(|current| current.set(locale));
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Locale::English => f.write_str("English"),
+            Locale::Swedish => f.write_str("Svenska"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PluralForm {
+    One,
+    Other,
+}
+
+impl Locale {
+    pub fn plural_form(&self, count: i64) -> PluralForm {
+        match self {
+            Locale::English | Locale::Swedish => {
+                if count == 1 { PluralForm::One } else { PluralForm::Other }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Text {
+    // Rules
+    NotEnough,
+    TooMany,
+    NoneAllowed,
+    NothingToRepair,
+    MissingAlternative,
+    Require,
+    SetTo,
+    Repair,
+    AtMost,
+    Forbid,
+    Chance,
+    Between,
+    Expected,
+    StartingResources,
+    DayCount,
+    DaysCount,
+    // Daily challenge
+    DailyChallenge,
+    DailyTitle,
+    DailyBanned,
+    DailyBest,
+    BackToFacility,
+    // Generated scenarios
+    DifficultyEasy,
+    DifficultyNormal,
+    DifficultyHard,
+    DifficultyBrutal,
+    GenerateScenario,
+    GeneratorDepth,
+    GeneratorWidth,
+    GeneratedTitle,
+    GeneratedPar,
+    // Scenario editor
+    ScenarioEditor,
+    BackToGame,
+    NewScenario,
+    Playtest,
+    ExportScenario,
+    ImportScenario,
+    ScenarioName,
+    Resources,
+    Jobs,
+    Id,
+    Name,
+    PluralName,
+    Visible,
+    Row,
+    Upkeep,
+    Description,
+    TimeSlots,
+    WinsTheRun,
+    AddResource,
+    AddJob,
+    AddAlternative,
+    AddChange,
+    AddModifier,
+    NoProblems,
+    FixProblemsFirst,
+    JobIdCombined,
+    // Content packs
+    ContentPacks,
+    LoadPackFile,
+    PackUrl,
+    PackFetchFailed,
+    LoadPackUrl,
+    NoPacks,
+    PackEntry,
+    PacksRestart,
+    // Upgrades
+    AlreadyUpgraded,
+    UpgradeEffects,
+    ModifierMore,
+    ModifierFewer,
+    ModifierSlower,
+    ModifierFaster,
+    UpgradedFrom,
+    NoLongerNeeded,
+    UpgradeDone,
+    // Effects
+    EffectLeft,
+    EffectAfterwards,
+    EffectRule,
+    // Main view
+    Language,
+    NumberFormat,
+    RepeatHint,
+    RepeatCountHint,
+    TotalDaysSpent,
+    Undo,
+    Redo,
+    Recipes,
+    HideRecipes,
+    JournalButton,
+    Shortcuts,
+    MacroName,
+    SaveMacro,
+    PlanPlaceholder,
+    ExportPlan,
+    ImportPlan,
+    GhostBestRun,
+    GhostSolver,
+    Racing,
+    NoGhost,
+    ComparedToGhost,
+    EvenWithGhost,
+    Remove,
+    AlternativeNumber,
+    AlternativeAuto,
+    Uses,
+    ByDay,
+    ByJob,
+    // Ghosts and macros
+    BestRunName,
+    NoFinishedRun,
+    SolverName,
+    SolverFoundNothing,
+    SelectRowsFirst,
+    MacroNeedsName,
+    MacroExists,
+    EmptySelection,
+    // Journal
+    Journal,
+    NothingWritten,
+    Continue,
+    Close,
+    // Keyboard
+    KeyJob,
+    PreviousRow,
+    NextRow,
+    AddOneToRow,
+    RemoveOneFromRow,
+    RemoveRow,
+    ShowShortcuts,
+    KeyboardShortcuts,
+    PressAKey,
+    CtrlKey,
+    ResetToDefaults,
+    // Summary
+    DaysSpent,
+    Actions,
+    Edits,
+    Undos,
+    FailedAttempts,
+    PersonalBest,
+    Par,
+    PeakAndGained,
+    NewPersonalBest,
+    MatchedPersonalBest,
+    BehindPersonalBest,
+    FirstEscape,
+    EscapedIn,
+    ParIs,
+    RunStatistics,
+    ExportSummary,
+    YourName,
+    LeaderboardServer,
+    SubmitToLeaderboard,
+    LeaderboardEntry,
+    Anonymous,
+    SeedTooLate,
+    SeedFromElsewhere,
+    // Plans
+    RepeatsZeroTimes,
+    AlternativeNotANumber,
+    TooFewAlternatives,
+    UnknownJob,
+    PlanLine,
+    // Content packs
+    NotAContentPack,
+    ScenarioOwner,
+    PackOwner,
+    PackNeedsId,
+    PackLoadedTwice,
+    PackResourceOutsideNamespace,
+    PackResourceTaken,
+    PackJobOutsideNamespace,
+    PackJobTaken,
+    PackJobMissing,
+    // Scenario editor
+    ScenarioNeedsName,
+    ResourceHasNoId,
+    DuplicateResourceId,
+    ResourceNeedsNames,
+    ScenarioHasNoJobs,
+    JobHasNoId,
+    DuplicateJobId,
+    JobNeedsName,
+    JobTakesNoTime,
+    AlternativeDoesNothing,
+    EffectForNoTime,
+    WinJobNotAJob,
+    ResourceNeverDefined,
+    // Validation
+    JobOwner,
+    UpkeepOwner,
+    ChanceOutOfRange,
+    RangeBackwards,
+    AmountNotPositive,
+    SpendsXWithoutGain,
+    GainsXWithoutSpend,
+    BreaksBeforeUse,
+    NothingProduces,
+    JobNeverPossible,
+    WinJobNeverReached,
+    UpgradeTargetMissing,
+    UpgradeResourceUnused,
+    UpkeepCycle,
+    // Daily challenges, generated scenarios and scenario names
+    NotADate,
+    NotARealDate,
+    DepthOutOfRange,
+    WidthOutOfRange,
+    TooManyResources,
+    NoWinnableScenario,
+    // Generated jobs and resources
+    GeneratedPlural,
+    GatherJob,
+    GatherDescription,
+    MakeJob,
+    MakeDescription,
+    FinishJob,
+    FinishDescription,
+    UnknownScenario,
+    // Verifying runs
+    PacksCantBeVerified,
+    WrongScenarioVersion,
+    NotTheDailySeed,
+    StepJobMissing,
+    NeverWins,
+    JobFailed,
+    WrongDayCount,
+    // Leaderboard server
+    SeedRequestFailed,
+    RunRejected,
+    LeaderboardReadFailed,
+}
+
+pub fn text(text: Text) -> &'static str {
+    match current_locale() {
+        Locale::English => english(text),
+        Locale::Swedish => swedish(text),
+    }
+}
+
+// The text with each `{}` replaced by the next value
+pub fn fill(text_id: Text, values: &[&dyn Display]) -> String {
+    let mut filled = String::new();
+    let mut values = values.iter();
+    let mut parts = text(text_id).split("{}").peekable();
+    while let Some(part) = parts.next() {
+        filled.push_str(part);
+        if parts.peek().is_some() {
+            if let Some(value) = values.next() {
+                filled.push_str(&value.to_string());
+            }
+        }
+    }
+    filled
+}
+
+// The same text in every language, for texts kept with the data they belong to, such as the story in `core::catalogue`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Localised {
+    pub english: &'static str,
+    pub swedish: &'static str,
+}
+
+pub fn localised(text: &Localised) -> &'static str {
+    match current_locale() {
+        Locale::English => text.english,
+        Locale::Swedish => text.swedish,
+    }
+}
+
+// "1 day" or "3 days"
+pub fn days(count: usize) -> String {
+    let text_id = match current_locale().plural_form(count as i64) {
+        PluralForm::One => Text::DayCount,
+        PluralForm::Other => Text::DaysCount,
+    };
+    fill(text_id, &[&count])
+}
+
+pub fn resource_name(resource: &Resource, count: i64) -> &'static str {
+    let locale = current_locale();
+    // Resources from scenario files have a single name for every language
+    if let Resource::Custom(_) = resource {
+        if let Some(definition) = resource_definition(resource) {
+            return match locale.plural_form(count) {
+                PluralForm::One => intern(&definition.name),
+                PluralForm::Other => intern(&definition.plural_name),
+            };
+        }
+    }
+    match (locale, locale.plural_form(count)) {
+        (Locale::English, PluralForm::One) => match resource {
+            Resource::Coin => "Coin",
+            Resource::Fragment => "Fragment",
+            Resource::DayDream => "Day Dream",
+            Resource::GlassBottle => "Glass bottle",
+            Resource::Dream => "Dream",
+            Resource::SoothingMemory => "Soothing memory",
+            Resource::ComfortDream => "Comfort dream",
+            Resource::ScaryFragment => "Scary fragment",
+            Resource::Nightmare => "Nightmare",
+            Resource::Custom(id) => id.0,
+        },
+        (Locale::English, PluralForm::Other) => match resource {
+            Resource::Coin => "Coins",
+            Resource::Fragment => "Fragments",
+            Resource::DayDream => "Day Dreams",
+            Resource::GlassBottle => "Glass bottles",
+            Resource::Dream => "Dreams",
+            Resource::SoothingMemory => "Soothing memories",
+            Resource::ComfortDream => "Comfort dreams",
+            Resource::ScaryFragment => "Scary fragments",
+            Resource::Nightmare => "Nightmares",
+            Resource::Custom(id) => id.0,
+        },
+        (Locale::Swedish, PluralForm::One) => match resource {
+            Resource::Coin => "Mynt",
+            Resource::Fragment => "Fragment",
+            Resource::DayDream => "Dagdröm",
+            Resource::GlassBottle => "Glasflaska",
+            Resource::Dream => "Dröm",
+            Resource::SoothingMemory => "Lugnande minne",
+            Resource::ComfortDream => "Tröstdröm",
+            Resource::ScaryFragment => "Skrämmande fragment",
+            Resource::Nightmare => "Mardröm",
+            Resource::Custom(id) => id.0,
+        },
+        (Locale::Swedish, PluralForm::Other) => match resource {
+            Resource::Coin => "Mynt",
+            Resource::Fragment => "Fragment",
+            Resource::DayDream => "Dagdrömmar",
+            Resource::GlassBottle => "Glasflaskor",
+            Resource::Dream => "Drömmar",
+            Resource::SoothingMemory => "Lugnande minnen",
+            Resource::ComfortDream => "Tröstdrömmar",
+            Resource::ScaryFragment => "Skrämmande fragment",
+            Resource::Nightmare => "Mardrömmar",
+            Resource::Custom(id) => id.0,
+        },
+    }
+}
+
+pub fn effect_name(effect: EffectId) -> &'static str {
+    match (current_locale(), effect) {
+        (Locale::English, EffectId::ComfortDream) => "Comforted",
+        (Locale::English, EffectId::Nightmare) => "Haunted",
+        (Locale::Swedish, EffectId::ComfortDream) => "Tröstad",
+        (Locale::Swedish, EffectId::Nightmare) => "Hemsökt",
+    }
+}
+
+// "1 Dream" or "3 Dreams"
+pub fn count_of(resource: &Resource, count: &Quantity) -> String {
+    format!("{} {}", format_quantity(count), resource_name(resource, to_count(count)))
+}
+
+// Button text and description of a job. English is written in the job catalogue itself.
+fn job_texts(locale: Locale, job_id: JobId) -> Option<(&'static str, &'static str)> {
+    match locale {
+        Locale::English => None,
+        Locale::Swedish => Some(match job_id {
+            JobId::FragmentCatch => ("Fånga fragment", "Sträck dig in i diset och fånga ett drivande fragment. Ibland följer ett skrämmande med."),
+            JobId::DayDreamCraft => ("Skapa dagdröm", "Väv ihop fragment till en dagdröm."),
+            JobId::DayDreamSell => ("Sälj dagdröm", "Någon vid disken betalar för dagdrömmar."),
+            JobId::BottleBuy => ("Köp glasflaska", "En flaska som hindrar en dröm från att avdunsta."),
+            JobId::DreamCraft => ("Skapa dröm", "Destillera dagdrömmar till en riktig dröm."),
+            JobId::DreamSell => ("Sälj dröm", "Drömmar ger ett bra pris."),
+            JobId::DreamUse => ("Använd dröm", "Sov dig igenom en dröm själv. Den lämnar något efter sig."),
+            JobId::ComfortDreamCraft => ("Skapa tröstdröm", "Lugna en dröm med minnen."),
+            JobId::ComfortDreamSell => ("Sälj tröstdröm", "Tröst är alltid efterfrågad."),
+            JobId::ComfortDreamUse => ("Sov på tröstdröm", "Vila på en tröstdröm. I tre dagar blir varje dröm du skapar två."),
+            JobId::NightmareCraft => ("Skapa mardröm", "Mata en dröm med skrämmande fragment. Den hemsöker dig i tre dagar, och händerna skakar när du fångar fragment."),
+            JobId::NightmareSell => ("Sälj mardröm", "Vissa samlare betalar bra för mardrömmar."),
+            JobId::BottleRepair => ("Laga glasflaska", "Lappa ihop en sprucken flaska."),
+            JobId::NetUpgrade => ("Väv ett finare nät", "Fragmenten slutar glida igenom. Varje fångst ger ett fragment till."),
+            JobId::BottleRackUpgrade => ("Bygg ett flaskställ", "Drömmarna vilar på stället medan de destilleras, så det går åt en glasflaska mindre."),
+            JobId::Retire => ("Gå i pension", "Köp dig fri från anläggningen, om du kan lämna mardrömmarna bakom dig."),
+            JobId::StartingResources | JobId::Custom(_) => return None,
+        }),
+    }
+}
+
+pub fn job_name(job: &Job) -> &'static str {
+    if job.id == JobId::StartingResources {
+        return text(Text::StartingResources);
+    }
+    job_texts(current_locale(), job.id).map(|(short_text, _long_text)| short_text).unwrap_or(job.short_text)
+}
+
+pub fn job_description(job: &Job) -> &'static str {
+    job_texts(current_locale(), job.id).map(|(_short_text, long_text)| long_text).unwrap_or(job.long_text)
+}
+
+// Every name a job is known by, so that plans can be read in any language
+pub fn job_names(job: &Job) -> Vec<&'static str> {
+    let mut names = vec![job.short_text];
+    for locale in all_locales() {
+        if let Some((short_text, _long_text)) = job_texts(locale, job.id) {
+            names.push(short_text);
+        }
+    }
+    names
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::NotEnough => "Not enough {}",
+        Text::TooMany => "Too many {}, at most {} allowed",
+        Text::NoneAllowed => "Can't have any {}",
+        Text::NothingToRepair => "No {} needs repairing",
+        Text::MissingAlternative => "The chosen alternative {} doesn't exist, this job only has {}",
+        Text::Require => "require {}",
+        Text::SetTo => "set to {}",
+        Text::Repair => "repair {}",
+        Text::AtMost => "at most {}",
+        Text::Forbid => "none allowed",
+        Text::Chance => "+{} ({}% chance)",
+        Text::Between => "+{} to {}",
+        Text::Expected => "expected {}, from {} to {}",
+        Text::StartingResources => "Starting resources",
+        Text::DayCount => "{} day",
+        Text::DaysCount => "{} days",
+        Text::DailyChallenge => "Daily challenge",
+        Text::DailyTitle => "Daily challenge for {}",
+        Text::DailyBanned => "{} is closed today",
+        Text::DailyBest => "Your best today: {}",
+        Text::BackToFacility => "Back to the facility",
+        Text::DifficultyEasy => "Easy",
+        Text::DifficultyNormal => "Normal",
+        Text::DifficultyHard => "Hard",
+        Text::DifficultyBrutal => "Brutal",
+        Text::GenerateScenario => "Generate a scenario",
+        Text::GeneratorDepth => "Steps",
+        Text::GeneratorWidth => "Resources per step",
+        Text::GeneratedTitle => "Generated scenario {}",
+        Text::GeneratedPar => "{}, can be won in {}",
+        Text::ScenarioEditor => "Scenario editor",
+        Text::BackToGame => "Back to the game",
+        Text::NewScenario => "New scenario",
+        Text::Playtest => "Playtest",
+        Text::ExportScenario => "Export scenario",
+        Text::ImportScenario => "Import scenario",
+        Text::ScenarioName => "Scenario name",
+        Text::Resources => "Resources",
+        Text::Jobs => "Jobs",
+        Text::Id => "Id",
+        Text::Name => "Name",
+        Text::PluralName => "Plural name",
+        Text::Visible => "Visible",
+        Text::Row => "Row",
+        Text::Upkeep => "Upkeep",
+        Text::Description => "Description",
+        Text::TimeSlots => "Time slots",
+        Text::WinsTheRun => "Wins the run",
+        Text::AddResource => "Add resource",
+        Text::AddJob => "Add job",
+        Text::AddAlternative => "Add alternative",
+        Text::AddChange => "Add change",
+        Text::AddModifier => "Add upgrade effect",
+        Text::NoProblems => "No problems found",
+        Text::FixProblemsFirst => "Fix the problems before playtesting",
+        Text::JobIdCombined => "{} has alternatives from the pack {}, so its id can't change while the pack is active",
+        Text::ContentPacks => "Content packs ({})",
+        Text::LoadPackFile => "Load a pack file",
+        Text::PackUrl => "Pack address",
+        Text::PackFetchFailed => "Couldn't load the pack from {} ({})",
+        Text::LoadPackUrl => "Load from address",
+        Text::NoPacks => "No packs are loaded",
+        Text::PackEntry => "{}, version {}",
+        Text::PacksRestart => "Loading or removing a pack starts the facility over",
+        Text::AlreadyUpgraded => "This upgrade has already been done",
+        Text::UpgradeEffects => "Once done, for the rest of the run:",
+        Text::ModifierMore => "{}: {} more {}",
+        Text::ModifierFewer => "{}: {} fewer {}",
+        Text::ModifierSlower => "{}: takes {} more time slots",
+        Text::ModifierFaster => "{}: takes {} fewer time slots",
+        Text::UpgradedFrom => "{} (was {})",
+        Text::NoLongerNeeded => "no longer needed (was {})",
+        Text::UpgradeDone => "Done",
+        Text::EffectLeft => "{}: {} left",
+        Text::EffectAfterwards => "Afterwards: {} for {}",
+        Text::EffectRule => "{} yields {}% of the usual {}",
+        Text::Language => "Language",
+        Text::NumberFormat => "Number format",
+        Text::RepeatHint => "Shift-click to repeat until it fails",
+        Text::RepeatCountHint => "How many times each job button adds its job",
+        Text::TotalDaysSpent => "Total days spent: {}",
+        Text::Undo => "Undo",
+        Text::Redo => "Redo",
+        Text::Recipes => "Recipes",
+        Text::HideRecipes => "Hide recipes",
+        Text::JournalButton => "Journal ({})",
+        Text::Shortcuts => "Shortcuts",
+        Text::MacroName => "Macro name",
+        Text::SaveMacro => "Save macro",
+        Text::PlanPlaceholder => "5x Catch fragment; Craft day dream; Sell day dream",
+        Text::ExportPlan => "Export plan",
+        Text::ImportPlan => "Import plan",
+        Text::GhostBestRun => "Ghost: best run",
+        Text::GhostSolver => "Ghost: solver",
+        Text::Racing => "Racing {}",
+        Text::NoGhost => "No ghost",
+        Text::ComparedToGhost => "Compared to the ghost on day {}",
+        Text::EvenWithGhost => "Even with the ghost",
+        Text::Remove => "Remove",
+        Text::AlternativeNumber => "Alternative {}",
+        Text::AlternativeAuto => "Alternative: auto",
+        Text::Uses => "({} uses)",
+        Text::ByDay => "By day",
+        Text::ByJob => "By job",
+        Text::BestRunName => "Best run ({})",
+        Text::NoFinishedRun => "There's no finished run to compare against yet",
+        Text::SolverName => "Solver ({})",
+        Text::SolverFoundNothing => "The solver couldn't find a way out",
+        Text::SelectRowsFirst => "Select the rows to save first",
+        Text::MacroNeedsName => "The macro needs a name",
+        Text::MacroExists => "There's already a macro called {}",
+        Text::EmptySelection => "There are no jobs in the selection",
+        Text::Journal => "Journal",
+        Text::NothingWritten => "Nothing written yet.",
+        Text::Continue => "Continue",
+        Text::Close => "Close",
+        Text::KeyJob => "Job {}",
+        Text::PreviousRow => "Previous row",
+        Text::NextRow => "Next row",
+        Text::AddOneToRow => "Add one to row",
+        Text::RemoveOneFromRow => "Remove one from row",
+        Text::RemoveRow => "Remove row",
+        Text::ShowShortcuts => "Show shortcuts",
+        Text::KeyboardShortcuts => "Keyboard shortcuts",
+        Text::PressAKey => "Press a key",
+        Text::CtrlKey => "Ctrl+{}",
+        Text::ResetToDefaults => "Reset to defaults",
+        Text::DaysSpent => "Days spent: {}",
+        Text::Actions => "Actions: {}",
+        Text::Edits => "Edits: {}",
+        Text::Undos => "Undos: {}",
+        Text::FailedAttempts => "Failed attempts: {}",
+        Text::PersonalBest => "Personal best: {}",
+        Text::Par => "Par: {}",
+        Text::PeakAndGained => "{}: peak {}, total gained {}",
+        Text::NewPersonalBest => "New personal best, {} better than before",
+        Text::MatchedPersonalBest => "Matched your personal best",
+        Text::BehindPersonalBest => "{} behind your personal best",
+        Text::FirstEscape => "Your first escape",
+        Text::EscapedIn => "You escaped in {}",
+        Text::ParIs => "Par is {}",
+        Text::RunStatistics => "{} actions, {} edits, {} undos, {} failed attempts",
+        Text::ExportSummary => "Export summary",
+        Text::YourName => "Your name",
+        Text::LeaderboardServer => "Leaderboard server (optional)",
+        Text::SubmitToLeaderboard => "Submit to leaderboard",
+        Text::LeaderboardEntry => "{}. {} - {}",
+        Text::Anonymous => "Anonymous",
+        Text::SeedTooLate => "The leaderboard's seed came after the run had started, so start over to submit this scenario",
+        Text::SeedFromElsewhere => "The run's seed didn't come from this leaderboard, so start over to submit to it",
+        Text::RepeatsZeroTimes => "\"{}\" repeats a job zero times",
+        Text::AlternativeNotANumber => "\"{}\" has an alternative that isn't a number, write it as [alt 2]",
+        Text::TooFewAlternatives => "{} only has {} alternatives",
+        Text::UnknownJob => "Unknown job \"{}\"",
+        Text::PlanLine => "Line {}: {}",
+        Text::NotAContentPack => "Not a content pack: {}",
+        Text::ScenarioOwner => "the scenario {}",
+        Text::PackOwner => "the pack {}",
+        Text::PackNeedsId => "The pack {} needs an id without ':' or '+' in it",
+        Text::PackLoadedTwice => "The pack {} is loaded more than once",
+        Text::PackResourceOutsideNamespace => "The pack {} defines the resource {}, which is outside its namespace",
+        Text::PackResourceTaken => "The pack {} defines the resource {}, which {} already defines",
+        Text::PackJobOutsideNamespace => "The pack {} defines the job {}, which is outside its namespace",
+        Text::PackJobTaken => "The pack {} defines the job {}, which {} already defines",
+        Text::PackJobMissing => "The pack {} adds to the job {}, which isn't in the scenario",
+        Text::ScenarioNeedsName => "The scenario needs a name",
+        Text::ResourceHasNoId => "Resource {} has no id",
+        Text::DuplicateResourceId => "More than one resource has the id {}",
+        Text::ResourceNeedsNames => "Resource {} needs both a name and a plural name",
+        Text::ScenarioHasNoJobs => "The scenario has no jobs",
+        Text::JobHasNoId => "Job {} has no id",
+        Text::DuplicateJobId => "More than one job has the id {}",
+        Text::JobNeedsName => "Job {} needs a name",
+        Text::JobTakesNoTime => "Job {} takes no time",
+        Text::AlternativeDoesNothing => "Job {} has an alternative that does nothing",
+        Text::EffectForNoTime => "Job {} sets off {} for no time at all",
+        Text::WinJobNotAJob => "The winning job {} isn't one of the jobs",
+        Text::ResourceNeverDefined => "{} is used but never defined",
+        Text::JobOwner => "Job {}",
+        Text::UpkeepOwner => "Upkeep of {}",
+        Text::ChanceOutOfRange => "{}, alternative {}: {} has a chance outside 1 to 100 percent ({})",
+        Text::RangeBackwards => "{}, alternative {}: {} has a range that doesn't go from low to high ({})",
+        Text::AmountNotPositive => "{}, alternative {}: {} has a zero or negative amount ({})",
+        Text::SpendsXWithoutGain => "{}, alternative {}: spends X without gaining anything per X",
+        Text::GainsXWithoutSpend => "{}, alternative {}: gains X without anything deciding what X is",
+        Text::BreaksBeforeUse => "{} has a durability of {}, so it breaks before it can be used",
+        Text::NothingProduces => "No job produces {}",
+        Text::JobNeverPossible => "Job {} can never have its requirements met",
+        Text::WinJobNeverReached => "The winning job {} can never be reached",
+        Text::UpgradeTargetMissing => "Job {} upgrades the job {}, which isn't in the scenario",
+        Text::UpgradeResourceUnused => "Job {} upgrades {} in the job {}, which doesn't use it",
+        Text::UpkeepCycle => "The upkeep of {} feeds back into itself and never settles",
+        Text::NotADate => "\"{}\" isn't a date written as YYYY-MM-DD",
+        Text::NotARealDate => "\"{}\" isn't a real date",
+        Text::DepthOutOfRange => "The depth has to be from 1 to {}, not {}",
+        Text::WidthOutOfRange => "The width has to be from 1 to {}, not {}",
+        Text::TooManyResources => "A depth of {} and a width of {} make more than {} resources",
+        Text::NoWinnableScenario => "Couldn't generate a winnable scenario from seed {} in {} attempts",
+        Text::GeneratedPlural => "{}s",
+        Text::GatherJob => "Gather {}",
+        Text::GatherDescription => "Collect {} from the edges of the facility.",
+        Text::MakeJob => "Make {}",
+        Text::MakeDescription => "Put together a {} from what you have gathered.",
+        Text::FinishJob => "Finish",
+        Text::FinishDescription => "Hand in one of everything from the last step and walk out.",
+        Text::UnknownScenario => "Unknown scenario {}",
+        Text::PacksCantBeVerified => "The run used the content packs {}, which can't be verified",
+        Text::WrongScenarioVersion => "The run is for scenario {} version {}, but this verifier checks version {}",
+        Text::NotTheDailySeed => "The run doesn't use the seed of the daily challenge for {}",
+        Text::StepJobMissing => "The step points at a job that doesn't exist in this scenario",
+        Text::NeverWins => "The run never reaches the winning job",
+        Text::JobFailed => "The job failed",
+        Text::WrongDayCount => "The run claims {} days, but took {}",
+        Text::SeedRequestFailed => "Couldn't get a seed from the leaderboard ({})",
+        Text::RunRejected => "The leaderboard rejected the run ({}): {}",
+        Text::LeaderboardReadFailed => "Couldn't read the leaderboard ({})",
+    }
+}
+
+fn swedish(text: Text) -> &'static str {
+    match text {
+        Text::NotEnough => "Inte tillräckligt med {}",
+        Text::TooMany => "För många {}, högst {} tillåts",
+        Text::NoneAllowed => "Du får inte ha några {}",
+        Text::NothingToRepair => "Inga {} behöver lagas",
+        Text::MissingAlternative => "Det valda alternativet {} finns inte, jobbet har bara {}",
+        Text::Require => "kräver {}",
+        Text::SetTo => "sätt till {}",
+        Text::Repair => "laga {}",
+        Text::AtMost => "högst {}",
+        Text::Forbid => "inga tillåtna",
+        Text::Chance => "+{} ({} % chans)",
+        Text::Between => "+{} till {}",
+        Text::Expected => "väntat {}, från {} till {}",
+        Text::StartingResources => "Startresurser",
+        Text::DayCount => "{} dag",
+        Text::DaysCount => "{} dagar",
+        Text::DailyChallenge => "Dagens utmaning",
+        Text::DailyTitle => "Dagens utmaning för {}",
+        Text::DailyBanned => "{} är stängt i dag",
+        Text::DailyBest => "Ditt bästa i dag: {}",
+        Text::BackToFacility => "Tillbaka till anläggningen",
+        Text::DifficultyEasy => "Lätt",
+        Text::DifficultyNormal => "Normal",
+        Text::DifficultyHard => "Svår",
+        Text::DifficultyBrutal => "Brutal",
+        Text::GenerateScenario => "Skapa ett scenario",
+        Text::GeneratorDepth => "Steg",
+        Text::GeneratorWidth => "Resurser per steg",
+        Text::GeneratedTitle => "Skapat scenario {}",
+        Text::GeneratedPar => "{}, kan vinnas på {}",
+        Text::ScenarioEditor => "Scenarioredigerare",
+        Text::BackToGame => "Tillbaka till spelet",
+        Text::NewScenario => "Nytt scenario",
+        Text::Playtest => "Provspela",
+        Text::ExportScenario => "Exportera scenario",
+        Text::ImportScenario => "Importera scenario",
+        Text::ScenarioName => "Scenariots namn",
+        Text::Resources => "Resurser",
+        Text::Jobs => "Jobb",
+        Text::Id => "Id",
+        Text::Name => "Namn",
+        Text::PluralName => "Pluralnamn",
+        Text::Visible => "Synlig",
+        Text::Row => "Rad",
+        Text::Upkeep => "Underhåll",
+        Text::Description => "Beskrivning",
+        Text::TimeSlots => "Tidsluckor",
+        Text::WinsTheRun => "Vinner omgången",
+        Text::AddResource => "Lägg till resurs",
+        Text::AddJob => "Lägg till jobb",
+        Text::AddAlternative => "Lägg till alternativ",
+        Text::AddChange => "Lägg till ändring",
+        Text::AddModifier => "Lägg till uppgraderingseffekt",
+        Text::NoProblems => "Inga problem hittades",
+        Text::FixProblemsFirst => "Rätta till problemen innan du provspelar",
+        Text::JobIdCombined => "{} har alternativ från paketet {}, så dess id kan inte ändras medan paketet är aktivt",
+        Text::ContentPacks => "Innehållspaket ({})",
+        Text::LoadPackFile => "Ladda en paketfil",
+        Text::PackUrl => "Paketets adress",
+        Text::PackFetchFailed => "Kunde inte ladda paketet från {} ({})",
+        Text::LoadPackUrl => "Ladda från adress",
+        Text::NoPacks => "Inga paket är laddade",
+        Text::PackEntry => "{}, version {}",
+        Text::PacksRestart => "Att ladda eller ta bort ett paket startar om anläggningen",
+        Text::AlreadyUpgraded => "Den här uppgraderingen är redan gjord",
+        Text::UpgradeEffects => "När den är gjord, resten av omgången:",
+        Text::ModifierMore => "{}: {} fler {}",
+        Text::ModifierFewer => "{}: {} färre {}",
+        Text::ModifierSlower => "{}: tar {} fler tidsluckor",
+        Text::ModifierFaster => "{}: tar {} färre tidsluckor",
+        Text::UpgradedFrom => "{} (var {})",
+        Text::NoLongerNeeded => "behövs inte längre (var {})",
+        Text::UpgradeDone => "Gjord",
+        Text::EffectLeft => "{}: {} kvar",
+        Text::EffectAfterwards => "Efteråt: {} i {}",
+        Text::EffectRule => "{} ger {}% av det vanliga antalet {}",
+        Text::Language => "Språk",
+        Text::NumberFormat => "Talformat",
+        Text::RepeatHint => "Skift-klicka för att upprepa tills det misslyckas",
+        Text::RepeatCountHint => "Hur många gånger varje jobbknapp lägger till sitt jobb",
+        Text::TotalDaysSpent => "Dagar totalt: {}",
+        Text::Undo => "Ångra",
+        Text::Redo => "Gör om",
+        Text::Recipes => "Recept",
+        Text::HideRecipes => "Dölj recept",
+        Text::JournalButton => "Dagbok ({})",
+        Text::Shortcuts => "Kortkommandon",
+        Text::MacroName => "Makrots namn",
+        Text::SaveMacro => "Spara makro",
+        Text::PlanPlaceholder => "5x Fånga fragment; Skapa dagdröm; Sälj dagdröm",
+        Text::ExportPlan => "Exportera plan",
+        Text::ImportPlan => "Importera plan",
+        Text::GhostBestRun => "Spöke: bästa runda",
+        Text::GhostSolver => "Spöke: lösare",
+        Text::Racing => "Tävlar mot {}",
+        Text::NoGhost => "Inget spöke",
+        Text::ComparedToGhost => "Jämfört med spöket dag {}",
+        Text::EvenWithGhost => "Jämsides med spöket",
+        Text::Remove => "Ta bort",
+        Text::AlternativeNumber => "Alternativ {}",
+        Text::AlternativeAuto => "Alternativ: auto",
+        Text::Uses => "({} användningar)",
+        Text::ByDay => "Per dag",
+        Text::ByJob => "Per jobb",
+        Text::BestRunName => "Bästa runda ({})",
+        Text::NoFinishedRun => "Det finns ingen avslutad runda att jämföra med än",
+        Text::SolverName => "Lösare ({})",
+        Text::SolverFoundNothing => "Lösaren hittade ingen väg ut",
+        Text::SelectRowsFirst => "Välj raderna som ska sparas först",
+        Text::MacroNeedsName => "Makrot behöver ett namn",
+        Text::MacroExists => "Det finns redan ett makro som heter {}",
+        Text::EmptySelection => "Det finns inga jobb i markeringen",
+        Text::Journal => "Dagbok",
+        Text::NothingWritten => "Inget skrivet än.",
+        Text::Continue => "Fortsätt",
+        Text::Close => "Stäng",
+        Text::KeyJob => "Jobb {}",
+        Text::PreviousRow => "Föregående rad",
+        Text::NextRow => "Nästa rad",
+        Text::AddOneToRow => "Lägg till en på raden",
+        Text::RemoveOneFromRow => "Ta bort en från raden",
+        Text::RemoveRow => "Ta bort raden",
+        Text::ShowShortcuts => "Visa kortkommandon",
+        Text::KeyboardShortcuts => "Kortkommandon",
+        Text::PressAKey => "Tryck på en tangent",
+        Text::CtrlKey => "Ctrl+{}",
+        Text::ResetToDefaults => "Återställ",
+        Text::DaysSpent => "Dagar: {}",
+        Text::Actions => "Handlingar: {}",
+        Text::Edits => "Ändringar: {}",
+        Text::Undos => "Ångringar: {}",
+        Text::FailedAttempts => "Misslyckade försök: {}",
+        Text::PersonalBest => "Personbästa: {}",
+        Text::Par => "Par: {}",
+        Text::PeakAndGained => "{}: som mest {}, totalt fått {}",
+        Text::NewPersonalBest => "Nytt personbästa, {} bättre än förut",
+        Text::MatchedPersonalBest => "Lika bra som ditt personbästa",
+        Text::BehindPersonalBest => "{} efter ditt personbästa",
+        Text::FirstEscape => "Din första flykt",
+        Text::EscapedIn => "Du flydde på {}",
+        Text::ParIs => "Par är {}",
+        Text::RunStatistics => "{} handlingar, {} ändringar, {} ångringar, {} misslyckade försök",
+        Text::ExportSummary => "Exportera sammanfattning",
+        Text::YourName => "Ditt namn",
+        Text::LeaderboardServer => "Topplisteserver (valfri)",
+        Text::SubmitToLeaderboard => "Skicka till topplistan",
+        Text::LeaderboardEntry => "{}. {} - {}",
+        Text::Anonymous => "Anonym",
+        Text::SeedTooLate => "Topplistans frö kom efter att rundan hade börjat, så börja om för att skicka in det här scenariot",
+        Text::SeedFromElsewhere => "Rundans frö kom inte från den här topplistan, så börja om för att skicka in till den",
+        Text::RepeatsZeroTimes => "\"{}\" upprepar ett jobb noll gånger",
+        Text::AlternativeNotANumber => "\"{}\" har ett alternativ som inte är ett nummer, skriv det som [alt 2]",
+        Text::TooFewAlternatives => "{} har bara {} alternativ",
+        Text::UnknownJob => "Okänt jobb \"{}\"",
+        Text::PlanLine => "Rad {}: {}",
+        Text::NotAContentPack => "Inte ett innehållspaket: {}",
+        Text::ScenarioOwner => "scenariot {}",
+        Text::PackOwner => "paketet {}",
+        Text::PackNeedsId => "Paketet {} behöver ett id utan ':' eller '+'",
+        Text::PackLoadedTwice => "Paketet {} är inläst mer än en gång",
+        Text::PackResourceOutsideNamespace => "Paketet {} definierar resursen {}, som ligger utanför dess namnrymd",
+        Text::PackResourceTaken => "Paketet {} definierar resursen {}, som {} redan definierar",
+        Text::PackJobOutsideNamespace => "Paketet {} definierar jobbet {}, som ligger utanför dess namnrymd",
+        Text::PackJobTaken => "Paketet {} definierar jobbet {}, som {} redan definierar",
+        Text::PackJobMissing => "Paketet {} lägger till i jobbet {}, som inte finns i scenariot",
+        Text::ScenarioNeedsName => "Scenariot behöver ett namn",
+        Text::ResourceHasNoId => "Resurs {} har inget id",
+        Text::DuplicateResourceId => "Mer än en resurs har id:t {}",
+        Text::ResourceNeedsNames => "Resursen {} behöver både ett namn och ett pluralnamn",
+        Text::ScenarioHasNoJobs => "Scenariot har inga jobb",
+        Text::JobHasNoId => "Jobb {} har inget id",
+        Text::DuplicateJobId => "Mer än ett jobb har id:t {}",
+        Text::JobNeedsName => "Jobbet {} behöver ett namn",
+        Text::JobTakesNoTime => "Jobbet {} tar ingen tid",
+        Text::AlternativeDoesNothing => "Jobbet {} har ett alternativ som inte gör något",
+        Text::EffectForNoTime => "Jobbet {} sätter igång {} under ingen tid alls",
+        Text::WinJobNotAJob => "Det vinnande jobbet {} är inte ett av jobben",
+        Text::ResourceNeverDefined => "{} används men definieras aldrig",
+        Text::JobOwner => "Jobbet {}",
+        Text::UpkeepOwner => "Underhåll av {}",
+        Text::ChanceOutOfRange => "{}, alternativ {}: {} har en chans utanför 1 till 100 procent ({})",
+        Text::RangeBackwards => "{}, alternativ {}: {} har ett intervall som inte går från lågt till högt ({})",
+        Text::AmountNotPositive => "{}, alternativ {}: {} har en mängd som är noll eller negativ ({})",
+        Text::SpendsXWithoutGain => "{}, alternativ {}: spenderar X utan att få något per X",
+        Text::GainsXWithoutSpend => "{}, alternativ {}: får X utan att något avgör vad X är",
+        Text::BreaksBeforeUse => "{} har en hållbarhet på {}, så den går sönder innan den kan användas",
+        Text::NothingProduces => "Inget jobb ger {}",
+        Text::JobNeverPossible => "Jobbet {} kan aldrig få sina krav uppfyllda",
+        Text::WinJobNeverReached => "Det vinnande jobbet {} kan aldrig nås",
+        Text::UpgradeTargetMissing => "Jobbet {} uppgraderar jobbet {}, som inte finns i scenariot",
+        Text::UpgradeResourceUnused => "Jobbet {} uppgraderar {} i jobbet {}, som inte använder det",
+        Text::UpkeepCycle => "Underhållet av {} matar sig självt och lugnar sig aldrig",
+        Text::NotADate => "\"{}\" är inte ett datum skrivet som ÅÅÅÅ-MM-DD",
+        Text::NotARealDate => "\"{}\" är inte ett riktigt datum",
+        Text::DepthOutOfRange => "Djupet måste vara från 1 till {}, inte {}",
+        Text::WidthOutOfRange => "Bredden måste vara från 1 till {}, inte {}",
+        Text::TooManyResources => "Ett djup på {} och en bredd på {} ger mer än {} resurser",
+        Text::NoWinnableScenario => "Kunde inte skapa ett scenario som går att vinna från fröet {} på {} försök",
+        Text::GeneratedPlural => "{}er",
+        Text::GatherJob => "Samla {}",
+        Text::GatherDescription => "Plocka {} från anläggningens utkanter.",
+        Text::MakeJob => "Tillverka {}",
+        Text::MakeDescription => "Sätt ihop en {} av det du har samlat.",
+        Text::FinishJob => "Avsluta",
+        Text::FinishDescription => "Lämna in en av allt från det sista steget och gå ut.",
+        Text::UnknownScenario => "Okänt scenario {}",
+        Text::PacksCantBeVerified => "Rundan använde innehållspaketen {}, som inte kan verifieras",
+        Text::WrongScenarioVersion => "Rundan gäller scenariot {} version {}, men den här kontrollen gäller version {}",
+        Text::NotTheDailySeed => "Rundan använder inte fröet för dagens utmaning {}",
+        Text::StepJobMissing => "Steget pekar på ett jobb som inte finns i det här scenariot",
+        Text::NeverWins => "Rundan når aldrig det vinnande jobbet",
+        Text::JobFailed => "Jobbet misslyckades",
+        Text::WrongDayCount => "Rundan påstår {} dagar, men tog {}",
+        Text::SeedRequestFailed => "Kunde inte få ett frö från topplistan ({})",
+        Text::RunRejected => "Topplistan avvisade rundan ({}): {}",
+        Text::LeaderboardReadFailed => "Kunde inte läsa topplistan ({})",
+    }
+}
@@ -1,5 +1,29 @@
 pub mod amount;
+#[cfg(feature = "big-numbers")]
+pub mod big_quantity;
+pub mod catalogue;
+pub mod custom;
+pub mod daily;
+pub mod editor;
+pub mod effect;
+pub mod generator;
+pub mod ghost;
+pub mod graph;
 pub mod inventory;
 pub mod job;
+pub mod leaderboard;
+pub mod locale;
+pub mod pack;
+pub mod plan;
+pub mod quantity;
+pub mod random;
+pub mod record;
+pub mod replay;
 pub mod resource;
-pub mod state;
\ No newline at end of file
+pub mod scenario;
+pub mod solver;
+pub mod state;
+pub mod story;
+pub mod upgrade;
+pub mod validate;
+pub mod verify;
\ No newline at end of file
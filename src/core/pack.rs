@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::core::amount::Amount;
+use crate::core::custom::{CustomId, JobDefinition, ResourceDefinition};
+use crate::core::editor::{check_scenario_file, job_id_text, resource_id_text};
+use crate::core::locale::{fill, Text};
+use crate::core::job::JobId;
+use crate::core::resource::Resource;
+use crate::core::scenario::Scenario;
+use crate::core::upgrade::JobModifier;
+
+// Content packs add resources and jobs to a scenario at runtime.
+// Ids in a pack without a namespace, such as `kiln`, are put in the pack's own namespace and become `glassworks:kiln`.
+// Ids that already have one, such as `otherpack:sand`, refer to something another pack or the scenario defines.
+
+type Deltas = Vec<Vec<(Resource, Amount)>>;
+
+// New ways of doing a job that is already in the scenario, added after its own alternatives
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Combination {
+    pub job: JobId,
+    pub end_deltas: Deltas,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContentPack {
+    // The namespace of everything the pack defines
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub resources: Vec<ResourceDefinition>,
+    #[serde(default)]
+    pub jobs: Vec<JobDefinition>,
+    #[serde(default)]
+    pub combinations: Vec<Combination>,
+    // Added to what the player starts with
+    #[serde(default)]
+    pub starting_resources: Vec<(Resource, Amount)>,
+}
+
+impl ContentPack {
+    // How runs and saves refer to the pack
+    pub fn label(&self) -> String {
+        format!("{}@{}", self.id, self.version)
+    }
+}
+
+pub fn pack_from_json(text: &str) -> Result<ContentPack, String> {
+    serde_json::from_str::<ContentPack>(text).map_err(|error| fill(Text::NotAContentPack, &[&error]))
+}
+
+fn namespaced(namespace: &str, id: CustomId) -> CustomId {
+    if id.0.contains(':') {
+        id
+    } else {
+        CustomId::new(&format!("{}:{}", namespace, id.0))
+    }
+}
+
+fn namespaced_resource(namespace: &str, resource: Resource) -> Resource {
+    match resource {
+        Resource::Custom(id) => Resource::Custom(namespaced(namespace, id)),
+        built_in => built_in,
+    }
+}
+
+fn namespaced_job(namespace: &str, job: JobId) -> JobId {
+    match job {
+        JobId::Custom(id) => JobId::Custom(namespaced(namespace, id)),
+        built_in => built_in,
+    }
+}
+
+fn namespaced_deltas(namespace: &str, deltas: &Deltas) -> Deltas {
+    deltas.iter()
+        .map(|delta| delta.iter().map(|(resource, amount)| (namespaced_resource(namespace, *resource), *amount)).collect())
+        .collect()
+}
+
+fn namespaced_modifier(namespace: &str, modifier: &JobModifier) -> JobModifier {
+    match modifier {
+        JobModifier::Amount(job, resource, change) => JobModifier::Amount(namespaced_job(namespace, *job), namespaced_resource(namespace, *resource), *change),
+        JobModifier::TimeSlots(job, change) => JobModifier::TimeSlots(namespaced_job(namespace, *job), *change),
+    }
+}
+
+// The pack with every id written out in full
+fn namespace_pack(pack: &ContentPack) -> ContentPack {
+    let namespace = pack.id.as_str();
+    ContentPack {
+        resources: pack.resources.iter().map(|definition| ResourceDefinition {
+            id: namespaced_resource(namespace, definition.id),
+            upkeep: namespaced_deltas(namespace, &definition.upkeep),
+            ..definition.clone()
+        }).collect(),
+        jobs: pack.jobs.iter().map(|job| JobDefinition {
+            id: namespaced_job(namespace, job.id),
+            end_deltas: namespaced_deltas(namespace, &job.end_deltas),
+            modifiers: job.modifiers.iter().map(|modifier| namespaced_modifier(namespace, modifier)).collect(),
+            ..job.clone()
+        }).collect(),
+        combinations: pack.combinations.iter().map(|combination| Combination {
+            job: namespaced_job(namespace, combination.job),
+            end_deltas: namespaced_deltas(namespace, &combination.end_deltas),
+        }).collect(),
+        starting_resources: namespaced_deltas(namespace, &vec![pack.starting_resources.clone()]).remove(0),
+        ..pack.clone()
+    }
+}
+
+fn in_namespace(namespace: &str, id: &str) -> bool {
+    id.strip_prefix(namespace).map(|rest| rest.starts_with(':')).unwrap_or(false)
+}
+
+// The scenario with the packs added in order, or every conflict and problem found.
+// The name lists the packs, so runs with packs are kept apart from runs without them.
+pub fn apply_packs(base: &Scenario, packs: &[ContentPack]) -> Result<Scenario, Vec<String>> {
+    if packs.is_empty() {
+        return Ok(base.clone());
+    }
+    let mut file = base.to_file();
+    let mut problems = Vec::new();
+    // Who defined each id, for telling the player which two things collide
+    let mut resource_owners = file.resources.iter()
+        .map(|definition| (definition.id, fill(Text::ScenarioOwner, &[&base.name])))
+        .collect::<BTreeMap<_, _>>();
+    let mut job_owners = file.jobs.iter()
+        .map(|job| (job.id, fill(Text::ScenarioOwner, &[&base.name])))
+        .collect::<BTreeMap<_, _>>();
+    let mut loaded: Vec<&str> = Vec::new();
+    for pack in packs.iter() {
+        if pack.id.is_empty() || pack.id.contains(':') || pack.id.contains('+') {
+            problems.push(fill(Text::PackNeedsId, &[&pack.name]));
+            continue;
+        }
+        if loaded.contains(&pack.id.as_str()) {
+            problems.push(fill(Text::PackLoadedTwice, &[&pack.id]));
+            continue;
+        }
+        loaded.push(&pack.id);
+        let owner = fill(Text::PackOwner, &[&pack.id]);
+        let pack = namespace_pack(pack);
+        for definition in pack.resources.iter() {
+            let id = resource_id_text(&definition.id);
+            if !in_namespace(&pack.id, &id) {
+                problems.push(fill(Text::PackResourceOutsideNamespace, &[&pack.id, &id]));
+            } else if let Some(other) = resource_owners.get(&definition.id) {
+                problems.push(fill(Text::PackResourceTaken, &[&pack.id, &id, other]));
+            } else {
+                resource_owners.insert(definition.id, owner.clone());
+                file.resources.push(definition.clone());
+            }
+        }
+        for job in pack.jobs.iter() {
+            let id = job_id_text(&job.id);
+            if !in_namespace(&pack.id, &id) {
+                problems.push(fill(Text::PackJobOutsideNamespace, &[&pack.id, &id]));
+            } else if let Some(other) = job_owners.get(&job.id) {
+                problems.push(fill(Text::PackJobTaken, &[&pack.id, &id, other]));
+            } else {
+                job_owners.insert(job.id, owner.clone());
+                file.jobs.push(job.clone());
+            }
+        }
+        for combination in pack.combinations.iter() {
+            match file.jobs.iter_mut().find(|job| job.id == combination.job) {
+                Some(job) => job.end_deltas.extend(combination.end_deltas.iter().cloned()),
+                None => problems.push(fill(Text::PackJobMissing, &[&pack.id, &job_id_text(&combination.job)])),
+            }
+        }
+        // Only one alternative of the starting resources applies, so every one of them gets the pack's resources
+        if !pack.starting_resources.is_empty() {
+            if file.starting_resources.is_empty() {
+                file.starting_resources.push(vec![]);
+            }
+            for delta in file.starting_resources.iter_mut() {
+                delta.extend(pack.starting_resources.iter().cloned());
+            }
+        }
+    }
+    file.name = std::iter::once(base.name.as_str()).chain(loaded.iter().cloned()).collect::<Vec<_>>().join("+");
+    // Only look for problems in the result once the packs fit together, or every conflict gets reported twice
+    if problems.is_empty() {
+        problems.extend(check_scenario_file(&file));
+    }
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+    let mut scenario = Scenario::from_file(&file);
+    scenario.packs = packs.iter().map(ContentPack::label).collect();
+    Ok(scenario)
+}
@@ -0,0 +1,134 @@
+use crate::core::job::Job;
+use crate::core::locale::{fill, job_name, job_names, Text};
+
+// Plans are written as entries separated by semicolons or new lines, such as
+// `5x Catch fragment; Craft day dream; 2x Sell day dream; Retire`.
+// A pinned alternative is written after the name, as in `Craft dream [alt 2]`.
+// Plans are written in the current language, and read in any of them.
+
+fn format_entry(job: &Job, count: usize) -> String {
+    let mut entry = if count > 1 {
+        format!("{}x {}", count, job_name(job))
+    } else {
+        job_name(job).to_string()
+    };
+    if let Some(chosen_index) = job.chosen_alternative {
+        entry.push_str(&format!(" [alt {}]", chosen_index + 1));
+    }
+    entry
+}
+
+// Write the jobs as a plan, merging consecutive identical jobs like the job rows do
+pub fn format_plan(jobs: &[Job]) -> String {
+    let mut entries: Vec<(&Job, usize)> = Vec::new();
+    for job in jobs.iter() {
+        match entries.last_mut() {
+            Some((last_job, count)) if last_job.id == job.id && last_job.chosen_alternative == job.chosen_alternative => {
+                *count += 1;
+            }
+            _ => {
+                entries.push((job, 1));
+            }
+        }
+    }
+    entries.iter()
+        .map(|(job, count)| format_entry(job, *count))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn parse_entry(entry: &str, scenario_jobs: &[Job]) -> Result<Vec<Job>, String> {
+    let mut rest = entry.trim();
+    let mut count = 1;
+    if let Some((number, name)) = rest.split_once(char::is_whitespace) {
+        if let Some(number) = number.strip_suffix('x').or_else(|| number.strip_suffix('X')) {
+            if let Ok(number) = number.parse::<usize>() {
+                if number == 0 {
+                    return Err(fill(Text::RepeatsZeroTimes, &[&entry.trim()]));
+                }
+                count = number;
+                rest = name.trim();
+            }
+        }
+    }
+    let mut chosen_alternative = None;
+    if let Some((name, alternative)) = rest.rsplit_once('[') {
+        let alternative = alternative.trim_end_matches(']').trim();
+        let number = alternative.strip_prefix("alt").map(|number| number.trim().parse::<usize>());
+        match number {
+            Some(Ok(number)) if number > 0 => {
+                chosen_alternative = Some(number - 1);
+                rest = name.trim();
+            }
+            _ => {
+                return Err(fill(Text::AlternativeNotANumber, &[&entry.trim()]));
+            }
+        }
+    }
+    let job = scenario_jobs.iter().find(|&job| job_names(job).iter().any(|name| name.to_lowercase() == rest.to_lowercase())).cloned();
+    match job {
+        Some(mut job) => {
+            if let Some(chosen_index) = chosen_alternative {
+                if chosen_index >= job.end_deltas.len() {
+                    return Err(fill(Text::TooFewAlternatives, &[&job_name(&job), &job.end_deltas.len()]));
+                }
+            }
+            job.chosen_alternative = chosen_alternative;
+            Ok(vec![job; count])
+        }
+        None => Err(fill(Text::UnknownJob, &[&rest])),
+    }
+}
+
+// Read a plan back into jobs of the scenario being played. Every entry that can't be read gets its own error,
+// with the line it is on counted from 1, blank lines included.
+pub fn parse_plan(text: &str, scenario_jobs: &[Job]) -> Result<Vec<Job>, Vec<String>> {
+    let mut jobs = Vec::new();
+    let mut errors = Vec::new();
+    for (line_index, line) in text.lines().enumerate() {
+        for entry in line.split(';').filter(|entry| !entry.trim().is_empty()) {
+            match parse_entry(entry, scenario_jobs) {
+                Ok(entry_jobs) => jobs.extend(entry_jobs),
+                Err(error) => errors.push(fill(Text::PlanLine, &[&(line_index + 1), &error])),
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(jobs)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::locale::{set_locale, Locale};
+    use crate::core::scenario::facility;
+
+    #[test]
+    fn errors_name_the_line_they_are_on() {
+        let jobs = facility().jobs;
+        let errors = parse_plan("2x Catch fragment\n\nCraft day dream; Juggle\n\n0x Retire", &jobs).unwrap_err();
+        assert_eq!(errors, vec![
+            "Line 3: Unknown job \"Juggle\"".to_string(),
+            "Line 5: \"0x Retire\" repeats a job zero times".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn errors_are_in_the_chosen_language() {
+        let jobs = facility().jobs;
+        set_locale(Locale::Swedish);
+        let errors = parse_plan("Jonglera", &jobs).unwrap_err();
+        set_locale(Locale::English);
+        assert_eq!(errors, vec!["Rad 1: Okänt jobb \"Jonglera\"".to_string()]);
+    }
+
+    #[test]
+    fn plans_read_back_as_written() {
+        let jobs = facility().jobs;
+        let plan = parse_plan("3x Catch fragment; Craft day dream\nSell day dream", &jobs).unwrap();
+        assert_eq!(format_plan(&plan), "3x Catch fragment; Craft day dream; Sell day dream");
+    }
+}
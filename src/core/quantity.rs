@@ -0,0 +1,188 @@
+use std::cell::Cell;
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+// How much of a resource is held. Plain integers are the default, and the `big-numbers` feature swaps in an
+// arbitrary precision integer for scenarios where X jobs grow resources past what an i64 can hold.
+// Everything that does arithmetic on held amounts goes through the functions here, so that it works with either.
+#[cfg(not(feature = "big-numbers"))]
+pub type Quantity = i64;
+#[cfg(feature = "big-numbers")]
+pub type Quantity = crate::core::big_quantity::BigQuantity;
+
+#[cfg(not(feature = "big-numbers"))]
+pub fn quantity(amount: i64) -> Quantity {
+    amount
+}
+
+#[cfg(feature = "big-numbers")]
+pub fn quantity(amount: i64) -> Quantity {
+    Quantity::from_i64(amount)
+}
+
+// Plain integers saturate instead of overflowing
+#[cfg(not(feature = "big-numbers"))]
+pub fn add(first: &Quantity, second: &Quantity) -> Quantity {
+    first.saturating_add(*second)
+}
+
+#[cfg(feature = "big-numbers")]
+pub fn add(first: &Quantity, second: &Quantity) -> Quantity {
+    first.add(second)
+}
+
+#[cfg(not(feature = "big-numbers"))]
+pub fn subtract(first: &Quantity, second: &Quantity) -> Quantity {
+    first.saturating_sub(*second)
+}
+
+#[cfg(feature = "big-numbers")]
+pub fn subtract(first: &Quantity, second: &Quantity) -> Quantity {
+    first.subtract(second)
+}
+
+#[cfg(not(feature = "big-numbers"))]
+pub fn scale(value: &Quantity, factor: i64) -> Quantity {
+    value.saturating_mul(factor)
+}
+
+#[cfg(feature = "big-numbers")]
+pub fn scale(value: &Quantity, factor: i64) -> Quantity {
+    value.multiply(factor)
+}
+
+// Rounds towards zero, and is None when dividing by zero
+#[cfg(not(feature = "big-numbers"))]
+pub fn divide(value: &Quantity, divisor: i64) -> Option<Quantity> {
+    value.checked_div(divisor)
+}
+
+#[cfg(feature = "big-numbers")]
+pub fn divide(value: &Quantity, divisor: i64) -> Option<Quantity> {
+    value.divide(divisor)
+}
+
+#[cfg(not(feature = "big-numbers"))]
+pub fn to_f64(value: &Quantity) -> f64 {
+    *value as f64
+}
+
+#[cfg(feature = "big-numbers")]
+pub fn to_f64(value: &Quantity) -> f64 {
+    value.to_f64()
+}
+
+// For counting things like units and plural forms, where anything past an i64 is as good as infinite
+#[cfg(not(feature = "big-numbers"))]
+pub fn to_count(value: &Quantity) -> i64 {
+    *value
+}
+
+#[cfg(feature = "big-numbers")]
+pub fn to_count(value: &Quantity) -> i64 {
+    value.to_i64_saturating()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumberFormat {
+    // Every digit
+    Full,
+    // 1.2k, 3.4M and so on, switching to scientific past the named suffixes
+    Compact,
+    // 1.23e6
+    Scientific,
+}
+
+thread_local! {
+    static CURRENT_NUMBER_FORMAT: Cell<NumberFormat> = const { Cell::new(NumberFormat::Compact) };
+}
+
+pub fn all_number_formats() -> Vec<NumberFormat> {
+    vec![
+        NumberFormat::Full,
+        NumberFormat::Compact,
+        NumberFormat::Scientific,
+    ]
+}
+
+pub fn current_number_format() -> NumberFormat {
+    CURRENT_NUMBER_FORMAT.with(|format| format.get())
+}
+
+pub fn set_number_format(format: NumberFormat) {
+    CURRENT_NUMBER_FORMAT.with(|current| current.set(format));
+}
+
+impl Display for NumberFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberFormat::Full => f.write_str("1234567"),
+            NumberFormat::Compact => f.write_str("1.2M"),
+            NumberFormat::Scientific => f.write_str("1.23e6"),
+        }
+    }
+}
+
+const SUFFIXES: [&str; 5] = ["", "k", "M", "B", "T"];
+// Numbers smaller than this are always written out in full
+const SHORTEN_FROM: f64 = 1000.0;
+
+fn scientific(value: f64) -> String {
+    let exponent = value.abs().log10().floor();
+    let mantissa = value / 10f64.powf(exponent);
+    format!("{:.2}e{}", mantissa, exponent)
+}
+
+fn compact(value: f64) -> String {
+    let mut scaled = value;
+    let mut suffix_index = 0;
+    // Just under a thousand would round up to "1000k", so it moves on to the next suffix too
+    while scaled.abs() >= 999.5 && suffix_index + 1 < SUFFIXES.len() {
+        scaled /= 1000.0;
+        suffix_index += 1;
+    }
+    if scaled.abs() >= 999.5 {
+        return scientific(value);
+    }
+    // Keep about three significant digits
+    let text = if scaled.abs() >= 100.0 {
+        format!("{:.0}", scaled)
+    } else if scaled.abs() >= 10.0 {
+        format!("{:.1}", scaled)
+    } else {
+        format!("{:.2}", scaled)
+    };
+    let text = if text.contains('.') { text.trim_end_matches('0').trim_end_matches('.').to_string() } else { text };
+    format!("{}{}", text, SUFFIXES[suffix_index])
+}
+
+// Write a held amount the way the player chose
+pub fn format_quantity(value: &Quantity) -> String {
+    let approximate = to_f64(value);
+    if approximate.abs() < SHORTEN_FROM {
+        return value.to_string();
+    }
+    match current_number_format() {
+        NumberFormat::Full => value.to_string(),
+        NumberFormat::Compact => compact(approximate),
+        NumberFormat::Scientific => scientific(approximate),
+    }
+}
+
+// Amounts written in the catalogue are plain integers
+pub fn format_number(value: i64) -> String {
+    format_quantity(&quantity(value))
+}
+
+// An average, such as the expected outcome of a roll, which can have a fraction
+pub fn format_expected(value: f64) -> String {
+    if value.abs() < SHORTEN_FROM {
+        let text = format!("{:.2}", value);
+        return text.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+    match current_number_format() {
+        NumberFormat::Full => format!("{:.0}", value),
+        NumberFormat::Compact => compact(value),
+        NumberFormat::Scientific => scientific(value),
+    }
+}
@@ -0,0 +1,86 @@
+use crate::core::job::JobId;
+
+// Small deterministic random number generator for jobs with uncertain outcomes.
+// The whole run shares one seed, and every job gets its own generator from the seed, the job's id and how many times it was done before,
+// so replaying the same history with the same seed always rolls the same results.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng {
+            state: seed,
+        }
+    }
+
+    // SplitMix64
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut mixed = self.state;
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        mixed ^ (mixed >> 31)
+    }
+
+    // A number from 0 up to but not including `bound`
+    pub fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            return 0;
+        }
+        self.next_u64() % bound
+    }
+}
+
+// Decides how the uncertain amounts of a job turn out
+#[derive(Clone, Debug)]
+pub enum Roller {
+    Seeded(Rng),
+    // Always the worst outcome, for planning ahead when the rolls aren't known
+    Lowest,
+}
+
+impl Roller {
+    // The roller for the job the `times_done`th time it succeeds in the run.
+    // Undoing, adding or moving other jobs, or failing this one, doesn't change how it rolls.
+    pub fn for_job(seed: u64, job_id: JobId, times_done: usize) -> Roller {
+        let mut mixer = Rng::new(seed ^ job_hash(job_id) ^ (times_done as u64).wrapping_mul(0xD6E8_FEB8_6659_FD93));
+        Roller::Seeded(Rng::new(mixer.next_u64()))
+    }
+
+    // Does something with this percent chance happen?
+    pub fn chance(&mut self, percent: i64) -> bool {
+        match self {
+            Roller::Seeded(rng) => (rng.below(100) as i64) < percent,
+            Roller::Lowest => percent >= 100,
+        }
+    }
+
+    // A number from `min` to `max`, both included
+    pub fn between(&mut self, min: i64, max: i64) -> i64 {
+        if max <= min {
+            return min;
+        }
+        match self {
+            Roller::Seeded(rng) => min + rng.below((max - min) as u64 + 1) as i64,
+            Roller::Lowest => min,
+        }
+    }
+}
+
+// FNV-1a of the id as it is stored in run records, so it is the same on every platform
+fn job_hash(job_id: JobId) -> u64 {
+    let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+    for byte in serde_json::to_string(&job_id).unwrap_or_default().bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+    }
+    hash
+}
+
+// A fresh seed for a new run
+pub fn new_seed(entropy: f64) -> u64 {
+    let mut rng = Rng::new((entropy * u64::MAX as f64) as u64);
+    rng.next_u64()
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use crate::core::job::JobId;
+
+// A job as it was placed in the history, without the texts and deltas that come from the catalogue
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: JobId,
+    pub chosen_alternative: Option<usize>,
+}
+
+// One history step, in a form that can be sent to a server and replayed there
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StepRecord {
+    Job(JobRecord),
+    Jobs(Vec<JobRecord>),
+    Repeat(JobRecord, usize),
+    AddOne(usize),
+    RemoveOne(usize),
+    RemoveCluster(usize),
+    CycleAlternative(usize),
+}
+
+// A finished run, with everything needed to verify it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub scenario: String,
+    pub scenario_version: u32,
+    pub player: String,
+    // The day count the player's browser computed, which a verifier should recompute
+    pub days: usize,
+    pub history: Vec<StepRecord>,
+    // Decides how uncertain amounts roll. It is handed out by the leaderboard, apart from a daily challenge's, which everyone shares.
+    // Runs recorded before there was any randomness have none, which is 0.
+    #[serde(default)]
+    pub seed: u64,
+    // Content packs that were active, written as `id@version`
+    #[serde(default)]
+    pub packs: Vec<String>,
+}
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use crate::core::job::{apply_job, Job, JobId, JobOutput};
+use crate::core::random::Roller;
+use crate::core::record::{JobRecord, StepRecord};
+use crate::core::scenario::Scenario;
+use crate::core::upgrade::{already_done_output, Upgrades};
+
+// The jobs a history expands into, in the order they are executed
+pub struct ExpandedHistory {
+    pub jobs: Vec<Job>,
+    // Which history step put each job in the list. The starting resources don't come from any step.
+    pub origins: Vec<Option<usize>>,
+    // Steps that pointed at a job that doesn't exist in the scenario, and so did nothing
+    pub invalid_steps: Vec<usize>,
+}
+
+// Days spent up to and including each job, and whether the run was won
+pub struct DayCount {
+    pub days_after: Vec<usize>,
+    pub total_days: usize,
+    // Days spent when the winning job was first done
+    pub won_after: Option<usize>,
+    pub win_index: Option<usize>,
+}
+
+// The job as the scenario defines it, or None if the scenario doesn't have it
+pub fn job_from_record(record: &JobRecord, scenario: &Scenario) -> Option<Job> {
+    let mut job = scenario.job(record.id)?;
+    job.chosen_alternative = record.chosen_alternative;
+    Some(job)
+}
+
+// Apply the history to create the job application order
+pub fn expand_history(steps: &[StepRecord], scenario: &Scenario) -> ExpandedHistory {
+    let mut jobs = vec![scenario.starting_resources.clone()];
+    let mut origins = vec![None];
+    let mut invalid_steps = Vec::new();
+    for (step_index, step) in steps.iter().enumerate() {
+        match step {
+            StepRecord::Job(record) => {
+                match job_from_record(record, scenario) {
+                    Some(job) => {
+                        jobs.push(job);
+                        origins.push(Some(step_index));
+                    }
+                    None => invalid_steps.push(step_index),
+                }
+            }
+            StepRecord::Jobs(records) => {
+                let step_jobs = records.iter().map(|record| job_from_record(record, scenario)).collect::<Option<Vec<_>>>();
+                match step_jobs {
+                    Some(step_jobs) => {
+                        for job in step_jobs.into_iter() {
+                            jobs.push(job);
+                            origins.push(Some(step_index));
+                        }
+                    }
+                    None => invalid_steps.push(step_index),
+                }
+            }
+            StepRecord::Repeat(record, times) => {
+                match job_from_record(record, scenario) {
+                    Some(job) => {
+                        for _ in 0..*times {
+                            jobs.push(job.clone());
+                            origins.push(Some(step_index));
+                        }
+                    }
+                    None => invalid_steps.push(step_index),
+                }
+            }
+            StepRecord::AddOne(index) => {
+                match jobs.get(*index).cloned() {
+                    Some(job) => {
+                        jobs.insert(*index, job);
+                        origins.insert(*index, Some(step_index));
+                    }
+                    None => invalid_steps.push(step_index),
+                }
+            }
+            StepRecord::RemoveOne(index) => {
+                if *index < jobs.len() {
+                    jobs.remove(*index);
+                    origins.remove(*index);
+                } else {
+                    invalid_steps.push(step_index);
+                }
+            }
+            StepRecord::RemoveCluster(index) => {
+                if *index < jobs.len() {
+                    let first_job = jobs.remove(*index);
+                    origins.remove(*index);
+                    // The latter elements have moved over now, so we can keep checking this
+                    // slot to find all consecutive similar jobs
+                    while jobs.get(*index).map(|job| job.id == first_job.id).unwrap_or(false) {
+                        jobs.remove(*index);
+                        origins.remove(*index);
+                    }
+                } else {
+                    invalid_steps.push(step_index);
+                }
+            }
+            StepRecord::CycleAlternative(index) => {
+                // Every job in the cluster shares the same pinned alternative so that the row stays merged
+                match jobs.get(*index).cloned() {
+                    Some(first_job) => {
+                        for job in jobs.iter_mut().skip(*index) {
+                            if job.id != first_job.id || job.chosen_alternative != first_job.chosen_alternative {
+                                break;
+                            }
+                            job.cycle_alternative();
+                        }
+                    }
+                    None => invalid_steps.push(step_index),
+                }
+            }
+        }
+    }
+    ExpandedHistory {
+        jobs,
+        origins,
+        invalid_steps,
+    }
+}
+
+// The jobs as they were done, with the upgrades before them applied, and what each one did
+pub struct Replay {
+    pub jobs: Vec<Job>,
+    pub outputs: Vec<JobOutput>,
+    // Upgrades done by the end of the run
+    pub upgrades: Upgrades,
+    // How many times each job succeeded, which decides how the next one rolls
+    pub times_done: BTreeMap<JobId, usize>,
+}
+
+// Execute the jobs in order. A job that fails leaves the resources as they were.
+// Uncertain amounts are rolled from the run's seed, the job and how many times it succeeded before, so the same history always plays out the same.
+// Every job is changed by the upgrades that succeeded before it.
+pub fn replay_jobs(jobs: &[Job], seed: u64) -> Result<Replay, String> {
+    let mut resources = BTreeMap::new();
+    let mut durability = BTreeMap::new();
+    let mut effects = BTreeMap::new();
+    let mut upgrades = Upgrades::default();
+    let mut done_jobs = Vec::new();
+    let mut outputs = Vec::new();
+    let mut times_done = BTreeMap::new();
+    for job in jobs.iter() {
+        let job = upgrades.apply(job);
+        let output = if upgrades.is_done(&job) {
+            already_done_output(resources.clone(), durability.clone(), effects.clone())
+        } else {
+            apply_job(resources.clone(), durability.clone(), effects.clone(), &job, &mut Roller::for_job(seed, job.id, *times_done.get(&job.id).unwrap_or(&0)))?
+        };
+        if output.is_ok() {
+            *times_done.entry(job.id).or_insert(0) += 1;
+            resources = output.resources_after.clone();
+            durability = output.durability_after.clone();
+            effects = output.effects_after.clone();
+            upgrades.record(&job);
+        }
+        done_jobs.push(job);
+        outputs.push(output);
+    }
+    Ok(Replay {
+        jobs: done_jobs,
+        outputs,
+        upgrades,
+        times_done,
+    })
+}
+
+pub fn run_jobs(jobs: &[Job], seed: u64) -> Result<Vec<JobOutput>, String> {
+    replay_jobs(jobs, seed).map(|replay| replay.outputs)
+}
+
+pub fn count_days(jobs: &[Job], outputs: &[JobOutput], win_job: JobId) -> DayCount {
+    let mut total_days = 0;
+    let mut days_after = Vec::new();
+    let mut won_after = None;
+    let mut win_index = None;
+    for (job_index, (job, output)) in jobs.iter().zip(outputs.iter()).enumerate() {
+        // The starting resources are not a job the player spent a day on
+        if output.is_ok() && job_index > 0 {
+            total_days += 1;
+            if job.id == win_job && won_after.is_none() {
+                won_after = Some(total_days);
+                win_index = Some(job_index);
+            }
+        }
+        days_after.push(total_days);
+    }
+    DayCount {
+        days_after,
+        total_days,
+        won_after,
+        win_index,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::catalogue::create_job;
+    use crate::core::job::starting_resources;
+    use crate::core::resource::Resource;
+    use crate::core::scenario::facility;
+
+    // Which catches also brought in a scary fragment
+    fn scary_catches(jobs: &[Job], seed: u64) -> Vec<bool> {
+        run_jobs(jobs, seed).unwrap().iter()
+            .zip(jobs.iter())
+            .filter(|(output, job)| job.id == JobId::FragmentCatch && output.is_ok())
+            .map(|(output, _job)| output.main_output.changed_resources.contains(&Resource::ScaryFragment))
+            .collect()
+    }
+
+    #[test]
+    fn other_jobs_dont_change_the_rolls() {
+        facility().activate();
+        let mut jobs = vec![starting_resources()];
+        jobs.extend(std::iter::repeat_n(create_job(JobId::FragmentCatch), 12));
+        let rolls = scary_catches(&jobs, 7);
+        assert!(rolls.contains(&true));
+        assert!(rolls.contains(&false));
+
+        // A craft in the middle, and a sale that fails since there is nothing to sell
+        let mut with_others = jobs.clone();
+        with_others.insert(4, create_job(JobId::DayDreamCraft));
+        with_others.insert(1, create_job(JobId::DayDreamSell));
+        assert_eq!(scary_catches(&with_others, 7), rolls);
+
+        // Undoing the last catch leaves the ones before it alone
+        assert_eq!(scary_catches(&jobs[..12], 7), rolls[..11]);
+        assert_ne!(scary_catches(&jobs, 8), rolls);
+    }
+}
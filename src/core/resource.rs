@@ -1,7 +1,13 @@
+use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+use serde::{Deserialize, Serialize};
 use crate::core::amount::Amount;
-use crate::core::job::Job;
+use crate::core::custom::{CustomId, ResourceDefinition};
+use crate::core::inventory::DurabilitySet;
+use crate::core::locale::resource_name;
+use crate::core::quantity::Quantity;
 
 pub struct ResourceAttributes {
     pub upkeep: Vec<Vec<(Resource, Amount)>>,
@@ -9,26 +15,22 @@ pub struct ResourceAttributes {
     // Display as a name on each row intead of reserving a column
     pub display_as_name: bool,
     pub row: usize,
+    // How many times a single unit can be used as a catalyst before it breaks. None lasts forever.
+    pub durability: Option<i64>,
 }
 
-pub type ResourceSet = BTreeMap<Resource, i64>;
-pub type ResourceList = Vec<(Resource, i64)>;
+pub type ResourceSet = BTreeMap<Resource, Quantity>;
 pub type AttributeMappings = BTreeMap<Resource, ResourceAttributes>;
 
-#[derive(Clone, Debug)]
-pub enum CombinationResult {
-    Job(Job, Option<String>),
-    Text(String),
-    Nothing,
-}
-
 pub struct DeltaOutput {
     pub status: DeltaOutputStatus,
     pub changed_resources: Vec<Resource>,
     pub resources_after: ResourceSet,
+    pub durability_after: DurabilitySet,
 }
 
-
+// Which alternative was applied, X and what failed are kept for inspecting outputs, even though the game only needs to know if it worked
+#[allow(dead_code)]
 pub enum DeltaOutputStatus {
     // All requirements were fulfilled for some set
     Success {
@@ -38,7 +40,7 @@ pub enum DeltaOutputStatus {
     // Included is the value that was used for X
     SuccessX {
         delta_index: usize,
-        x: i64,
+        x: Quantity,
     },
     // The requirements failed, so the entire task failed
     Failure {
@@ -58,37 +60,6 @@ impl DeltaOutput {
     pub fn is_ok(&self) -> bool {
         self.status.is_ok()
     }
-    pub fn delta_index(&self) -> usize {
-        self.status.delta_index()
-    }
-    pub fn failing_resources(&self) -> Vec<Resource> {
-        self.status.failing_resources()
-    }
-    pub fn is_mergeable(&self, other: &DeltaOutput) -> bool {
-        match (&self.status, &other.status) {
-            (
-                DeltaOutputStatus::Success { delta_index: first_index },
-                DeltaOutputStatus::Success { delta_index: second_index }
-            ) => {
-                if first_index != second_index {
-                    return false;
-                }
-            }
-            (
-                DeltaOutputStatus::SuccessX { delta_index: first_index, .. },
-                DeltaOutputStatus::SuccessX { delta_index: second_index, .. }
-            ) => {
-                if first_index != second_index {
-                    return false;
-                }
-            }
-            (DeltaOutputStatus::Failure { .. }, DeltaOutputStatus::Failure { .. }) => {}
-            (_, _) => {
-                return false;
-            }
-        }
-        true
-    }
 }
 
 impl DeltaOutputStatus {
@@ -99,23 +70,6 @@ impl DeltaOutputStatus {
             DeltaOutputStatus::Failure { .. } => false,
         }
     }
-    // Get which delta index
-    pub fn delta_index(&self) -> usize {
-        match self {
-            DeltaOutputStatus::Success { delta_index } => delta_index.clone(),
-            DeltaOutputStatus::SuccessX { delta_index, .. } => delta_index.clone(),
-            DeltaOutputStatus::Failure { .. } => { 0 }
-        }
-    }
-    pub fn failing_resources(&self) -> Vec<Resource> {
-        match self {
-            DeltaOutputStatus::Success { .. } => { Vec::new() }
-            DeltaOutputStatus::SuccessX { .. } => { Vec::new() }
-            DeltaOutputStatus::Failure { failing_resources, .. } => {
-                failing_resources.clone()
-            }
-        }
-    }
 }
 
 // impl Job {
@@ -147,7 +101,7 @@ impl DeltaOutputStatus {
 //     }
 // }
 
-#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Resource {
     Coin,
     Fragment,
@@ -158,26 +112,88 @@ pub enum Resource {
     ComfortDream,
     ScaryFragment,
     Nightmare,
+    // Defined by a scenario file rather than here, see `core::custom`
+    Custom(CustomId),
+}
+
+pub fn all_resources() -> Vec<Resource> {
+    vec![
+        Resource::Coin,
+        Resource::Fragment,
+        Resource::DayDream,
+        Resource::GlassBottle,
+        Resource::Dream,
+        Resource::SoothingMemory,
+        Resource::ComfortDream,
+        Resource::ScaryFragment,
+        Resource::Nightmare,
+    ]
 }
 
-pub fn attributes() -> AttributeMappings {
+thread_local! {
+    // Jobs look the attributes up on every step, so they are worked out once when a scenario defines its resources
+    static ATTRIBUTES: RefCell<Rc<AttributeMappings>> = RefCell::new(Rc::new(built_in_attributes()));
+}
+
+// Built in attributes, overridden by whatever the scenario being played defines
+pub fn attributes() -> Rc<AttributeMappings> {
+    ATTRIBUTES.with(|attributes| attributes.borrow().clone())
+}
+
+// Called whenever the defined resources change
+pub fn cache_attributes(definitions: &[ResourceDefinition]) {
+    ATTRIBUTES.with(|attributes| *attributes.borrow_mut() = Rc::new(attributes_with(definitions)));
+}
+
+// Built in attributes, overridden by the given definitions
+pub fn attributes_with(definitions: &[ResourceDefinition]) -> AttributeMappings {
+    let mut attributes = built_in_attributes();
+    for definition in definitions.iter() {
+        attributes.insert(definition.id, definition.attributes());
+    }
+    attributes
+}
+
+fn built_in_attributes() -> AttributeMappings {
     BTreeMap::from([
+        (Resource::GlassBottle, ResourceAttributes {
+            upkeep: vec![],
+            visible: true,
+            display_as_name: true,
+            row: 0,
+            durability: Some(3),
+        }),
     ])
 }
 
 impl Display for Resource {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Resource::Coin => f.write_str("Coin"),
-            Resource::Fragment => f.write_str("Fragment"),
-            Resource::DayDream => f.write_str("Day Dream"),
-            Resource::GlassBottle => f.write_str("Glass bottle"),
-            Resource::Dream => f.write_str("Dream"),
-            Resource::SoothingMemory => f.write_str("Soothing memory"),
-            Resource::ComfortDream => f.write_str("Comfort dream"),
-            Resource::ScaryFragment => f.write_str("Scary fragment"),
-            Resource::Nightmare => f.write_str("Nightmare"),
-        }
+        f.write_str(resource_name(self, 1))
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::custom::define_resources;
+
+    #[test]
+    fn attributes_follow_the_defined_resources() {
+        let jar = Resource::Custom(CustomId::new("test:jar"));
+        define_resources(&[ResourceDefinition {
+            id: jar,
+            name: "Jar".to_string(),
+            plural_name: "Jars".to_string(),
+            visible: true,
+            display_as_name: false,
+            row: 0,
+            durability: Some(2),
+            upkeep: vec![],
+        }]);
+        assert_eq!(attributes().get(&jar).and_then(|attribute| attribute.durability), Some(2));
+        assert_eq!(attributes().get(&Resource::GlassBottle).and_then(|attribute| attribute.durability), Some(3));
+        define_resources(&[]);
+        assert!(attributes().get(&jar).is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::BTreeSet;
+use serde::{Deserialize, Serialize};
+use crate::core::amount::Amount;
+use crate::core::custom::{define_resources, JobDefinition, ResourceDefinition};
+use crate::core::daily::{daily_date, daily_scenario};
+use crate::core::generator::{generated_settings, generate};
+use crate::core::catalogue::{all_job_ids, create_job, SCENARIO_NAME, SCENARIO_VERSION, WIN_JOB_ID};
+use crate::core::job::{starting_resources, Job, JobId};
+use crate::core::locale::{fill, Text};
+use crate::core::resource::Resource;
+
+// Everything a run is played against: which jobs exist, and what the player starts with.
+// Histories only store job ids, so replaying a run looks the jobs up here.
+#[derive(Clone, Debug)]
+pub struct Scenario {
+    // Runs are stored and ranked per scenario name
+    pub name: String,
+    pub version: u32,
+    // Resources the scenario defines itself. The built in ones don't need a definition.
+    pub resources: Vec<ResourceDefinition>,
+    pub jobs: Vec<Job>,
+    pub starting_resources: Job,
+    // Doing this job wins the run
+    pub win_job: JobId,
+    // Content packs added on top, written as `id@version`
+    pub packs: Vec<String>,
+}
+
+// A scenario written as data, which is how scenarios are saved, shared and generated
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioFile {
+    pub name: String,
+    pub version: u32,
+    #[serde(default)]
+    pub resources: Vec<ResourceDefinition>,
+    pub jobs: Vec<JobDefinition>,
+    #[serde(default)]
+    pub starting_resources: Vec<Vec<(Resource, Amount)>>,
+    pub win_job: JobId,
+}
+
+impl Scenario {
+    // The job as this scenario defines it, or None if it isn't part of the scenario
+    pub fn job(&self, id: JobId) -> Option<Job> {
+        self.jobs.iter().find(|job| job.id == id).cloned()
+    }
+
+    // Jobs the player has found, in scenario order. A job is found once every resource one of its alternatives
+    // needs has been held, so jobs that need nothing are there from the start.
+    pub fn discoverable_jobs(&self, held: &BTreeSet<Resource>) -> Vec<Job> {
+        let needs = |amount: &Amount| matches!(amount, Amount::Spend(_) | Amount::Catalyst(_) | Amount::SpendX(_) | Amount::CatalystX(_) | Amount::Repair(_));
+        self.jobs.iter()
+            .filter(|job| job.saved)
+            .filter(|job| job.end_deltas.is_empty() || job.end_deltas.iter().any(|delta| {
+                delta.iter().all(|(resource, amount)| !needs(amount) || held.contains(resource))
+            }))
+            .cloned()
+            .collect()
+    }
+
+    // Make the resources of this scenario the ones every resource lookup sees. Call before playing or replaying it.
+    pub fn activate(&self) {
+        define_resources(&self.resources);
+    }
+
+    // Every resource the scenario mentions, in order
+    pub fn resource_ids(&self) -> Vec<Resource> {
+        let mut resources = self.resources.iter().map(|definition| definition.id).collect::<Vec<_>>();
+        let deltas = self.jobs.iter()
+            .chain(std::iter::once(&self.starting_resources))
+            .flat_map(|job| job.start_deltas.iter().chain(job.end_deltas.iter()))
+            .chain(self.resources.iter().flat_map(|definition| definition.upkeep.iter()));
+        for delta in deltas {
+            for (resource, _amount) in delta.iter() {
+                if !resources.contains(resource) {
+                    resources.push(*resource);
+                }
+            }
+        }
+        resources.sort();
+        resources
+    }
+
+    pub fn to_file(&self) -> ScenarioFile {
+        ScenarioFile {
+            name: self.name.clone(),
+            version: self.version,
+            resources: self.resources.clone(),
+            jobs: self.jobs.iter().map(JobDefinition::from_job).collect(),
+            starting_resources: self.starting_resources.end_deltas.clone(),
+            win_job: self.win_job,
+        }
+    }
+
+    pub fn from_file(file: &ScenarioFile) -> Scenario {
+        let mut starting = starting_resources();
+        starting.end_deltas = file.starting_resources.clone();
+        Scenario {
+            name: file.name.clone(),
+            version: file.version,
+            resources: file.resources.clone(),
+            jobs: file.jobs.iter().map(JobDefinition::to_job).collect(),
+            starting_resources: starting,
+            win_job: file.win_job,
+            packs: vec![],
+        }
+    }
+}
+
+pub fn scenario_to_json(scenario: &Scenario) -> Result<String, String> {
+    serde_json::to_string_pretty(&scenario.to_file()).map_err(|error| error.to_string())
+}
+
+// The hand-authored facility
+pub fn facility() -> Scenario {
+    Scenario {
+        name: SCENARIO_NAME.to_string(),
+        version: SCENARIO_VERSION,
+        resources: vec![],
+        jobs: all_job_ids().into_iter().map(create_job).collect(),
+        starting_resources: starting_resources(),
+        win_job: WIN_JOB_ID,
+        packs: vec![],
+    }
+}
+
+// Rebuild the scenario a run was recorded against from its name alone
+pub fn scenario_by_name(name: &str) -> Result<Scenario, String> {
+    if name == SCENARIO_NAME {
+        return Ok(facility());
+    }
+    if let Some(date) = daily_date(name) {
+        return daily_scenario(date);
+    }
+    match generated_settings(name) {
+        Some(settings) => generate(&settings).map(|generated| generated.scenario),
+        None => Err(fill(Text::UnknownScenario, &[&name])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jobs_are_found_once_their_resources_are_held() {
+        let scenario = facility();
+        let ids = |held: &[Resource]| scenario.discoverable_jobs(&held.iter().cloned().collect()).into_iter().map(|job| job.id).collect::<Vec<_>>();
+        assert_eq!(ids(&[]), vec![JobId::FragmentCatch]);
+        let found = ids(&[Resource::Fragment]);
+        assert!(found.contains(&JobId::DayDreamCraft));
+        assert!(!found.contains(&JobId::Retire));
+        assert!(ids(&[Resource::Coin]).contains(&JobId::Retire));
+    }
+}
@@ -0,0 +1,1018 @@
+use std::collections::{BTreeMap, BTreeSet};
+use crate::core::amount::Amount;
+use crate::core::effect::{affected_amount, EffectId, EffectSet};
+use crate::core::inventory::DurabilitySet;
+use crate::core::job::{apply_job, Job, JobId, JobOutput};
+use crate::core::quantity::{quantity, to_count, Quantity};
+use crate::core::random::Roller;
+use crate::core::record::JobRecord;
+use crate::core::resource::{attributes, Resource, ResourceSet};
+use crate::core::scenario::Scenario;
+use crate::core::upgrade::Upgrades;
+
+// Stop searching after this many distinct states, so that a bad scenario can't hang the game
+pub const DEFAULT_MAX_STATES: usize = 100_000;
+// States kept to compare new ones against for each state apart from the plentiful amounts. Comparing with fewer only drops fewer states,
+// and the comparisons are what the search spends its time on.
+const MAX_FRONT: usize = 256;
+
+// Resources that ran out are dropped, so that the same holdings are always the same state
+fn held(mut resources: ResourceSet) -> ResourceSet {
+    resources.retain(|_resource, amount| *amount != quantity(0));
+    resources
+}
+
+// Add the job after the jobs that make what it spends, or return false if something is needed to make itself
+fn order_jobs(index: usize, jobs: &[Job], makers: &BTreeMap<Resource, usize>, visiting: &mut BTreeSet<usize>, order: &mut Vec<usize>) -> bool {
+    if order.contains(&index) {
+        return true;
+    }
+    if !visiting.insert(index) {
+        return false;
+    }
+    for (resource, amount) in jobs[index].end_deltas[0].iter() {
+        if let (Amount::Spend(_), Some(maker)) = (amount, makers.get(resource)) {
+            if !order_jobs(*maker, jobs, makers, visiting, order) {
+                return false;
+            }
+        }
+    }
+    order.push(index);
+    true
+}
+
+// In a crafting tree every job has a single alternative that only gains and spends, every resource is made by one job at most,
+// and nothing is needed to make itself. Every job then has to be done as often as it takes to make what the jobs after it spend,
+// starting from the winning job, and doing the jobs from gathering up to winning always works. So the plan is counted instead of searched for.
+// None for any other scenario, or if the plan doesn't win after all.
+fn crafting_tree_plan(scenario: &Scenario, start: &JobOutput) -> Result<Option<Vec<JobRecord>>, String> {
+    let jobs = &scenario.jobs;
+    let attributes = attributes();
+    let plain = scenario.resource_ids().iter()
+            .filter_map(|resource| attributes.get(resource))
+            .all(|attribute| attribute.upkeep.is_empty() && attribute.durability.is_none())
+        && jobs.iter().all(|job| {
+            !job.is_upgrade() && job.effects.is_empty() && job.start_deltas.is_empty() && job.end_deltas.len() == 1
+                && job.end_deltas[0].iter().all(|(_resource, amount)| matches!(amount, Amount::Gain(number) | Amount::Spend(number) if *number > 0))
+        });
+    if !plain {
+        return Ok(None);
+    }
+    let mut makers = BTreeMap::new();
+    for (index, job) in jobs.iter().enumerate() {
+        for (resource, amount) in job.end_deltas[0].iter() {
+            if let Amount::Gain(_) = amount {
+                if makers.insert(*resource, index).is_some() {
+                    return Ok(None);
+                }
+            }
+        }
+    }
+    let win_index = match jobs.iter().position(|job| job.id == scenario.win_job) {
+        Some(win_index) => win_index,
+        None => return Ok(None),
+    };
+    let mut order = Vec::new();
+    if !order_jobs(win_index, jobs, &makers, &mut BTreeSet::new(), &mut order) {
+        return Ok(None);
+    }
+
+    // Every job comes before the jobs that make what it spends, so all of what a resource is needed for is known before its maker
+    let mut times = vec![0; jobs.len()];
+    times[win_index] = 1;
+    let mut needed: BTreeMap<Resource, i64> = BTreeMap::new();
+    for index in order.iter().rev() {
+        let delta = &jobs[*index].end_deltas[0];
+        if *index != win_index {
+            times[*index] = delta.iter()
+                .filter_map(|(resource, amount)| match amount {
+                    Amount::Gain(gain) => {
+                        let held = start.resources_after.get(resource).map(to_count).unwrap_or(0);
+                        let missing = (needed.get(resource).copied().unwrap_or(0) - held).max(0);
+                        Some(missing.saturating_add(gain - 1) / gain)
+                    }
+                    _ => None,
+                })
+                .max()
+                .unwrap_or(0);
+        }
+        for (resource, amount) in delta.iter() {
+            if let Amount::Spend(cost) = amount {
+                let total = needed.entry(*resource).or_insert(0);
+                *total = (*total).saturating_add(times[*index].saturating_mul(*cost));
+            }
+        }
+    }
+    let plan = order.iter()
+        .flat_map(|index| std::iter::repeat_n(jobs[*index].id, times[*index] as usize))
+        .collect::<Vec<_>>();
+
+    // Resources that nothing makes could still run short, which only doing the plan shows
+    let mut resources = start.resources_after.clone();
+    let mut durability = start.durability_after.clone();
+    let mut effects = start.effects_after.clone();
+    for job_id in plan.iter() {
+        let job = &jobs[order.iter().copied().find(|index| jobs[*index].id == *job_id).unwrap_or(win_index)];
+        let output = apply_job(resources, durability, effects, job, &mut Roller::Lowest)?;
+        if !output.is_ok() {
+            return Ok(None);
+        }
+        resources = output.resources_after;
+        durability = output.durability_after;
+        effects = output.effects_after;
+    }
+    Ok(Some(plan.into_iter().map(|id| JobRecord { id, chosen_alternative: None }).collect()))
+}
+
+// Find the shortest list of jobs that ends with the winning job, or the shortest found within `max_states` states when there are too many to try.
+// Every job takes one day, so the length of the plan is the par for the scenario.
+// Uncertain amounts always roll their worst, so the plan works whatever the seed.
+// Amounts are never capped, since saving up more than any one job needs can pay off over several jobs,
+// but states that can't do better than another are dropped, see `search`.
+// Crafting trees, like generated scenarios, are counted rather than searched, see `crafting_tree_plan`.
+// The scenario has to be active, since upkeep and durability come from its resources.
+pub fn solve(scenario: &Scenario, max_states: usize) -> Result<Option<Vec<JobRecord>>, String> {
+    let start = apply_job(ResourceSet::new(), DurabilitySet::new(), EffectSet::new(), &scenario.starting_resources, &mut Roller::Lowest)?;
+    match crafting_tree_plan(scenario, &start)? {
+        Some(plan) => Ok(Some(plan)),
+        None => search(scenario, start, max_states),
+    }
+}
+
+// Resources that having more of never hurts: jobs only gain, spend or need them, nothing has upkeep on them and they don't wear out.
+// Held amounts of these are compared rather than matched, see `search`.
+fn plentiful_resources(scenario: &Scenario) -> Vec<Resource> {
+    let attributes = attributes();
+    let mut limited = BTreeSet::new();
+    for (resource, attribute) in attributes.iter() {
+        if !attribute.upkeep.is_empty() || attribute.durability.is_some() {
+            limited.insert(*resource);
+        }
+        limited.extend(attribute.upkeep.iter().flatten().map(|(resource, _amount)| *resource));
+    }
+    for (resource, amount) in every_delta(scenario) {
+        if !matches!(amount, Amount::Gain(_) | Amount::Spend(_) | Amount::Catalyst(_) | Amount::Chance(_, _) | Amount::Between(_, _)) {
+            limited.insert(*resource);
+        }
+    }
+    scenario.resource_ids().into_iter()
+        .filter(|resource| !limited.contains(resource))
+        .collect()
+}
+
+// Resources whose held count only moves by what the jobs say, so that how much a job can add to them is known beforehand.
+// Upkeep and amounts that depend on other amounts are left out.
+fn counted_resources(scenario: &Scenario) -> Vec<Resource> {
+    let mut uncounted = attributes().values()
+        .flat_map(|attribute| attribute.upkeep.iter().flatten().map(|(resource, _amount)| *resource))
+        .collect::<BTreeSet<_>>();
+    for (resource, amount) in every_delta(scenario) {
+        if matches!(amount, Amount::GainX(_) | Amount::SpendX(_) | Amount::CatalystX(_) | Amount::Set(_)) {
+            uncounted.insert(*resource);
+        }
+    }
+    scenario.resource_ids().into_iter()
+        .filter(|resource| !uncounted.contains(resource))
+        .collect()
+}
+
+fn every_delta(scenario: &Scenario) -> impl Iterator<Item = &(Resource, Amount)> {
+    scenario.jobs.iter()
+        .chain(std::iter::once(&scenario.starting_resources))
+        .flat_map(|job| job.start_deltas.iter().chain(job.end_deltas.iter()))
+        .flatten()
+}
+
+// Each alternative of a job is tried on its own, since a player can pick any that works
+fn alternatives(job: Job) -> Vec<Job> {
+    if job.end_deltas.len() < 2 {
+        return vec![job];
+    }
+    (0..job.end_deltas.len())
+        .map(|index| {
+            let mut alternative = job.clone();
+            alternative.chosen_alternative = Some(index);
+            alternative
+        })
+        .collect()
+}
+
+// The job as every order of every choice of the upgrades that change it would leave it, since the order can matter once an amount runs out
+fn upgraded_versions(job: &Job, upgrades: &[&Job]) -> Vec<Job> {
+    let mut versions = vec![job.clone()];
+    for (index, upgrade) in upgrades.iter().enumerate() {
+        let applied = Upgrades { done: vec![], modifiers: upgrade.modifiers.clone() }.apply(job);
+        let rest = upgrades.iter().enumerate().filter(|(other, _upgrade)| *other != index).map(|(_other, upgrade)| *upgrade).collect::<Vec<_>>();
+        versions.extend(upgraded_versions(&applied, &rest));
+    }
+    versions
+}
+
+const EPSILON: f64 = 1e-9;
+
+// Maximise `objective · x` over x ≥ 0 with `row · x ≤ bound` for every row, with the simplex method.
+// Every bound is at least zero, so x = 0 is where it starts. None if the objective has no maximum.
+// Along with x comes the weight of each row in the best mix of rows, which is what minimising the other way round would give.
+fn maximise(objective: &[f64], rows: &[(Vec<f64>, f64)]) -> Option<(Vec<f64>, Vec<f64>)> {
+    let variables = objective.len();
+    let width = variables + rows.len() + 1;
+    let mut table = rows.iter()
+        .enumerate()
+        .map(|(index, (row, bound))| {
+            let mut line = vec![0.0; width];
+            line[..variables].copy_from_slice(row);
+            line[variables + index] = 1.0;
+            line[width - 1] = *bound;
+            line
+        })
+        .collect::<Vec<_>>();
+    let mut costs = vec![0.0; width];
+    for (cost, weight) in costs.iter_mut().zip(objective.iter()) {
+        *cost = -weight;
+    }
+    table.push(costs);
+    let last = rows.len();
+    let mut basis = (variables..variables + rows.len()).collect::<Vec<_>>();
+    // Always taking the first column and row that will do keeps it from going round in circles
+    for _step in 0..10_000 {
+        let column = match (0..width - 1).find(|column| table[last][*column] < -EPSILON) {
+            Some(column) => column,
+            None => break,
+        };
+        let mut pivot: Option<(usize, f64)> = None;
+        for row in 0..last {
+            if table[row][column] > EPSILON {
+                let ratio = table[row][width - 1] / table[row][column];
+                let better = match pivot {
+                    None => true,
+                    Some((best, best_ratio)) => ratio < best_ratio - EPSILON || (ratio < best_ratio + EPSILON && basis[row] < basis[best]),
+                };
+                if better {
+                    pivot = Some((row, ratio));
+                }
+            }
+        }
+        let (pivot_row, _ratio) = pivot?;
+        let divisor = table[pivot_row][column];
+        for entry in table[pivot_row].iter_mut() {
+            *entry /= divisor;
+        }
+        let pivot_line = table[pivot_row].clone();
+        for (row, line) in table.iter_mut().enumerate() {
+            let factor = line[column];
+            if row != pivot_row && factor != 0.0 {
+                for (entry, pivot_entry) in line.iter_mut().zip(pivot_line.iter()) {
+                    *entry -= factor * pivot_entry;
+                }
+            }
+        }
+        basis[pivot_row] = column;
+    }
+    let mut solution = vec![0.0; variables];
+    for (row, variable) in basis.iter().enumerate() {
+        if *variable < variables {
+            solution[*variable] = table[row][width - 1].max(0.0);
+        }
+    }
+    let weights = table[last][variables..width - 1].iter().map(|weight| weight.max(0.0)).collect();
+    Some((solution, weights))
+}
+
+fn full_durability(resource: &Resource) -> Option<i64> {
+    attributes().get(resource).and_then(|attribute| attribute.durability)
+}
+
+fn dot(row: &[f64], prices: &[f64]) -> f64 {
+    row.iter().zip(prices.iter()).map(|(coefficient, price)| coefficient * price).sum()
+}
+
+// The most each way of doing the job could add to every counted resource and effect, with the alternative it is.
+// Effects come after the resources. Every alternative counts, with any of the given upgrades done in any order
+// and any effects active, and uncertain amounts roll their worst.
+fn gain_rows(job: &Job, upgrades: &[&Job], resources: &[Resource], effects: &[EffectId]) -> Vec<(usize, Vec<f64>)> {
+    let mut rows = Vec::new();
+    for version in upgraded_versions(job, upgrades) {
+        for active_mask in 0..1usize << effects.len() {
+            let active = effects.iter()
+                .enumerate()
+                .filter(|(index, _effect)| active_mask & (1 << index) != 0)
+                .map(|(_index, effect)| (*effect, 1))
+                .collect::<EffectSet>();
+            for (alternative, delta) in version.end_deltas.iter().enumerate() {
+                let mut row = vec![0.0; resources.len() + effects.len()];
+                for (resource, amount) in delta.iter() {
+                    if let Some(index) = resources.iter().position(|counted| counted == resource) {
+                        // A new unit brings all its uses, a used or spent one loses at least one, and a repaired one gets back all but one at most
+                        let full = full_durability(resource).unwrap_or(1) as f64;
+                        row[index] += match affected_amount(&active, version.id, resource, amount) {
+                            Amount::Gain(number) => number as f64,
+                            Amount::Spend(number) | Amount::Catalyst(number) if full > 1.0 => -number as f64 / full,
+                            Amount::Spend(number) => -number as f64,
+                            Amount::Repair(number) => number as f64 * (full - 1.0) / full,
+                            Amount::Chance(number, percent) if percent >= 100 => number as f64,
+                            Amount::Between(min, _max) => min as f64,
+                            _ => 0.0,
+                        };
+                    }
+                }
+                // An active effect loses at least a time slot to any job that takes time, and a new one adds at most its length
+                for (index, effect) in effects.iter().enumerate() {
+                    let granted = version.effects.iter()
+                        .filter(|(granted, _time_slots)| granted == effect)
+                        .map(|(_granted, time_slots)| *time_slots)
+                        .max();
+                    row[resources.len() + index] = match (active.contains_key(effect), granted) {
+                        (true, Some(time_slots)) => time_slots as f64 - 1.0,
+                        (true, None) => -(version.total_time_slots.min(1) as f64),
+                        (false, Some(time_slots)) => time_slots as f64,
+                        (false, None) => 0.0,
+                    };
+                }
+                rows.push((alternative, row));
+            }
+        }
+    }
+    rows
+}
+
+// A lower bound on the days still needed to win, for runs that end up with a given set of upgrades.
+// Resources, and the time slots left of effects, get prices such that no job other than the upgrades adds more than one day's worth
+// to what is held, so the days left are at least what the winning job needs minus what is held, plus the days and worth lost to the
+// upgrades still to do. Units that wear out are worth their uses left, since that is what using them takes away.
+// When `free_upgrades` is set, upgrades are left out and count as ordinary jobs instead.
+struct UpgradeModel {
+    upgrades: Vec<JobId>,
+    free_upgrades: bool,
+    // Each row keeps a way of doing a job to a day's worth at most, or the goal below what a way of winning needs
+    rows: Vec<(Vec<f64>, f64)>,
+    // The job and alternative of each row that has one
+    row_jobs: Vec<(JobId, Option<usize>)>,
+    needs: Vec<Vec<f64>>,
+    upgrade_gains: Vec<(JobId, Vec<Vec<f64>>)>,
+}
+
+// The model with prices worked out for one state
+struct UpgradeBound {
+    upgrades: Vec<JobId>,
+    prices: Vec<f64>,
+    goal: f64,
+    losses: Vec<(JobId, f64)>,
+    // How often each job and alternative is done in a fastest run, as far as the prices can tell
+    counts: BTreeMap<(JobId, Option<usize>), f64>,
+}
+
+impl UpgradeBound {
+    fn shortfall(&self, holdings: &[f64], upgrades: &Upgrades) -> f64 {
+        let losses = self.losses.iter()
+            .filter(|(upgrade, _loss)| !upgrades.done.contains(upgrade))
+            .map(|(_upgrade, loss)| loss)
+            .sum::<f64>();
+        self.goal - dot(holdings, &self.prices) + losses
+    }
+}
+
+fn upgrade_model(scenario: &Scenario, chosen: &[&Job], free_upgrades: bool, resources: &[Resource], effects: &[EffectId]) -> Option<UpgradeModel> {
+    let counted = resources.len() + effects.len();
+    let modifying = |job: &Job| chosen.iter().copied().filter(|upgrade| upgrade.modifiers.iter().any(|modifier| modifier.job() == job.id)).collect::<Vec<_>>();
+
+    let mut rows = Vec::new();
+    let mut row_jobs = Vec::new();
+    for job in scenario.jobs.iter().filter(|job| job.id != scenario.win_job && (free_upgrades || !job.is_upgrade())) {
+        for (alternative, mut row) in gain_rows(job, &modifying(job), resources, effects) {
+            // The goal comes after the prices
+            row.push(0.0);
+            rows.push((row, 1.0));
+            row_jobs.push((job.id, if job.end_deltas.len() > 1 { Some(alternative) } else { None }));
+        }
+    }
+    // The goal is at most what any way of winning needs to be held
+    let win_job = scenario.jobs.iter().find(|job| job.id == scenario.win_job)?;
+    let mut needs = Vec::new();
+    for version in upgraded_versions(win_job, &modifying(win_job)) {
+        for delta in version.end_deltas.iter() {
+            let mut need = vec![0.0; counted];
+            for (resource, amount) in delta.iter() {
+                if let (Some(index), Amount::Spend(number) | Amount::Catalyst(number)) = (resources.iter().position(|counted| counted == resource), amount) {
+                    need[index] += *number as f64 / full_durability(resource).unwrap_or(1) as f64;
+                }
+            }
+            let mut row = need.iter().map(|number| -number).collect::<Vec<_>>();
+            row.push(1.0);
+            rows.push((row, 0.0));
+            needs.push(need);
+        }
+    }
+    let upgrade_gains = if free_upgrades {
+        vec![]
+    } else {
+        chosen.iter()
+            .map(|upgrade| (upgrade.id, gain_rows(upgrade, &modifying(upgrade), resources, effects).into_iter().map(|(_alternative, row)| row).collect()))
+            .collect()
+    };
+    Some(UpgradeModel {
+        upgrades: chosen.iter().map(|upgrade| upgrade.id).collect(),
+        free_upgrades,
+        rows,
+        row_jobs,
+        needs,
+        upgrade_gains,
+    })
+}
+
+impl UpgradeModel {
+    // The prices that make the bound as high as possible for the state, or None if no job could ever bring the winning job closer
+    fn bound(&self, holdings: &[f64], upgrades: &Upgrades) -> Option<UpgradeBound> {
+        let mut objective = holdings.iter().map(|held| -held).collect::<Vec<_>>();
+        // Upgrades still to do lower the bound by what they cost. Each way of doing one would be a different objective, so the first
+        // stands for them all here, and the losses below are worked out from the prices as found.
+        for (_upgrade, gains) in self.upgrade_gains.iter().filter(|(upgrade, _gains)| !upgrades.done.contains(upgrade)) {
+            if let Some(gain) = gains.first() {
+                for (weight, coefficient) in objective.iter_mut().zip(gain.iter()) {
+                    *weight += coefficient;
+                }
+            }
+        }
+        objective.push(1.0);
+        let (mut prices, weights) = maximise(&objective, &self.rows)?;
+        prices.pop();
+
+        // Rounding can leave a job slightly over a day's worth, so the prices are scaled down until none is
+        let largest = self.rows.iter()
+            .filter(|(_row, bound)| *bound > 0.0)
+            .map(|(row, _bound)| dot(row, &prices))
+            .fold(1.0, f64::max);
+        for price in prices.iter_mut() {
+            *price /= largest;
+        }
+        let goal = self.needs.iter()
+            .map(|need| dot(need, &prices))
+            .fold(f64::INFINITY, f64::min);
+        let losses = self.upgrade_gains.iter()
+            .map(|(upgrade, gains)| (*upgrade, 1.0 - gains.iter().map(|gain| dot(gain, &prices)).fold(f64::NEG_INFINITY, f64::max)))
+            .collect();
+        let mut counts = BTreeMap::new();
+        for (job, weight) in self.row_jobs.iter().zip(weights.iter()) {
+            *counts.entry(*job).or_insert(0.0) += weight;
+        }
+        Some(UpgradeBound {
+            upgrades: self.upgrades.clone(),
+            prices,
+            goal,
+            losses,
+            counts,
+        })
+    }
+
+    fn allows(&self, upgrades: &Upgrades) -> bool {
+        self.free_upgrades || upgrades.done.iter().all(|done| self.upgrades.contains(done))
+    }
+}
+
+// Past this many upgrades, trying every set of them would take longer than the search, so upgrades are counted as free instead
+const MAX_BOUNDED_UPGRADES: usize = 6;
+
+// A bound for every set of upgrades a run could end with, with prices worked out for the start
+struct DaysLeft {
+    resources: Vec<Resource>,
+    effects: Vec<EffectId>,
+    models: Vec<UpgradeModel>,
+    bounds: Vec<UpgradeBound>,
+}
+
+impl DaysLeft {
+    // None if the scenario can't be won at all
+    fn new(scenario: &Scenario, start: &JobOutput) -> Option<DaysLeft> {
+        let resources = counted_resources(scenario);
+        let effects = scenario.jobs.iter()
+            .chain(std::iter::once(&scenario.starting_resources))
+            .flat_map(|job| job.effects.iter().map(|(effect, _time_slots)| *effect))
+            .chain(start.effects_after.keys().copied())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let upgrades = scenario.jobs.iter().filter(|job| job.is_upgrade() && job.id != scenario.win_job).collect::<Vec<_>>();
+        let models = if upgrades.len() > MAX_BOUNDED_UPGRADES {
+            upgrade_model(scenario, &upgrades, true, &resources, &effects).into_iter().collect::<Vec<_>>()
+        } else {
+            (0..1usize << upgrades.len())
+                .filter_map(|mask| {
+                    let chosen = upgrades.iter().enumerate().filter(|(index, _upgrade)| mask & (1 << index) != 0).map(|(_index, upgrade)| *upgrade).collect::<Vec<_>>();
+                    upgrade_model(scenario, &chosen, false, &resources, &effects)
+                })
+                .collect()
+        };
+        let mut days_left = DaysLeft { resources, effects, models, bounds: Vec::new() };
+        let holdings = days_left.holdings(&start.resources_after, &start.durability_after, &start.effects_after);
+        days_left.bounds = days_left.models.iter().filter_map(|model| model.bound(&holdings, &Upgrades::default())).collect();
+        if days_left.bounds.is_empty() {
+            return None;
+        }
+        Some(days_left)
+    }
+
+    // What the state holds of every counted resource and effect, in the order the prices are in
+    fn holdings(&self, resources: &ResourceSet, durability: &DurabilitySet, effects: &EffectSet) -> Vec<f64> {
+        self.resources.iter()
+            .map(|resource| match full_durability(resource) {
+                Some(full) => durability.get(resource).map(|units| units.iter().sum::<i64>()).unwrap_or(0) as f64 / full as f64,
+                None => resources.get(resource).map(to_count).unwrap_or(0) as f64,
+            })
+            .chain(self.effects.iter().map(|effect| effects.get(effect).copied().unwrap_or(0) as f64))
+            .collect()
+    }
+
+    // How many days' worth the state is short of winning, not counting the winning job. None if the run can't be won any more.
+    fn shortfall(&self, resources: &ResourceSet, durability: &DurabilitySet, effects: &EffectSet, upgrades: &Upgrades) -> Option<f64> {
+        let holdings = self.holdings(resources, durability, effects);
+        self.models.iter()
+            .zip(self.bounds.iter())
+            .filter(|(model, _bound)| model.allows(upgrades))
+            .map(|(_model, bound)| bound.shortfall(&holdings, upgrades))
+            .min_by(f64::total_cmp)
+    }
+
+    // Counts the winning job itself
+    fn at_least(&self, resources: &ResourceSet, durability: &DurabilitySet, effects: &EffectSet, upgrades: &Upgrades) -> Option<usize> {
+        self.shortfall(resources, durability, effects, upgrades).map(|shortfall| 1 + (shortfall - 1e-6).ceil().max(0.0) as usize)
+    }
+
+    // The bound with prices worked out for the state itself, for the set of upgrades that looks fastest from it, or for the given one
+    fn best_bound(&self, holdings: &[f64], upgrades: &Upgrades, only: Option<usize>) -> Option<UpgradeBound> {
+        self.models.iter()
+            .enumerate()
+            .filter(|(index, model)| only.map(|only| only == *index).unwrap_or(true) && model.allows(upgrades))
+            .map(|(_index, model)| model)
+            .filter_map(|model| model.bound(holdings, upgrades))
+            .min_by(|first, second| first.shortfall(holdings, upgrades).total_cmp(&second.shortfall(holdings, upgrades)))
+    }
+}
+
+// A state of a run while searching: its index among the states found, its days, and what it carries to the next job
+type SearchState = (usize, usize, ResourceSet, DurabilitySet, EffectSet, Upgrades);
+
+// Every job, and alternative of a job, that would work in the state, with what it would leave
+fn next_jobs(scenario: &Scenario, resources: &ResourceSet, durability: &DurabilitySet, effects: &EffectSet, upgrades: &Upgrades) -> Result<Vec<(Job, JobOutput)>, String> {
+    let mut next = Vec::new();
+    for job in scenario.jobs.iter() {
+        let job = upgrades.apply(job);
+        if upgrades.is_done(&job) {
+            continue;
+        }
+        for job in alternatives(job) {
+            let output = apply_job(resources.clone(), durability.clone(), effects.clone(), &job, &mut Roller::Lowest)?;
+            if output.is_ok() {
+                next.push((job, output));
+            }
+        }
+    }
+    Ok(next)
+}
+
+// Follow the states back to the start. Each state remembers the index of the state it came from and the job that led to it.
+fn plan_to(states: &[(Option<usize>, Option<JobRecord>)], state_index: usize, last: JobRecord) -> Vec<JobRecord> {
+    let mut plan = vec![last];
+    let mut current = state_index;
+    while let (Some(previous), Some(record)) = &states[current] {
+        plan.push(record.clone());
+        current = *previous;
+    }
+    plan.reverse();
+    plan
+}
+
+// How deep `pursue` looks for jobs that make what a job lacks
+const PURSUE_DEPTH: usize = 8;
+
+// What a job makes under the worst rolls, by resource
+fn made(job: &Job) -> Vec<(Resource, i64)> {
+    job.end_deltas.get(job.chosen_alternative.unwrap_or(0))
+        .map(|delta| delta.iter()
+            .filter_map(|(resource, amount)| match amount {
+                Amount::Gain(number) | Amount::Chance(number, 100..) | Amount::Between(number, _) if *number > 0 => Some((*resource, *number)),
+                _ => None,
+            })
+            .collect())
+        .unwrap_or_default()
+}
+
+// Every job, and alternative of a job, that could still be done apart from the winning one
+fn open_jobs(scenario: &Scenario, upgrades: &Upgrades) -> Vec<Job> {
+    scenario.jobs.iter()
+        .filter(|job| job.id != scenario.win_job)
+        .map(|job| upgrades.apply(job))
+        .filter(|job| !upgrades.is_done(job))
+        .flat_map(alternatives)
+        .collect()
+}
+
+// The days a job takes, with the days it would take to make what it lacks at the given costs per unit. Units that wear out
+// cost the share of their uses the job takes, and a catalyst that doesn't wear out costs nothing once one is held.
+fn days_with_inputs(job: &Job, costs: &BTreeMap<Resource, f64>, resources: &ResourceSet, durability: &DurabilitySet, effects: &EffectSet) -> f64 {
+    let mut days = 1.0;
+    if let Some(delta) = job.end_deltas.get(job.chosen_alternative.unwrap_or(0)) {
+        for (resource, amount) in delta.iter() {
+            let cost = costs.get(resource).copied().unwrap_or(f64::INFINITY);
+            let held = match full_durability(resource) {
+                Some(_full) => durability.get(resource).map(|units| units.len() as i64).unwrap_or(0),
+                None => resources.get(resource).map(to_count).unwrap_or(0),
+            };
+            days += match (affected_amount(effects, job.id, resource, amount), full_durability(resource)) {
+                (Amount::Spend(number), Some(full)) if held > 0 => number as f64 * cost / full as f64,
+                (Amount::Spend(number), None) => (number - held).max(0) as f64 * cost,
+                (Amount::Catalyst(number), Some(full)) if held >= number => number as f64 * cost / full as f64,
+                (Amount::Spend(number) | Amount::Catalyst(number), _) => (number - held).max(0) as f64 * cost,
+                (Amount::Repair(_number), _) if held == 0 => cost,
+                _ => 0.0,
+            };
+        }
+    }
+    days
+}
+
+// Roughly how many days each resource takes to make from nothing, by the cheapest way found to make it
+fn unit_costs(jobs: &[Job], effects: &EffectSet) -> BTreeMap<Resource, f64> {
+    let mut costs = BTreeMap::new();
+    let nothing = (ResourceSet::new(), DurabilitySet::new());
+    // Each round can only lower a cost, and a way of making something is never longer than there are jobs
+    for _round in 0..=jobs.len() {
+        let mut changed = false;
+        for job in jobs.iter() {
+            let days = days_with_inputs(job, &costs, &nothing.0, &nothing.1, effects);
+            if !days.is_finite() {
+                continue;
+            }
+            for (resource, number) in made(job) {
+                let cost = days / number as f64;
+                if costs.get(&resource).map(|known| cost < known - EPSILON).unwrap_or(true) {
+                    costs.insert(resource, cost);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    costs
+}
+
+// The job if it works now, or else a job that works now and makes something it lacks, or something that one lacks, and so on.
+// Of the jobs that make a resource, the one that looks quickest with what is held comes first. A resource already being looked for
+// is left out, so it can't go round in circles.
+fn pursue(state: (&ResourceSet, &DurabilitySet, &EffectSet), jobs: &[Job], costs: &BTreeMap<Resource, f64>, job: Job, chasing: &mut BTreeSet<Resource>, depth: usize) -> Result<Option<(Job, JobOutput)>, String> {
+    let (resources, durability, effects) = state;
+    let output = apply_job(resources.clone(), durability.clone(), effects.clone(), &job, &mut Roller::Lowest)?;
+    if output.is_ok() {
+        return Ok(Some((job, output)));
+    }
+    if depth == 0 {
+        return Ok(None);
+    }
+    let delta = match job.end_deltas.get(job.chosen_alternative.unwrap_or(0)) {
+        Some(delta) => delta,
+        None => return Ok(None),
+    };
+    for (resource, amount) in delta.iter() {
+        let needed = match affected_amount(effects, job.id, resource, amount) {
+            Amount::Spend(number) | Amount::Catalyst(number) => number,
+            Amount::Repair(_number) => 1,
+            _ => continue,
+        };
+        let held = match full_durability(resource) {
+            Some(_full) => durability.get(resource).map(|units| units.len() as i64).unwrap_or(0),
+            None => resources.get(resource).map(to_count).unwrap_or(0),
+        };
+        if held >= needed || chasing.contains(resource) {
+            continue;
+        }
+        let mut makers = jobs.iter()
+            .filter_map(|maker| made(maker).into_iter()
+                .find(|(made, _number)| made == resource)
+                .map(|(_made, number)| (days_with_inputs(maker, costs, resources, durability, effects) / number as f64, maker)))
+            .collect::<Vec<_>>();
+        makers.sort_by(|(first, _first), (second, _second)| first.total_cmp(second));
+        chasing.insert(*resource);
+        for (_days, maker) in makers {
+            if let Some(found) = pursue(state, jobs, costs, maker.clone(), chasing, depth - 1)? {
+                chasing.remove(resource);
+                return Ok(Some(found));
+            }
+        }
+        chasing.remove(resource);
+    }
+    Ok(None)
+}
+
+// A plan found quickly by following the bound. Each day the prices are worked out again for the state, and they tell which jobs
+// a fastest run would still do and how often. The winning job comes first if it works, then the upgrades in the set that looks fastest,
+// then of the other jobs the one done the fewest times, since those tend to be the later steps that use up what the earlier ones made.
+// When the job picked can't be done yet, `pursue` finds one that gets closer to it.
+// It works whatever the seed, like any plan the solver finds, but a faster one can be missed, so `search` then looks for one.
+fn quick_plan(scenario: &Scenario, start: &JobOutput, days_left: &DaysLeft, only: Option<usize>, most_first: bool) -> Result<Option<Vec<JobRecord>>, String> {
+    let (mut resources, mut durability, mut effects, mut upgrades) = (start.resources_after.clone(), start.durability_after.clone(), start.effects_after.clone(), Upgrades::default());
+    let mut plan = Vec::new();
+    // A plan many times longer than the bound at the start has lost its way
+    let longest = days_left.at_least(&resources, &durability, &effects, &upgrades).unwrap_or(0).saturating_mul(10);
+    while plan.len() < longest {
+        let holdings = days_left.holdings(&resources, &durability, &effects);
+        let bound = match days_left.best_bound(&holdings, &upgrades, only) {
+            Some(bound) => bound,
+            None => return Ok(None),
+        };
+        let mut counts = bound.counts.iter()
+            .filter(|(_job, count)| **count > EPSILON)
+            .collect::<Vec<_>>();
+        counts.sort_by(|(_first, first), (_second, second)| first.total_cmp(second));
+        if most_first {
+            counts.reverse();
+        }
+        let preferred = counts.iter().map(|(job, _count)| **job).collect::<Vec<_>>();
+        let upgrades_to_do = bound.upgrades.iter().filter(|upgrade| !upgrades.done.contains(upgrade)).map(|upgrade| (*upgrade, None));
+        let candidates = upgrades_to_do.chain(preferred.iter().copied()).collect::<Vec<_>>();
+
+        let state = (&resources, &durability, &effects);
+        let jobs = open_jobs(scenario, &upgrades);
+        let costs = unit_costs(&jobs, &effects);
+        let mut next = None;
+        if let Some(win_job) = scenario.jobs.iter().find(|job| job.id == scenario.win_job) {
+            next = pursue(state, &jobs, &costs, upgrades.apply(win_job), &mut BTreeSet::new(), 0)?;
+        }
+        for (job_id, alternative) in candidates {
+            if next.is_some() {
+                break;
+            }
+            let job = match scenario.jobs.iter().find(|job| job.id == job_id) {
+                Some(job) => upgrades.apply(job),
+                None => continue,
+            };
+            if upgrades.is_done(&job) {
+                continue;
+            }
+            for job in alternatives(job) {
+                if alternative.is_none() || job.chosen_alternative == alternative {
+                    next = pursue(state, &jobs, &costs, job, &mut BTreeSet::new(), PURSUE_DEPTH)?;
+                    if next.is_some() {
+                        break;
+                    }
+                }
+            }
+        }
+        // If none of those gets anywhere, whatever adds most to what is held by the prices
+        if next.is_none() {
+            next = next_jobs(scenario, &resources, &durability, &effects, &upgrades)?
+                .into_iter()
+                .map(|(job, output)| {
+                    let worth = dot(&days_left.holdings(&output.resources_after, &output.durability_after, &output.effects_after), &bound.prices);
+                    (worth, job, output)
+                })
+                .max_by(|(first, ..), (second, ..)| first.total_cmp(second))
+                .map(|(_worth, job, output)| (job, output));
+        }
+        let (job, output) = match next {
+            Some(next) => next,
+            None => return Ok(None),
+        };
+        plan.push(JobRecord { id: job.id, chosen_alternative: job.chosen_alternative });
+        if job.id == scenario.win_job {
+            return Ok(Some(plan));
+        }
+        upgrades.record(&job);
+        resources = output.resources_after;
+        durability = output.durability_after;
+        effects = output.effects_after;
+    }
+    Ok(None)
+}
+
+// Whether the plan works from the start and ends with the winning job, however the rolls go
+fn wins(scenario: &Scenario, start: &JobOutput, plan: &[JobRecord]) -> Result<bool, String> {
+    let (mut resources, mut durability, mut effects, mut upgrades) = (start.resources_after.clone(), start.durability_after.clone(), start.effects_after.clone(), Upgrades::default());
+    for record in plan.iter() {
+        let mut job = match scenario.jobs.iter().find(|job| job.id == record.id) {
+            Some(job) => upgrades.apply(job),
+            None => return Ok(false),
+        };
+        if upgrades.is_done(&job) {
+            return Ok(false);
+        }
+        job.chosen_alternative = record.chosen_alternative;
+        let output = apply_job(resources, durability, effects, &job, &mut Roller::Lowest)?;
+        if !output.is_ok() {
+            return Ok(false);
+        }
+        upgrades.record(&job);
+        resources = output.resources_after;
+        durability = output.durability_after;
+        effects = output.effects_after;
+    }
+    Ok(plan.last().map(|record| record.id == scenario.win_job).unwrap_or(false))
+}
+
+// Leave out every job the plan still wins without. A rollout makes more than it needs here and there, and this is much cheaper than searching.
+fn shorten(scenario: &Scenario, start: &JobOutput, mut plan: Vec<JobRecord>) -> Result<Vec<JobRecord>, String> {
+    let mut index = 0;
+    while index < plan.len() {
+        let mut without = plan.clone();
+        without.remove(index);
+        if wins(scenario, start, &without)? {
+            plan = without;
+        } else {
+            index += 1;
+        }
+    }
+    Ok(plan)
+}
+
+// First a plan is found quickly, then states are tried in order of the days they took plus the fewest days they could still win in,
+// leaving out any that can't beat the plan, so the first win found is the fastest. If every state is tried without a win, the quick plan
+// was the fastest, and if the search runs out of states first, the quick plan is the best there is.
+// A state is dropped when one reached no later holds at least as much of every plentiful resource and is the same in everything else,
+// since whatever the dropped state could do, that one can too.
+fn search(scenario: &Scenario, start: JobOutput, max_states: usize) -> Result<Option<Vec<JobRecord>>, String> {
+    let days_left = match DaysLeft::new(scenario, &start) {
+        Some(days_left) => days_left,
+        None => return Ok(None),
+    };
+    let mut quick: Option<Vec<JobRecord>> = None;
+    for most_first in [false, true] {
+        for only in std::iter::once(None).chain((0..days_left.models.len()).map(Some)) {
+            if let Some(plan) = quick_plan(scenario, &start, &days_left, only, most_first)? {
+                let plan = shorten(scenario, &start, plan)?;
+                if quick.as_ref().map(|quick| plan.len() < quick.len()).unwrap_or(true) {
+                    quick = Some(plan);
+                }
+            }
+        }
+    }
+    let beat = quick.as_ref().map(|plan| plan.len()).unwrap_or(usize::MAX);
+    let plentiful = plentiful_resources(scenario);
+    // Splits held resources into the plentiful amounts, in a fixed order, and everything else
+    let split = |resources: ResourceSet| {
+        let mut rest = held(resources);
+        let amounts = plentiful.iter()
+            .map(|resource| rest.remove(resource).unwrap_or_else(|| quantity(0)))
+            .collect::<Vec<_>>();
+        (amounts, rest)
+    };
+
+    // For each state apart from the plentiful amounts, the days and plentiful amounts that no other reached state beats
+    let mut best: BTreeMap<_, Vec<(usize, Vec<Quantity>)>> = BTreeMap::new();
+    let mut states = Vec::new();
+    // States waiting to be tried, by their days plus the days they still need at least
+    let mut waiting: Vec<Vec<SearchState>> = Vec::new();
+    let (start_amounts, start_rest) = split(start.resources_after.clone());
+    // Which upgrades are done and which effects are active are part of the state, since the same resources can lead to different places with them
+    best.insert((start_rest, start.durability_after.clone(), start.effects_after.clone(), Vec::new()), vec![(0, start_amounts)]);
+    states.push((None, None));
+    let estimate = match days_left.at_least(&start.resources_after, &start.durability_after, &start.effects_after, &Upgrades::default()) {
+        Some(estimate) => estimate,
+        None => return Ok(None),
+    };
+    waiting.resize(estimate + 1, Vec::new());
+    waiting[estimate].push((0, 0, held(start.resources_after), start.durability_after, start.effects_after, Upgrades::default()));
+    let mut estimate = 0;
+    while estimate < waiting.len().min(beat) {
+        // The latest state first, which goes deeper before going wider among equally promising states
+        let (state_index, days, resources, durability, effects, upgrades) = match waiting[estimate].pop() {
+            Some(state) => state,
+            None => {
+                estimate += 1;
+                continue;
+            }
+        };
+        for (job, output) in next_jobs(scenario, &resources, &durability, &effects, &upgrades)? {
+            let record = JobRecord { id: job.id, chosen_alternative: job.chosen_alternative };
+            if job.id == scenario.win_job {
+                return Ok(Some(plan_to(&states, state_index, record)));
+            }
+            let mut next_upgrades = upgrades.clone();
+            next_upgrades.record(&job);
+            let mut done = next_upgrades.done.clone();
+            done.sort();
+            let next_resources = held(output.resources_after);
+            // The bound never drops by more than a day for each job, so a state never goes back to an estimate already done
+            let next_estimate = match days_left.at_least(&next_resources, &output.durability_after, &output.effects_after, &next_upgrades) {
+                Some(left) if days + 1 + left < beat => days + 1 + left,
+                _ => continue,
+            };
+            let (amounts, rest) = split(next_resources.clone());
+            let front = best.entry((rest, output.durability_after.clone(), output.effects_after.clone(), done)).or_default();
+            let beaten = |(other_days, other): &(usize, Vec<Quantity>), days: usize, amounts: &[Quantity]| {
+                *other_days <= days && other.iter().zip(amounts.iter()).all(|(other, amount)| other >= amount)
+            };
+            if front.iter().any(|other| beaten(other, days + 1, &amounts)) {
+                continue;
+            }
+            if states.len() >= max_states {
+                return Ok(quick);
+            }
+            front.retain(|(other_days, other)| !beaten(&(days + 1, amounts.clone()), *other_days, other));
+            if front.len() < MAX_FRONT {
+                front.push((days + 1, amounts));
+            }
+            states.push((Some(state_index), Some(record)));
+            if next_estimate >= waiting.len() {
+                waiting.resize(next_estimate + 1, Vec::new());
+            }
+            waiting[next_estimate].push((states.len() - 1, days + 1, next_resources, output.durability_after, output.effects_after, next_upgrades));
+        }
+    }
+    Ok(quick)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::generator::{generate, GeneratorSettings};
+    use crate::core::scenario::facility;
+
+    // Tries every order of jobs up to the given number of days, without merging any states
+    fn wins_within(scenario: &Scenario, resources: ResourceSet, durability: DurabilitySet, effects: EffectSet, upgrades: &Upgrades, days: usize) -> bool {
+        if days == 0 {
+            return false;
+        }
+        for job in scenario.jobs.iter() {
+            let job = upgrades.apply(job);
+            if upgrades.is_done(&job) {
+                continue;
+            }
+            let output = apply_job(resources.clone(), durability.clone(), effects.clone(), &job, &mut Roller::Lowest).unwrap();
+            if !output.is_ok() {
+                continue;
+            }
+            if job.id == scenario.win_job {
+                return true;
+            }
+            let mut next_upgrades = upgrades.clone();
+            next_upgrades.record(&job);
+            if wins_within(scenario, output.resources_after, output.durability_after, output.effects_after, &next_upgrades, days - 1) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn brute_force_par(scenario: &Scenario, max_days: usize) -> Option<usize> {
+        let start = apply_job(ResourceSet::new(), DurabilitySet::new(), EffectSet::new(), &scenario.starting_resources, &mut Roller::Lowest).unwrap();
+        (1..=max_days).find(|days| wins_within(scenario, start.resources_after.clone(), start.durability_after.clone(), start.effects_after.clone(), &Upgrades::default(), *days))
+    }
+
+    fn with_deltas(scenario: &mut Scenario, job_id: JobId, end_deltas: Vec<Vec<(Resource, Amount)>>) {
+        let job: &mut Job = scenario.jobs.iter_mut().find(|job| job.id == job_id).unwrap();
+        job.end_deltas = end_deltas;
+    }
+
+    #[test]
+    fn saving_up_beyond_one_job_counts() {
+        // Catching two fragments at a time while crafting needs three, so the fastest way leaves fragments over between crafts
+        let mut scenario = facility();
+        scenario.jobs.retain(|job| [JobId::FragmentCatch, JobId::DayDreamCraft, JobId::Retire].contains(&job.id));
+        with_deltas(&mut scenario, JobId::FragmentCatch, vec![vec![(Resource::Fragment, Amount::Gain(2))]]);
+        with_deltas(&mut scenario, JobId::Retire, vec![vec![(Resource::DayDream, Amount::Spend(2))]]);
+        scenario.activate();
+
+        let plan = solve(&scenario, DEFAULT_MAX_STATES).unwrap().unwrap();
+        assert_eq!(plan.len(), 6);
+        assert_eq!(Some(plan.len()), brute_force_par(&scenario, 8));
+    }
+
+    #[test]
+    fn counted_crafting_trees_match_the_search() {
+        for seed in 0..4 {
+            let scenario = generate(&GeneratorSettings { seed, depth: 2, width: 2 }).unwrap().scenario;
+            let start = apply_job(ResourceSet::new(), DurabilitySet::new(), EffectSet::new(), &scenario.starting_resources, &mut Roller::Lowest).unwrap();
+            let counted = crafting_tree_plan(&scenario, &start).unwrap().unwrap();
+            let searched = search(&scenario, start, DEFAULT_MAX_STATES).unwrap().unwrap();
+            assert_eq!(counted.len(), searched.len());
+        }
+        // The facility has effects and upgrades, so it is searched
+        let scenario = facility();
+        scenario.activate();
+        let start = apply_job(ResourceSet::new(), DurabilitySet::new(), EffectSet::new(), &scenario.starting_resources, &mut Roller::Lowest).unwrap();
+        assert_eq!(crafting_tree_plan(&scenario, &start).unwrap(), None);
+    }
+
+    #[test]
+    fn facility_has_a_par() {
+        let scenario = facility();
+        scenario.activate();
+        let plan = solve(&scenario, DEFAULT_MAX_STATES).unwrap().unwrap();
+        let start = apply_job(ResourceSet::new(), DurabilitySet::new(), EffectSet::new(), &scenario.starting_resources, &mut Roller::Lowest).unwrap();
+        assert!(wins(&scenario, &start, &plan).unwrap());
+        // Never shorter than the bound says a run can be
+        let days_left = DaysLeft::new(&scenario, &start).unwrap();
+        assert!(plan.len() >= days_left.at_least(&start.resources_after, &start.durability_after, &start.effects_after, &Upgrades::default()).unwrap());
+    }
+
+    #[test]
+    fn facility_par_matches_every_order_of_jobs() {
+        // Retiring for the full price takes too long to try every order, so it is cheaper here
+        let mut scenario = facility();
+        with_deltas(&mut scenario, JobId::Retire, vec![vec![(Resource::Coin, Amount::Spend(2)), (Resource::Nightmare, Amount::Forbid)]]);
+        scenario.activate();
+
+        let plan = solve(&scenario, DEFAULT_MAX_STATES).unwrap().unwrap();
+        assert_eq!(plan.iter().map(|record| record.id).collect::<Vec<_>>(), vec![JobId::FragmentCatch, JobId::FragmentCatch, JobId::FragmentCatch, JobId::DayDreamCraft, JobId::DayDreamSell, JobId::Retire]);
+        assert_eq!(Some(plan.len()), brute_force_par(&scenario, 8));
+    }
+}
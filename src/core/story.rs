@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use crate::core::locale::{job_name, localised, Localised};
+use crate::core::catalogue::{create_job, discovery, facility_logs};
+use crate::core::job::{Job, JobId, JobOutput};
+use crate::core::quantity::quantity;
+use crate::core::resource::Resource;
+
+// When the story unlocks, separate from the rules. The story itself is kept with the jobs in `core::catalogue`,
+// with every language's text side by side, so writers don't have to touch the code that unlocks it.
+// Job flavour text is the `long_text` of each job in the catalogue.
+
+// A facility log, unlocked the first time the player holds enough of a resource
+pub struct FacilityLog {
+    // Stable name the journal remembers the log by
+    pub id: &'static str,
+    pub title: Localised,
+    pub text: Localised,
+    pub resource: Resource,
+    pub amount: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum JournalEntryId {
+    // The first time a job was done
+    Discovery(JobId),
+    FacilityLog(String),
+}
+
+pub struct JournalEntry {
+    pub title: String,
+    pub text: &'static str,
+}
+
+pub fn journal_entry(id: &JournalEntryId) -> Option<JournalEntry> {
+    match id {
+        JournalEntryId::Discovery(job_id) => discovery(*job_id).map(|text| JournalEntry {
+            title: job_name(&create_job(*job_id)).to_string(),
+            text: localised(&text),
+        }),
+        JournalEntryId::FacilityLog(log_id) => facility_logs().into_iter()
+            .find(|log| log.id == log_id)
+            .map(|log| JournalEntry {
+                title: localised(&log.title).to_string(),
+                text: localised(&log.text),
+            }),
+    }
+}
+
+// Everything a run unlocks, in the order it was unlocked. The first job is the starting resources, which isn't a discovery.
+pub fn unlocked_entries(jobs: &[Job], outputs: &[JobOutput]) -> Vec<JournalEntryId> {
+    let logs = facility_logs();
+    let mut unlocked = Vec::new();
+    for (job_index, (job, output)) in jobs.iter().zip(outputs.iter()).enumerate() {
+        if !output.is_ok() {
+            continue;
+        }
+        if job_index > 0 && discovery(job.id).is_some() {
+            let discovery = JournalEntryId::Discovery(job.id);
+            if !unlocked.contains(&discovery) {
+                unlocked.push(discovery);
+            }
+        }
+        for log in logs.iter() {
+            let reached = output.resources_after.get(&log.resource).map(|amount| *amount >= quantity(log.amount)).unwrap_or(false);
+            let entry = JournalEntryId::FacilityLog(log.id.to_string());
+            if reached && !unlocked.contains(&entry) {
+                unlocked.push(entry);
+            }
+        }
+    }
+    unlocked
+}
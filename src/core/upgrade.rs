@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use crate::core::amount::Amount;
+use crate::core::effect::EffectSet;
+use crate::core::inventory::DurabilitySet;
+use crate::core::job::{Job, JobId, JobOutput};
+use crate::core::locale::{text, Text};
+use crate::core::resource::{DeltaOutput, DeltaOutputStatus, Resource, ResourceSet};
+
+// Upgrades are jobs that make other jobs better for the rest of the run.
+// They aren't stored anywhere but the history, so replaying it without an upgrade takes the upgrade back too.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobModifier {
+    // The job gains, or needs, this many more of the resource in every alternative that has it.
+    // Negative numbers mean fewer, and an amount that reaches zero is dropped from the alternative.
+    Amount(JobId, Resource, i64),
+    // The job takes this many more time slots, but never less than one
+    TimeSlots(JobId, i64),
+}
+
+impl JobModifier {
+    pub fn job(&self) -> JobId {
+        match self {
+            JobModifier::Amount(job, _resource, _change) => *job,
+            JobModifier::TimeSlots(job, _change) => *job,
+        }
+    }
+}
+
+// Upgrades done so far in a run
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Upgrades {
+    // Each upgrade can only be done once
+    pub done: Vec<JobId>,
+    pub modifiers: Vec<JobModifier>,
+}
+
+// The amount with its number changed, or None when nothing is left of it
+pub fn modify_amount(amount: &Amount, change: i64) -> Option<Amount> {
+    let changed = |number: i64| if number + change > 0 { Some(number + change) } else { None };
+    match amount {
+        Amount::Gain(number) => changed(*number).map(Amount::Gain),
+        Amount::Spend(number) => changed(*number).map(Amount::Spend),
+        Amount::Catalyst(number) => changed(*number).map(Amount::Catalyst),
+        Amount::GainX(number) => changed(*number).map(Amount::GainX),
+        Amount::SpendX(number) => changed(*number).map(Amount::SpendX),
+        Amount::CatalystX(number) => changed(*number).map(Amount::CatalystX),
+        Amount::Repair(number) => changed(*number).map(Amount::Repair),
+        Amount::Chance(number, percent) => changed(*number).map(|number| Amount::Chance(number, *percent)),
+        Amount::Between(min, max) => changed(*max).map(|max| Amount::Between((*min + change).max(0), max)),
+        // Limits aren't yields or costs, so upgrades leave them alone
+        Amount::Set(_) | Amount::AtMost(_) | Amount::Forbid => Some(*amount),
+    }
+}
+
+fn apply_modifier(job: &mut Job, modifier: &JobModifier) {
+    if modifier.job() != job.id {
+        return;
+    }
+    match modifier {
+        JobModifier::Amount(_job, resource, change) => {
+            for delta in job.end_deltas.iter_mut() {
+                *delta = delta.iter()
+                    .filter_map(|(delta_resource, amount)| {
+                        if delta_resource == resource {
+                            modify_amount(amount, *change).map(|amount| (*delta_resource, amount))
+                        } else {
+                            Some((*delta_resource, *amount))
+                        }
+                    })
+                    .collect();
+            }
+        }
+        JobModifier::TimeSlots(_job, change) => {
+            job.total_time_slots = (job.total_time_slots as i64 + change).max(1) as usize;
+        }
+    }
+}
+
+impl Upgrades {
+    pub fn is_done(&self, job: &Job) -> bool {
+        job.is_upgrade() && self.done.contains(&job.id)
+    }
+
+    // The job as it is with the upgrades done so far
+    pub fn apply(&self, job: &Job) -> Job {
+        let mut job = job.clone();
+        for modifier in self.modifiers.iter() {
+            apply_modifier(&mut job, modifier);
+        }
+        job
+    }
+
+    // Call once the job has succeeded
+    pub fn record(&mut self, job: &Job) {
+        if job.is_upgrade() && !self.done.contains(&job.id) {
+            self.done.push(job.id);
+            self.modifiers.extend(job.modifiers.iter().cloned());
+        }
+    }
+}
+
+// Doing an upgrade a second time fails without changing anything
+pub fn already_done_output(resources: ResourceSet, durability: DurabilitySet, effects: EffectSet) -> JobOutput {
+    JobOutput {
+        main_output: DeltaOutput {
+            status: DeltaOutputStatus::Failure {
+                errors: vec![text(Text::AlreadyUpgraded).to_string()],
+                failing_resources: vec![],
+            },
+            changed_resources: vec![],
+            resources_after: resources.clone(),
+            durability_after: durability.clone(),
+        },
+        upkeep_outputs: vec![],
+        resources_after: resources,
+        durability_after: durability,
+        effects_after: effects,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::effect::EffectId;
+    use crate::core::replay::replay_jobs;
+    use crate::core::scenario::facility;
+    use crate::core::solver::{solve, DEFAULT_MAX_STATES};
+
+    #[test]
+    fn upgrade_takes_days_off_the_run() {
+        // Start with enough coins for a finer net, and retire on fragments instead
+        let mut scenario = facility();
+        scenario.starting_resources.end_deltas = vec![vec![(Resource::Coin, Amount::Gain(4))]];
+        scenario.jobs.retain(|job| [JobId::FragmentCatch, JobId::NetUpgrade, JobId::Retire].contains(&job.id));
+        for job in scenario.jobs.iter_mut() {
+            match job.id {
+                JobId::FragmentCatch => job.end_deltas = vec![vec![(Resource::Fragment, Amount::Gain(1))]],
+                JobId::Retire => job.end_deltas = vec![vec![(Resource::Fragment, Amount::Spend(6))]],
+                _ => {}
+            }
+        }
+        scenario.activate();
+        let with_upgrade = solve(&scenario, DEFAULT_MAX_STATES).unwrap().unwrap();
+        assert_eq!(with_upgrade.iter().map(|record| record.id).collect::<Vec<_>>(), vec![JobId::NetUpgrade, JobId::FragmentCatch, JobId::FragmentCatch, JobId::FragmentCatch, JobId::Retire]);
+
+        scenario.jobs.retain(|job| job.id != JobId::NetUpgrade);
+        let without_upgrade = solve(&scenario, DEFAULT_MAX_STATES).unwrap().unwrap();
+        assert_eq!(without_upgrade.len(), 7);
+    }
+
+    #[test]
+    fn quicker_job_wears_effects_down_less() {
+        // Catching takes three time slots until a better net cuts it to one
+        let mut scenario = facility();
+        scenario.starting_resources.end_deltas = vec![vec![(Resource::Coin, Amount::Gain(4)), (Resource::ComfortDream, Amount::Gain(2))]];
+        for job in scenario.jobs.iter_mut() {
+            match job.id {
+                JobId::FragmentCatch => job.total_time_slots = 3,
+                JobId::NetUpgrade => job.modifiers = vec![JobModifier::TimeSlots(JobId::FragmentCatch, -5)],
+                _ => {}
+            }
+        }
+        scenario.activate();
+        let job = |job_id: JobId| scenario.job(job_id).unwrap();
+        let jobs = vec![
+            scenario.starting_resources.clone(),
+            job(JobId::ComfortDreamUse),
+            job(JobId::FragmentCatch),
+            job(JobId::NetUpgrade),
+            job(JobId::ComfortDreamUse),
+            job(JobId::FragmentCatch),
+        ];
+        let replay = replay_jobs(&jobs, 1).unwrap();
+        assert_eq!(replay.jobs[2].total_time_slots, 3);
+        assert_eq!(replay.outputs[2].effects_after.get(&EffectId::ComfortDream), None);
+        // Never quicker than a single time slot
+        assert_eq!(replay.jobs[5].total_time_slots, 1);
+        assert_eq!(replay.outputs[5].effects_after.get(&EffectId::ComfortDream), Some(&2));
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::{BTreeMap, BTreeSet};
+use crate::core::amount::Amount;
+use crate::core::editor::job_id_text;
+use crate::core::job::{Job, JobId};
+use crate::core::locale::{fill, Text};
+use crate::core::resource::{attributes_with, AttributeMappings, Resource};
+use crate::core::scenario::{facility, Scenario};
+use crate::core::upgrade::JobModifier;
+
+type Deltas = Vec<Vec<(Resource, Amount)>>;
+
+// Validate the built in jobs and attributes
+pub fn validate_catalogue() -> Vec<String> {
+    validate_scenario(&facility())
+}
+
+// Validate a scenario with the attributes of its own resources, whether or not it is active
+pub fn validate_scenario(scenario: &Scenario) -> Vec<String> {
+    validate(&scenario.jobs, &attributes_with(&scenario.resources), &scenario.starting_resources, &scenario.resource_ids(), scenario.win_job)
+}
+
+// Check the job and attribute definitions for mistakes that would only show up while playing.
+// Every problem found is returned, rather than stopping at the first one.
+pub fn validate(jobs: &[Job], attributes: &AttributeMappings, starting_resources: &Job, resources: &[Resource], win_job: JobId) -> Vec<String> {
+    let mut problems = Vec::new();
+    for job in jobs.iter().chain(std::iter::once(starting_resources)) {
+        let owner = fill(Text::JobOwner, &[&job_id_text(&job.id)]);
+        check_deltas(&mut problems, &owner, &job.start_deltas);
+        check_deltas(&mut problems, &owner, &job.end_deltas);
+    }
+    for (resource, attribute) in attributes.iter() {
+        check_deltas(&mut problems, &fill(Text::UpkeepOwner, &[resource]), &attribute.upkeep);
+        if let Some(durability) = attribute.durability {
+            if durability <= 0 {
+                problems.push(fill(Text::BreaksBeforeUse, &[resource, &durability]));
+            }
+        }
+    }
+
+    let produced = produced_resources(jobs, attributes, starting_resources);
+    for resource in resources.iter() {
+        if !produced.contains(resource) && !only_limited(jobs, *resource) {
+            problems.push(fill(Text::NothingProduces, &[resource]));
+        }
+    }
+
+    for job in jobs.iter() {
+        check_modifiers(&mut problems, job, jobs);
+    }
+
+    let reachable_jobs = reachable_jobs(jobs, starting_resources);
+    for job in jobs.iter() {
+        if !reachable_jobs.contains(&job.id) {
+            problems.push(fill(Text::JobNeverPossible, &[&job_id_text(&job.id)]));
+        }
+    }
+    if !reachable_jobs.contains(&win_job) {
+        problems.push(fill(Text::WinJobNeverReached, &[&job_id_text(&win_job)]));
+    }
+
+    check_upkeep_cycles(&mut problems, attributes);
+    problems
+}
+
+fn check_deltas(problems: &mut Vec<String>, owner: &str, deltas: &Deltas) {
+    for (alternative_index, delta) in deltas.iter().enumerate() {
+        let mut has_gain_x = false;
+        let mut has_spend_x = false;
+        for (resource, amount) in delta.iter() {
+            let number = match amount {
+                Amount::Gain(number) |
+                Amount::Spend(number) |
+                Amount::Catalyst(number) |
+                Amount::GainX(number) |
+                Amount::SpendX(number) |
+                Amount::CatalystX(number) |
+                Amount::Repair(number) => Some(*number),
+                // Zero is meaningful for these, so only negative numbers are mistakes
+                Amount::Set(number) |
+                Amount::AtMost(number) => if *number < 0 { Some(*number) } else { None },
+                Amount::Forbid => None,
+                Amount::Chance(number, percent) => {
+                    if *percent <= 0 || *percent > 100 {
+                        problems.push(fill(Text::ChanceOutOfRange, &[&owner, &(alternative_index + 1), resource, amount]));
+                    }
+                    Some(*number)
+                }
+                // Nothing at all is a fine lowest roll, as long as the highest one gains something
+                Amount::Between(min, max) => {
+                    if *min < 0 || *max < *min {
+                        problems.push(fill(Text::RangeBackwards, &[&owner, &(alternative_index + 1), resource, amount]));
+                    }
+                    Some(*max)
+                }
+            };
+            if let Some(number) = number {
+                if number <= 0 {
+                    problems.push(fill(Text::AmountNotPositive, &[&owner, &(alternative_index + 1), resource, amount]));
+                }
+            }
+            match amount {
+                Amount::GainX(_) => has_gain_x = true,
+                Amount::SpendX(_) | Amount::CatalystX(_) => has_spend_x = true,
+                _ => {}
+            }
+        }
+        if has_spend_x && !has_gain_x {
+            problems.push(fill(Text::SpendsXWithoutGain, &[&owner, &(alternative_index + 1)]));
+        }
+        if has_gain_x && !has_spend_x {
+            problems.push(fill(Text::GainsXWithoutSpend, &[&owner, &(alternative_index + 1)]));
+        }
+    }
+}
+
+// Upgrades have to change something that is there
+fn check_modifiers(problems: &mut Vec<String>, job: &Job, jobs: &[Job]) {
+    for modifier in job.modifiers.iter() {
+        match jobs.iter().find(|other| other.id == modifier.job()) {
+            None => problems.push(fill(Text::UpgradeTargetMissing, &[&job_id_text(&job.id), &job_id_text(&modifier.job())])),
+            Some(target) => {
+                if let JobModifier::Amount(_target, resource, _change) = modifier {
+                    if !target.end_deltas.iter().flatten().any(|(target_resource, _amount)| target_resource == resource) {
+                        problems.push(fill(Text::UpgradeResourceUnused, &[&job_id_text(&job.id), resource, &job_id_text(&target.id)]));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn gains(delta: &[(Resource, Amount)]) -> impl Iterator<Item=Resource> + '_ {
+    delta.iter().filter_map(|(resource, amount)| match amount {
+        Amount::Gain(_) | Amount::GainX(_) | Amount::Chance(_, _) | Amount::Between(_, _) => Some(*resource),
+        Amount::Set(target) if *target > 0 => Some(*resource),
+        _ => None,
+    })
+}
+
+// Resources that have to be held for the delta to apply
+fn requirements(delta: &[(Resource, Amount)]) -> impl Iterator<Item=Resource> + '_ {
+    delta.iter().filter_map(|(resource, amount)| match amount {
+        Amount::Spend(_) | Amount::Catalyst(_) | Amount::SpendX(_) | Amount::CatalystX(_) => Some(*resource),
+        _ => None,
+    })
+}
+
+fn produced_resources(jobs: &[Job], attributes: &AttributeMappings, starting_resources: &Job) -> BTreeSet<Resource> {
+    let mut produced = BTreeSet::new();
+    for job in jobs.iter().chain(std::iter::once(starting_resources)) {
+        for delta in job.start_deltas.iter().chain(job.end_deltas.iter()) {
+            produced.extend(gains(delta));
+        }
+    }
+    for attribute in attributes.values() {
+        for delta in attribute.upkeep.iter() {
+            produced.extend(gains(delta));
+        }
+    }
+    produced
+}
+
+// A resource that jobs only keep below a limit never has to be made, such as one that is forbidden for winning
+fn only_limited(jobs: &[Job], resource: Resource) -> bool {
+    let mut amounts = jobs.iter()
+        .flat_map(|job| job.start_deltas.iter().chain(job.end_deltas.iter()))
+        .flatten()
+        .filter(|(mentioned, _amount)| *mentioned == resource)
+        .map(|(_resource, amount)| amount)
+        .peekable();
+    amounts.peek().is_some() && amounts.all(|amount| matches!(amount, Amount::Forbid | Amount::AtMost(_)))
+}
+
+// Jobs that can be done at some point, found by growing the set of obtainable resources until nothing changes
+pub fn reachable_jobs(jobs: &[Job], starting_resources: &Job) -> BTreeSet<JobId> {
+    let mut obtainable = BTreeSet::new();
+    for delta in starting_resources.end_deltas.iter() {
+        obtainable.extend(gains(delta));
+    }
+    let mut reachable = BTreeSet::new();
+    loop {
+        let mut changed = false;
+        for job in jobs.iter() {
+            for delta in job.end_deltas.iter() {
+                if requirements(delta).all(|resource| obtainable.contains(&resource)) {
+                    changed |= reachable.insert(job.id);
+                    for resource in gains(delta) {
+                        changed |= obtainable.insert(resource);
+                    }
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    reachable
+}
+
+// Upkeep that produces a resource whose own upkeep leads back to the first one would grow forever
+fn check_upkeep_cycles(problems: &mut Vec<String>, attributes: &AttributeMappings) {
+    let mut feeds: BTreeMap<Resource, BTreeSet<Resource>> = BTreeMap::new();
+    for (resource, attribute) in attributes.iter() {
+        for delta in attribute.upkeep.iter() {
+            feeds.entry(*resource).or_default().extend(gains(delta));
+        }
+    }
+    for start in feeds.keys() {
+        let mut stack = vec![*start];
+        let mut visited = BTreeSet::new();
+        while let Some(current) = stack.pop() {
+            for next in feeds.get(&current).into_iter().flatten() {
+                if next == start {
+                    problems.push(fill(Text::UpkeepCycle, &[&start]));
+                    stack.clear();
+                    break;
+                }
+                if visited.insert(*next) {
+                    stack.push(*next);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::daily::{banned_job, daily_scenario};
+    use crate::core::generator::{generate, GeneratorSettings, DEFAULT_DEPTH, DEFAULT_WIDTH};
+
+    #[test]
+    fn facility_is_valid() {
+        assert_eq!(validate_catalogue(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn daily_challenges_are_valid() {
+        // A month of days bans every bannable job at least once
+        let mut banned = BTreeSet::new();
+        for day in 1..=31 {
+            let date = format!("2026-10-{:02}", day);
+            let scenario = daily_scenario(&date).unwrap();
+            assert_eq!(validate_scenario(&scenario), Vec::<String>::new(), "{}", date);
+            banned.extend(banned_job(&scenario));
+        }
+        assert_eq!(banned.len(), 7);
+    }
+
+    #[test]
+    fn generated_scenarios_are_valid() {
+        for seed in 0..3 {
+            let settings = GeneratorSettings {
+                seed,
+                depth: DEFAULT_DEPTH,
+                width: DEFAULT_WIDTH,
+            };
+            assert_eq!(validate_scenario(&generate(&settings).unwrap().scenario), Vec::<String>::new(), "seed {}", seed);
+        }
+    }
+}
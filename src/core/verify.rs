@@ -0,0 +1,132 @@
+use crate::core::daily::{daily_date, daily_seed};
+use crate::core::locale::{fill, text, Text};
+use crate::core::record::RunRecord;
+use crate::core::replay::{count_days, expand_history, run_jobs};
+use crate::core::scenario::scenario_by_name;
+
+#[derive(Debug, PartialEq)]
+pub struct Verification {
+    pub passed: bool,
+    // Days the rules engine counted, which may differ from what the run claims
+    pub days: usize,
+    // Index of the first history step that broke the rules
+    pub first_illegal_step: Option<usize>,
+    pub reason: Option<String>,
+}
+
+impl Verification {
+    fn fail(days: usize, first_illegal_step: Option<usize>, reason: String) -> Verification {
+        Verification {
+            passed: false,
+            days,
+            first_illegal_step,
+            reason: Some(reason),
+        }
+    }
+}
+
+// Replay a submitted run through the rules engine, and check that it really wins in the days it claims.
+// Daily challenge runs are checked against the variant rebuilt from the date in their scenario name.
+pub fn verify_run(run: &RunRecord) -> Result<Verification, String> {
+    // Packs are loaded in the player's browser, so there is nothing to rebuild them from here
+    if !run.packs.is_empty() {
+        return Ok(Verification::fail(0, None, fill(Text::PacksCantBeVerified, &[&run.packs.join(", ")])));
+    }
+    let scenario = match scenario_by_name(&run.scenario) {
+        Ok(scenario) => scenario,
+        Err(reason) => return Ok(Verification::fail(0, None, reason)),
+    };
+    if run.scenario_version != scenario.version {
+        return Ok(Verification::fail(0, None, fill(Text::WrongScenarioVersion, &[&run.scenario, &run.scenario_version, &scenario.version])));
+    }
+    // Everyone gets the same rolls on a daily challenge
+    if let Some(date) = daily_date(&run.scenario) {
+        if run.seed != daily_seed(date) {
+            return Ok(Verification::fail(0, None, fill(Text::NotTheDailySeed, &[&date])));
+        }
+    }
+    scenario.activate();
+    let expanded = expand_history(&run.history, &scenario);
+    let outputs = run_jobs(&expanded.jobs, run.seed)?;
+    let day_count = count_days(&expanded.jobs, &outputs, scenario.win_job);
+    if let Some(step_index) = expanded.invalid_steps.first() {
+        return Ok(Verification::fail(day_count.total_days, Some(*step_index), text(Text::StepJobMissing).to_string()));
+    }
+    let (spent_days, win_index) = match (day_count.won_after, day_count.win_index) {
+        (Some(spent_days), Some(win_index)) => (spent_days, win_index),
+        _ => return Ok(Verification::fail(day_count.total_days, None, text(Text::NeverWins).to_string())),
+    };
+    // Jobs that fail before the win don't cost a day, but a fair run shouldn't contain them either
+    for job_index in 1..win_index {
+        if let Some(output) = outputs.get(job_index) {
+            if !output.is_ok() {
+                let reason = output.user_message().unwrap_or(text(Text::JobFailed)).to_string();
+                return Ok(Verification::fail(spent_days, expanded.origins[job_index], reason));
+            }
+        }
+    }
+    if spent_days != run.days {
+        return Ok(Verification::fail(spent_days, None, fill(Text::WrongDayCount, &[&run.days, &spent_days])));
+    }
+    Ok(Verification {
+        passed: true,
+        days: spent_days,
+        first_illegal_step: None,
+        reason: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::job::JobId;
+    use crate::core::record::{JobRecord, StepRecord};
+    use crate::core::scenario::facility;
+
+    fn repeat(id: JobId, times: usize) -> StepRecord {
+        StepRecord::Repeat(JobRecord { id, chosen_alternative: None }, times)
+    }
+
+    // Fifty day dreams sold for two coins each pays for retiring
+    fn slow_run() -> RunRecord {
+        RunRecord {
+            scenario: facility().name,
+            scenario_version: facility().version,
+            player: "tester".to_string(),
+            days: 251,
+            history: vec![
+                repeat(JobId::FragmentCatch, 150),
+                repeat(JobId::DayDreamCraft, 50),
+                repeat(JobId::DayDreamSell, 50),
+                repeat(JobId::Retire, 1),
+            ],
+            seed: 42,
+            packs: vec![],
+        }
+    }
+
+    #[test]
+    fn replaying_a_run_gives_the_same_verification() {
+        let first = verify_run(&slow_run()).unwrap();
+        assert!(first.passed, "{:?}", first.reason);
+        assert_eq!(first.days, 251);
+        assert_eq!(verify_run(&slow_run()).unwrap(), first);
+    }
+
+    #[test]
+    fn tampered_days_fail() {
+        let mut run = slow_run();
+        run.days = 200;
+        let verification = verify_run(&run).unwrap();
+        assert!(!verification.passed);
+        assert_eq!(verification.days, 251);
+        assert_eq!(verification, verify_run(&run).unwrap());
+    }
+
+    #[test]
+    fn runs_are_checked_against_their_own_scenario_version() {
+        let mut run = slow_run();
+        run.scenario_version += 1;
+        assert!(!verify_run(&run).unwrap().passed);
+    }
+}
@@ -1,4 +1,12 @@
+// Quantities are only Copy without big-numbers, so code that works with both has to clone them
+#![cfg_attr(not(feature = "big-numbers"), allow(clippy::clone_on_copy))]
+
+// Without the front end, the command line tools only use part of the rules engine
+#[cfg_attr(not(feature = "browser"), allow(dead_code))]
 mod core;
+// The front end is only started on wasm, so natively it is built just to be checked and tested
+#[cfg(feature = "browser")]
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
 mod view;
 
 #[cfg(target_arch = "wasm32")]
@@ -13,6 +21,144 @@ fn main() {
 
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
-    println!(r#"Please don't run this manually, instead use "cargo tauri dev""#)
-
+    let args = std::env::args().collect::<Vec<_>>();
+    match args.get(1).map(|arg| arg.as_str()) {
+        // Print the recipes of every job as Graphviz DOT
+        Some("graph") => {
+            let jobs = core::catalogue::all_job_ids().into_iter().map(core::catalogue::create_job).collect::<Vec<_>>();
+            print!("{}", core::graph::recipe_graph(&jobs).to_dot(&jobs));
+        }
+        // Check the job and attribute definitions, failing if anything is wrong
+        Some("validate") => {
+            let problems = core::validate::validate_catalogue();
+            for problem in problems.iter() {
+                println!("{}", problem);
+            }
+            if !problems.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        // Find the fewest days needed to win
+        Some("solve") => {
+            match core::solver::solve(&core::scenario::facility(), core::solver::DEFAULT_MAX_STATES) {
+                Ok(Some(plan)) => {
+                    println!("Par: {} days", plan.len());
+                    for record in plan.iter() {
+                        match record.chosen_alternative {
+                            Some(chosen_index) => println!("{:?} [alt {}]", record.id, chosen_index + 1),
+                            None => println!("{:?}", record.id),
+                        }
+                    }
+                }
+                Ok(None) => println!("No solution found"),
+                Err(error) => println!("{}", error),
+            }
+        }
+        // Print the facility as a scenario file
+        Some("export") => {
+            match core::scenario::scenario_to_json(&core::scenario::facility()) {
+                Ok(json) => println!("{}", json),
+                Err(error) => {
+                    println!("{}", error);
+                    std::process::exit(2);
+                }
+            }
+        }
+        // Generate a winnable scenario and print it as a scenario file: generate <seed> [depth] [width]
+        Some("generate") => {
+            let number = |index: usize, default: u64| args.get(index).and_then(|arg| arg.parse::<u64>().ok()).unwrap_or(default);
+            let settings = core::generator::GeneratorSettings {
+                seed: number(2, 0),
+                depth: number(3, core::generator::DEFAULT_DEPTH as u64) as usize,
+                width: number(4, core::generator::DEFAULT_WIDTH as u64) as usize,
+            };
+            match core::generator::generate(&settings).and_then(|generated| {
+                core::scenario::scenario_to_json(&generated.scenario).map(|json| (json, generated.par, generated.difficulty))
+            }) {
+                Ok((json, par, difficulty)) => {
+                    eprintln!("Par: {} days ({})", par, difficulty.name());
+                    println!("{}", json);
+                }
+                Err(error) => {
+                    println!("{}", error);
+                    std::process::exit(2);
+                }
+            }
+        }
+        // Show the daily challenge for a date written as YYYY-MM-DD, and check that it can be won
+        Some("daily") => {
+            let date = args.get(2).map(|date| date.as_str()).unwrap_or("");
+            match core::daily::daily_scenario(date) {
+                Ok(scenario) => {
+                    println!("Scenario: {}", scenario.name);
+                    println!("Seed: {}", core::daily::daily_seed(date));
+                    if let Some(job_id) = core::daily::banned_job(&scenario) {
+                        println!("Banned: {:?}", job_id);
+                    }
+                    for delta in scenario.starting_resources.end_deltas.iter() {
+                        for (resource, amount) in delta.iter() {
+                            println!("Start: {:?} {}", resource, amount);
+                        }
+                    }
+                    for job in scenario.jobs.iter() {
+                        for (alternative_index, delta) in job.end_deltas.iter().enumerate() {
+                            for (resource, amount) in delta.iter() {
+                                if *resource == core::resource::Resource::Coin {
+                                    println!("Price: {:?}, alternative {}: {}", job.id, alternative_index + 1, amount);
+                                }
+                            }
+                        }
+                    }
+                    match core::solver::solve(&scenario, core::solver::DEFAULT_MAX_STATES) {
+                        Ok(Some(plan)) => println!("Par: {} days", plan.len()),
+                        Ok(None) => {
+                            println!("No solution found");
+                            std::process::exit(1);
+                        }
+                        Err(error) => {
+                            println!("{}", error);
+                            std::process::exit(2);
+                        }
+                    }
+                }
+                Err(error) => {
+                    println!("{}", error);
+                    std::process::exit(2);
+                }
+            }
+        }
+        // Replay a submitted run, read as JSON from a file or standard input
+        Some("verify") => {
+            let input = match args.get(2) {
+                Some(path) => std::fs::read_to_string(path),
+                None => std::io::read_to_string(std::io::stdin()),
+            };
+            let result = input
+                .map_err(|error| error.to_string())
+                .and_then(|input| serde_json::from_str::<core::record::RunRecord>(&input).map_err(|error| error.to_string()))
+                .and_then(|run| core::verify::verify_run(&run));
+            match result {
+                Ok(verification) => {
+                    println!("{}", if verification.passed { "pass" } else { "fail" });
+                    println!("days: {}", verification.days);
+                    if let Some(step_index) = verification.first_illegal_step {
+                        println!("first illegal step: {}", step_index);
+                    }
+                    if let Some(reason) = verification.reason {
+                        println!("reason: {}", reason);
+                    }
+                    if !verification.passed {
+                        std::process::exit(1);
+                    }
+                }
+                Err(error) => {
+                    println!("{}", error);
+                    std::process::exit(2);
+                }
+            }
+        }
+        _ => {
+            println!(r#"Please don't run this manually, instead use "cargo tauri dev""#)
+        }
+    }
 }
\ No newline at end of file
@@ -1,58 +1,275 @@
 // #![cfg(target_arch = "wasm32")]
 
+use std::collections::BTreeMap;
 use yew::prelude::*;
-use crate::jobs::*;
-use gloo::timers::callback::Timeout;
+use gloo::events::EventListener;
 use crate::core::job::Job;
-use crate::game::Resource;
-use crate::view::view_logic::{GameState, HistoryStep, ViewCache};
-use crate::view_logic::*;
+use crate::core::validate::validate_scenario;
+use crate::core::record::RunRecord;
+use crate::core::leaderboard::needs_issued_seed;
+use crate::view::leaderboard::{backend, load_endpoint, save_endpoint};
+use crate::core::story::JournalEntryId;
+use crate::core::locale::{set_locale, text, Locale, Text};
+use crate::view::journal::load_journal;
+use crate::core::quantity::{set_number_format, NumberFormat};
+use crate::core::random::new_seed;
+use crate::core::daily::{daily_scenario, daily_seed};
+use crate::core::scenario::{Scenario, ScenarioFile};
+use crate::core::editor::{check_scenario_file, ScenarioEdit};
+use crate::view::editor::{editor_route, load_draft};
+use crate::core::pack::ContentPack;
+use crate::core::upgrade::Upgrades;
+use crate::view::packs::{facility_with_packs, load_packs};
+use gloo::file::callbacks::FileReader;
+use crate::core::generator::{DEFAULT_DEPTH, DEFAULT_WIDTH, MAX_DEPTH, MAX_WIDTH};
+use crate::view::daily::today;
+use crate::view::language::{load_locale, save_locale};
+use crate::view::number_format::{load_number_format, save_number_format};
+use crate::view::keyboard::{default_bindings, keyboard_listener, load_bindings, save_bindings, KeyAction, KeyBindings};
+use crate::view::summary::load_personal_best;
+use crate::core::resource::Resource;
+use crate::view::view_logic::{GameState, Ghost, HistoryStep, JobMacro, ViewCache};
 
 pub struct App {
     pub state: State,
 
     pub view_cache: ViewCache,
     pub programmer_error: Option<String>,
+    // Kept alive for as long as the app, since dropping it stops the listening
+    pub keyboard_listener: Option<EventListener>,
+    // Kept alive until the file being imported has been read
+    pub file_reader: Option<FileReader>,
 }
 
 pub struct State {
+    // The facility or today's challenge. Histories are replayed against its jobs.
+    pub scenario: Scenario,
+    // Decides how uncertain job amounts roll. It stays the same through undo and redo, so the rolls do too.
+    pub seed: u64,
     pub history: Vec<HistoryStep>,
     pub redo_queue: Vec<HistoryStep>,
     pub discovered_jobs: Vec<Job>,
+    pub macros: Vec<JobMacro>,
 
     // State for the view
     pub displayed_job: Option<Job>,
+    // First and last job index of the rows selected for saving as a macro
+    pub selected_rows: Option<(usize, usize)>,
+    pub macro_name: String,
+    pub macro_error: Option<String>,
+    // How many times a job button adds its job
+    pub repeat_count: usize,
+    // Resources the player has switched off in the chart
+    pub hidden_chart_resources: Vec<Resource>,
+    pub chart_by_day: bool,
+    // Job index under the mouse in the chart
+    pub highlighted_job: Option<usize>,
+    pub show_recipes: bool,
+
+    // Statistics for the end of game summary
+    pub undo_count: usize,
+    pub personal_best: Option<usize>,
+    pub par_days: Option<usize>,
+    pub par_searched: bool,
+
+    // Size of the next generated scenario
+    pub generator_depth: usize,
+    pub generator_width: usize,
+    pub generator_error: Option<String>,
+
+    // Scenario editor
+    pub show_editor: bool,
+    pub editor_draft: ScenarioFile,
+    // What the live validation found wrong with the draft
+    pub editor_problems: Vec<String>,
+    // Why the last import failed
+    pub editor_error: Option<String>,
+
+    // Content packs added to the facility, kept between visits
+    pub active_packs: Vec<ContentPack>,
+    pub show_packs: bool,
+    pub pack_url: String,
+    // Conflicts and problems from the last pack that was loaded
+    pub pack_errors: Vec<String>,
+
+    // Leaderboard
+    pub player_name: String,
+    // Where runs are submitted, or empty to only keep them in this browser
+    pub leaderboard_endpoint: String,
+    pub leaderboard: Vec<RunRecord>,
+    pub leaderboard_error: Option<String>,
+    // The run still has to get its seed from the leaderboard, which only takes runs with a seed it handed out
+    pub seed_wanted: bool,
+    // The leaderboard that handed out the seed, if one did. Servers only take runs with their own seeds.
+    pub seed_endpoint: Option<String>,
+
+    // Plan text being exported or imported, and what was wrong with the last import
+    pub plan_text: String,
+    pub plan_errors: Vec<String>,
+
+    pub ghost: Option<Ghost>,
+    pub ghost_error: Option<String>,
+
+    // Keyboard
+    pub key_bindings: KeyBindings,
+    // Which action the next key press gets bound to
+    pub rebinding: Option<KeyAction>,
+    // Position in the job rows that the keyboard edits
+    pub row_cursor: Option<usize>,
+    pub show_shortcuts: bool,
+
+    // Journal entries unlocked over every run, kept between visits
+    pub journal: Vec<JournalEntryId>,
+    // Entries unlocked by the latest change, shown until dismissed
+    pub new_journal_entries: Vec<JournalEntryId>,
+    pub show_journal: bool,
+
+    pub locale: Locale,
+    pub number_format: NumberFormat,
 }
 
 #[derive(Clone, Debug)]
 pub enum AppMessage {
     AddJob(Job),
+    AddRepeatedJob(Job, usize),
+    AddJobUntilFailure(Job),
+    RepeatCountInput(String),
+    AddMacro(JobMacro),
+    SelectRow(usize),
+    MacroNameInput(String),
+    SaveMacro(),
     AddOne(usize),
     RemoveOne(usize),
     RemoveCluster(usize),
+    CycleAlternative(usize),
+    ToggleChartResource(Resource),
+    ToggleChartAxis(),
+    HighlightJob(Option<usize>),
+    ToggleRecipes(),
+    ToggleJournal(),
+    SetLocale(Locale),
+    SetNumberFormat(NumberFormat),
+    DismissJournalEntries(),
+    PlayerNameInput(String),
+    EndpointInput(String),
+    SubmitRun(),
+    LeaderboardLoaded(Result<Vec<RunRecord>, String>),
+    // The seed a leaderboard handed out for a run of the scenario, with where the leaderboard is
+    SeedIssued(String, String, Result<u64, String>),
+    PlanInput(String),
+    ExportPlan(),
+    ImportPlan(),
+    GhostPersonalBest(),
+    GhostSolver(),
+    ClearGhost(),
+    StartDaily(),
+    StartFacility(),
+    GeneratorDepthInput(String),
+    GeneratorWidthInput(String),
+    StartGenerated(),
+    ToggleEditor(),
+    EditScenario(ScenarioEdit),
+    NewDraft(),
+    ScenarioFileChosen(web_sys::File),
+    ScenarioFileRead(Result<String, String>),
+    Playtest(),
+    ToggleContentPacks(),
+    PackFileChosen(web_sys::File),
+    PackUrlInput(String),
+    LoadPackUrl(),
+    PackRead(Result<String, String>),
+    RemovePack(usize),
+    KeyPressed(String),
+    StartRebinding(KeyAction),
+    ResetBindings(),
     Undo(),
     Redo(),
 }
 
+// Packs, generated scenarios and drafts can all bring mistakes the catalogue doesn't have, so every scenario is checked as it starts
+pub fn scenario_problems(scenario: &Scenario) -> Option<String> {
+    let problems = validate_scenario(scenario);
+    for problem in problems.iter() {
+        log::error!("{}", problem);
+    }
+    if problems.is_empty() { None } else { Some(problems.join("\n")) }
+}
+
 impl Component for App {
     type Message = AppMessage;
     type Properties = ();
 
     fn create(_ctx: &Context<Self>) -> Self {
+        let active_packs = load_packs();
+        let (scenario, pack_errors) = facility_with_packs(&active_packs);
+        // Packs that no longer fit together are dropped, with the reasons shown
+        let active_packs = if pack_errors.is_empty() { active_packs } else { vec![] };
+        scenario.activate();
+        let editor_draft = load_draft().unwrap_or_else(|| scenario.to_file());
         let state = State {
+            personal_best: load_personal_best(&scenario),
+            scenario,
+            seed: new_seed(js_sys::Math::random()),
             history: vec![],
             redo_queue: vec![],
             discovered_jobs: vec![],
+            macros: vec![],
             displayed_job: None,
+            selected_rows: None,
+            macro_name: String::new(),
+            macro_error: None,
+            repeat_count: 1,
+            hidden_chart_resources: vec![],
+            chart_by_day: false,
+            highlighted_job: None,
+            show_recipes: false,
+            undo_count: 0,
+            par_days: None,
+            par_searched: false,
+            generator_depth: DEFAULT_DEPTH,
+            generator_width: DEFAULT_WIDTH,
+            generator_error: None,
+            show_editor: editor_route(),
+            editor_problems: check_scenario_file(&editor_draft),
+            editor_draft,
+            editor_error: None,
+            active_packs,
+            show_packs: false,
+            pack_url: String::new(),
+            pack_errors,
+            player_name: String::new(),
+            leaderboard_endpoint: load_endpoint(),
+            leaderboard: vec![],
+            leaderboard_error: None,
+            seed_wanted: true,
+            seed_endpoint: None,
+            plan_text: String::new(),
+            plan_errors: vec![],
+            ghost: None,
+            ghost_error: None,
+            key_bindings: load_bindings(),
+            rebinding: None,
+            row_cursor: None,
+            show_shortcuts: false,
+            journal: load_journal(),
+            new_journal_entries: vec![],
+            show_journal: false,
+            locale: load_locale(),
+            number_format: load_number_format(),
         };
+        let problems = scenario_problems(&state.scenario);
         let result = App::create_view_cache(&state);
         match result {
             Ok(view_cache) => {
-                Self {
+                let mut app = Self {
                     state,
                     view_cache,
-                    programmer_error: None,
-                }
+                    programmer_error: problems,
+                    keyboard_listener: None,
+                    file_reader: None,
+                };
+                app.discover_jobs();
+                app
             }
             Err(error) => {
                 Self {
@@ -64,8 +281,18 @@ impl Component for App {
                         user_error: None,
                         game_state: GameState::Playing,
                         resource_headings: vec![],
+                        final_resources: BTreeMap::new(),
+                        final_durability: BTreeMap::new(),
+                        final_effects: BTreeMap::new(),
+                        snapshots: vec![],
+                        ghost_days: vec![],
+                        journal_unlocks: vec![],
+                        upgrades: Upgrades::default(),
+                        times_done: BTreeMap::new(),
                     },
                     programmer_error: Some(error),
+                    keyboard_listener: None,
+                    file_reader: None,
                 }
             }
         }
@@ -81,6 +308,37 @@ impl Component for App {
                 self.add_job(job);
                 true
             }
+            AppMessage::AddRepeatedJob(job, times) => {
+                self.add_repeated_job(job, times);
+                true
+            }
+            AppMessage::AddJobUntilFailure(job) => {
+                if let Err(programmer_error) = self.add_job_until_failure(job) {
+                    self.programmer_error = Some(programmer_error);
+                }
+                true
+            }
+            AppMessage::RepeatCountInput(text) => {
+                // Anything that isn't a positive number falls back to adding the job once
+                self.state.repeat_count = text.trim().parse::<usize>().unwrap_or(1).max(1);
+                true
+            }
+            AppMessage::AddMacro(job_macro) => {
+                self.add_macro(job_macro);
+                true
+            }
+            AppMessage::SelectRow(index) => {
+                self.select_row(index);
+                true
+            }
+            AppMessage::MacroNameInput(name) => {
+                self.state.macro_name = name;
+                true
+            }
+            AppMessage::SaveMacro() => {
+                self.state.macro_error = self.save_macro().err();
+                true
+            }
             AppMessage::AddOne(index) => {
                 self.state.history.push(HistoryStep::AddOne(index));
                 self.state.redo_queue.clear();
@@ -99,11 +357,240 @@ impl Component for App {
                 self.refresh_view_cache();
                 true
             }
+            AppMessage::CycleAlternative(index) => {
+                self.state.history.push(HistoryStep::CycleAlternative(index));
+                self.state.redo_queue.clear();
+                self.refresh_view_cache();
+                true
+            }
+            AppMessage::ToggleChartResource(resource) => {
+                if self.state.hidden_chart_resources.contains(&resource) {
+                    self.state.hidden_chart_resources.retain(|hidden| *hidden != resource);
+                } else {
+                    self.state.hidden_chart_resources.push(resource);
+                }
+                true
+            }
+            AppMessage::ToggleChartAxis() => {
+                self.state.chart_by_day = !self.state.chart_by_day;
+                true
+            }
+            AppMessage::HighlightJob(job_index) => {
+                self.state.highlighted_job = job_index;
+                true
+            }
+            AppMessage::ToggleRecipes() => {
+                self.state.show_recipes = !self.state.show_recipes;
+                true
+            }
+            AppMessage::ToggleJournal() => {
+                self.state.show_journal = !self.state.show_journal;
+                true
+            }
+            AppMessage::SetLocale(locale) => {
+                set_locale(locale);
+                save_locale(locale);
+                self.state.locale = locale;
+                // Error messages are written when the jobs run, so they need to run again
+                self.refresh_view_cache();
+                true
+            }
+            AppMessage::SetNumberFormat(format) => {
+                set_number_format(format);
+                save_number_format(format);
+                self.state.number_format = format;
+                // Amounts in error messages are written when the jobs run
+                self.refresh_view_cache();
+                true
+            }
+            AppMessage::DismissJournalEntries() => {
+                self.state.new_journal_entries.clear();
+                true
+            }
+            AppMessage::PlayerNameInput(name) => {
+                self.state.player_name = name;
+                true
+            }
+            AppMessage::EndpointInput(endpoint) => {
+                save_endpoint(&endpoint);
+                self.state.leaderboard_endpoint = endpoint;
+                // A run that hasn't started yet can still get its seed from the new leaderboard
+                if self.state.history.is_empty() && needs_issued_seed(&self.state.scenario.name) {
+                    self.state.seed_wanted = true;
+                }
+                true
+            }
+            AppMessage::SubmitRun() => {
+                if let Some(run) = self.create_run_record() {
+                    // Only this browser reads local runs back, so any seed does for them
+                    let endpoint = self.state.leaderboard_endpoint.trim();
+                    if !endpoint.is_empty() && needs_issued_seed(&run.scenario) && self.state.seed_endpoint.as_deref() != Some(endpoint) {
+                        self.state.leaderboard_error = Some(text(Text::SeedFromElsewhere).to_string());
+                        return true;
+                    }
+                    let backend = backend(&self.state.leaderboard_endpoint);
+                    ctx.link().send_future(async move {
+                        let scenario = run.scenario.clone();
+                        match backend.submit(run).await {
+                            Ok(()) => AppMessage::LeaderboardLoaded(backend.best_runs(&scenario).await),
+                            Err(error) => AppMessage::LeaderboardLoaded(Err(error)),
+                        }
+                    });
+                }
+                false
+            }
+            AppMessage::LeaderboardLoaded(result) => {
+                match result {
+                    Ok(runs) => {
+                        self.state.leaderboard = runs;
+                        self.state.leaderboard_error = None;
+                    }
+                    Err(error) => {
+                        self.state.leaderboard_error = Some(error);
+                    }
+                }
+                true
+            }
+            AppMessage::SeedIssued(scenario, endpoint, result) => {
+                // A seed for a scenario the player already left, or from a leaderboard they moved away from, is no use
+                if scenario != self.state.scenario.name || endpoint != self.state.leaderboard_endpoint.trim() {
+                    return false;
+                }
+                match result {
+                    Ok(seed) if self.state.history.is_empty() => {
+                        self.state.seed = seed;
+                        self.state.seed_endpoint = Some(endpoint);
+                        self.refresh_view_cache();
+                    }
+                    Ok(_seed) => self.state.leaderboard_error = Some(text(Text::SeedTooLate).to_string()),
+                    Err(error) => self.state.leaderboard_error = Some(error),
+                }
+                true
+            }
+            AppMessage::PlanInput(text) => {
+                self.state.plan_text = text;
+                true
+            }
+            AppMessage::ExportPlan() => {
+                self.state.plan_text = self.export_plan();
+                self.state.plan_errors.clear();
+                true
+            }
+            AppMessage::ImportPlan() => {
+                self.import_plan();
+                true
+            }
+            AppMessage::GhostPersonalBest() => {
+                self.ghost_personal_best();
+                true
+            }
+            AppMessage::GhostSolver() => {
+                self.ghost_solver();
+                true
+            }
+            AppMessage::ClearGhost() => {
+                self.state.ghost = None;
+                self.state.ghost_error = None;
+                self.refresh_view_cache();
+                true
+            }
+            AppMessage::StartDaily() => {
+                let date = today();
+                match daily_scenario(&date) {
+                    Ok(scenario) => self.start_scenario(scenario, daily_seed(&date)),
+                    Err(programmer_error) => self.programmer_error = Some(programmer_error),
+                }
+                true
+            }
+            AppMessage::StartFacility() => {
+                let (scenario, pack_errors) = facility_with_packs(&self.state.active_packs);
+                self.state.pack_errors = pack_errors;
+                self.start_scenario(scenario, new_seed(js_sys::Math::random()));
+                true
+            }
+            AppMessage::GeneratorDepthInput(text) => {
+                self.state.generator_depth = text.trim().parse::<usize>().unwrap_or(DEFAULT_DEPTH).clamp(1, MAX_DEPTH);
+                true
+            }
+            AppMessage::GeneratorWidthInput(text) => {
+                self.state.generator_width = text.trim().parse::<usize>().unwrap_or(DEFAULT_WIDTH).clamp(1, MAX_WIDTH);
+                true
+            }
+            AppMessage::StartGenerated() => {
+                self.start_generated();
+                true
+            }
+            AppMessage::ToggleEditor() => {
+                self.toggle_editor();
+                true
+            }
+            AppMessage::EditScenario(edit) => {
+                self.edit_scenario(edit);
+                true
+            }
+            AppMessage::NewDraft() => {
+                self.new_draft();
+                true
+            }
+            AppMessage::ScenarioFileChosen(file) => {
+                self.read_scenario_file(file, ctx);
+                false
+            }
+            AppMessage::ScenarioFileRead(result) => {
+                self.import_scenario(result);
+                true
+            }
+            AppMessage::Playtest() => {
+                self.playtest();
+                true
+            }
+            AppMessage::ToggleContentPacks() => {
+                self.state.show_packs = !self.state.show_packs;
+                true
+            }
+            AppMessage::PackFileChosen(file) => {
+                self.read_pack_file(file, ctx);
+                false
+            }
+            AppMessage::PackUrlInput(url) => {
+                self.state.pack_url = url;
+                true
+            }
+            AppMessage::LoadPackUrl() => {
+                self.fetch_pack(ctx);
+                false
+            }
+            AppMessage::PackRead(result) => {
+                self.add_pack(result);
+                true
+            }
+            AppMessage::RemovePack(index) => {
+                self.remove_pack(index);
+                true
+            }
+            AppMessage::KeyPressed(combination) => {
+                let (changed, message) = self.handle_key(combination);
+                match message {
+                    Some(message) => Component::update(self, ctx, message),
+                    None => changed,
+                }
+            }
+            AppMessage::StartRebinding(action) => {
+                self.state.rebinding = Some(action);
+                true
+            }
+            AppMessage::ResetBindings() => {
+                self.state.key_bindings = default_bindings();
+                self.state.rebinding = None;
+                save_bindings(&self.state.key_bindings);
+                true
+            }
             AppMessage::Undo() => {
                 match self.state.history.pop() {
                     None => {}
                     Some(step) => {
                         self.state.redo_queue.push(step);
+                        self.state.undo_count += 1;
                     }
                 }
                 self.refresh_view_cache();
@@ -122,8 +609,24 @@ impl Component for App {
         }
     }
 
+    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
+        if first_render {
+            self.keyboard_listener = Some(keyboard_listener(ctx));
+        }
+        if self.state.seed_wanted {
+            self.state.seed_wanted = false;
+            let backend = backend(&self.state.leaderboard_endpoint);
+            let scenario = self.state.scenario.name.clone();
+            let endpoint = self.state.leaderboard_endpoint.trim().to_string();
+            ctx.link().send_future(async move {
+                let result = backend.issue_seed(&scenario).await;
+                AppMessage::SeedIssued(scenario, endpoint, result)
+            });
+        }
+    }
+
     fn view(&self, ctx: &Context<Self>) -> Html {
-        crate::view::view(self, ctx)
+        crate::view::view::view(self, ctx)
     }
 }
 
@@ -0,0 +1,127 @@
+use yew::prelude::*;
+use crate::core::locale::{count_of, text, Text};
+use crate::core::quantity::{format_quantity, quantity, to_f64, Quantity};
+use crate::core::resource::{attributes, Resource};
+use crate::view::app::{App, AppMessage};
+use crate::view::view_logic::ResourceSnapshot;
+
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 200.0;
+const PADDING: f64 = 24.0;
+const COLORS: [&str; 9] = [
+    "#2563eb", "#dc2626", "#16a34a", "#9333ea", "#ea580c", "#0891b2", "#ca8a04", "#db2777", "#475569",
+];
+
+// Resources that should get a line, in a stable order so that colors don't jump around
+fn chart_resources(snapshots: &[ResourceSnapshot]) -> Vec<Resource> {
+    let attributes = attributes();
+    let mut resources = Vec::new();
+    for snapshot in snapshots.iter() {
+        for (resource, amount) in snapshot.resources.iter() {
+            let visible = attributes.get(resource).map(|attribute| attribute.visible).unwrap_or(true);
+            if visible && *amount != quantity(0) && !resources.contains(resource) {
+                resources.push(*resource);
+            }
+        }
+    }
+    resources.sort();
+    resources
+}
+
+// The points that are plotted. On the day axis, only the last job of each day is kept.
+fn plotted_snapshots(snapshots: &[ResourceSnapshot], by_day: bool) -> Vec<(usize, &ResourceSnapshot)> {
+    let mut points: Vec<(usize, &ResourceSnapshot)> = Vec::new();
+    for snapshot in snapshots.iter() {
+        if by_day {
+            match points.last_mut() {
+                Some((day, last)) if *day == snapshot.day => {
+                    *last = snapshot;
+                }
+                _ => {
+                    points.push((snapshot.day, snapshot));
+                }
+            }
+        } else {
+            points.push((snapshot.job_index, snapshot));
+        }
+    }
+    points
+}
+
+pub fn chart(app: &App, ctx: &Context<App>) -> Html {
+    let snapshots = &app.view_cache.snapshots;
+    let resources = chart_resources(snapshots);
+    let points = plotted_snapshots(snapshots, app.state.chart_by_day);
+    let max_x = points.last().map(|(x, _snapshot)| *x).unwrap_or(0).max(1) as f64;
+    let max_amount = points.iter()
+        .flat_map(|(_x, snapshot)| {
+            resources.iter()
+                .filter(|resource| !app.state.hidden_chart_resources.contains(resource))
+                .map(|resource| snapshot.resources.get(resource).cloned().unwrap_or_default())
+        })
+        .max()
+        .unwrap_or_default();
+    let max_y = to_f64(&max_amount).max(1.0);
+    let to_x = |x: usize| PADDING + (x as f64) / max_x * (WIDTH - 2.0 * PADDING);
+    let to_y = |amount: &Quantity| HEIGHT - PADDING - to_f64(amount) / max_y * (HEIGHT - 2.0 * PADDING);
+
+    html! {
+    <div class="border border-slate-900 p-2 my-2">
+        <div class="flex flex-row flex-wrap gap-2 mb-1">
+            <button class="border border-slate-900 px-2 rounded-md" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::ToggleChartAxis())}>
+                {if app.state.chart_by_day { text(Text::ByDay) } else { text(Text::ByJob) }}
+            </button>
+        { for resources.iter().enumerate().map(|(color_index, resource)| {
+            let hidden = app.state.hidden_chart_resources.contains(resource);
+            let callback_resource = *resource;
+            let style = if hidden {
+                "color: #94a3b8".to_string()
+            } else {
+                format!("color: {}", COLORS[color_index % COLORS.len()])
+            };
+            html! {
+            <button class="border border-slate-900 px-2 rounded-md" style={style} onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::ToggleChartResource(callback_resource))}>
+                {resource.to_string()}
+            </button>
+            }
+        })}
+        </div>
+        <svg width={WIDTH.to_string()} height={HEIGHT.to_string()} viewBox={format!("0 0 {} {}", WIDTH, HEIGHT)}>
+            <line x1={PADDING.to_string()} y1={(HEIGHT - PADDING).to_string()} x2={(WIDTH - PADDING).to_string()} y2={(HEIGHT - PADDING).to_string()} stroke="#0f172a" />
+            <line x1={PADDING.to_string()} y1={PADDING.to_string()} x2={PADDING.to_string()} y2={(HEIGHT - PADDING).to_string()} stroke="#0f172a" />
+            <text x={PADDING.to_string()} y={(PADDING - 6.0).to_string()} font-size="10">{format_quantity(&max_amount)}</text>
+            <text x={(WIDTH - PADDING).to_string()} y={(HEIGHT - 8.0).to_string()} font-size="10">{max_x.to_string()}</text>
+        { for resources.iter().enumerate()
+            .filter(|(_color_index, resource)| !app.state.hidden_chart_resources.contains(resource))
+            .map(|(color_index, resource)| {
+            let color = COLORS[color_index % COLORS.len()];
+            let line = points.iter()
+                .map(|(x, snapshot)| format!("{},{}", to_x(*x), to_y(&snapshot.resources.get(resource).cloned().unwrap_or_default())))
+                .collect::<Vec<_>>()
+                .join(" ");
+            html! {
+            <g>
+                <polyline points={line} fill="none" stroke={color} stroke-width="2" />
+            { for points.iter().map(|(x, snapshot)| {
+                let job_index = snapshot.job_index;
+                let highlighted = app.state.highlighted_job == Some(job_index);
+                let amount = snapshot.resources.get(resource).cloned().unwrap_or_default();
+                html! {
+                <circle
+                    cx={to_x(*x).to_string()}
+                    cy={to_y(&amount).to_string()}
+                    r={if highlighted { "5" } else { "3" }}
+                    fill={color}
+                    onmouseover={ctx.link().callback(move |_event: MouseEvent| AppMessage::HighlightJob(Some(job_index)))}
+                    onmouseout={ctx.link().callback(|_event: MouseEvent| AppMessage::HighlightJob(None))}>
+                    <title>{count_of(resource, &amount)}</title>
+                </circle>
+                }
+            })}
+            </g>
+            }
+        })}
+        </svg>
+    </div>
+    }
+}
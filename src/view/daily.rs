@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use gloo::storage::{LocalStorage, Storage};
+use yew::prelude::*;
+use crate::core::daily::{banned_job, daily_date};
+use crate::core::catalogue::create_job;
+use crate::core::leaderboard::needs_issued_seed;
+use crate::core::locale::{days, fill, job_name, text, Text};
+use crate::core::scenario::Scenario;
+use crate::view::app::{scenario_problems, App, AppMessage};
+use crate::view::summary::load_personal_best;
+
+const DAILY_RESULTS_KEY: &str = "strange-facility-daily-results";
+
+// Today's date in UTC, written as YYYY-MM-DD, so that everyone plays the same variant on the same day
+pub fn today() -> String {
+    let now = js_sys::Date::new_0();
+    format!("{:04}-{:02}-{:02}", now.get_utc_full_year(), now.get_utc_month() + 1, now.get_utc_date())
+}
+
+// The fewest days each daily challenge played in this browser was won in, by date.
+// Kept apart from the personal best, since every day is a different puzzle.
+pub fn load_daily_results() -> BTreeMap<String, usize> {
+    LocalStorage::get::<BTreeMap<String, usize>>(DAILY_RESULTS_KEY).unwrap_or_default()
+}
+
+pub fn save_daily_result(date: &str, spent_days: usize) {
+    let mut results = load_daily_results();
+    results.insert(date.to_string(), spent_days);
+    if let Err(error) = LocalStorage::set(DAILY_RESULTS_KEY, results) {
+        log::warn!("Couldn't save the daily result: {}", error);
+    }
+}
+
+impl App {
+    // Start over in another scenario. Settings and the journal stay, but the run and everything about it goes.
+    // The scenario's resources replace the ones defined before.
+    pub fn start_scenario(&mut self, scenario: Scenario, seed: u64) {
+        // Jobs are found again in the new scenario, which refreshing the view cache below does
+        self.state.discovered_jobs.clear();
+        self.state.history.clear();
+        self.state.redo_queue.clear();
+        self.state.displayed_job = None;
+        self.state.selected_rows = None;
+        self.state.highlighted_job = None;
+        self.state.row_cursor = None;
+        self.state.undo_count = 0;
+        self.state.personal_best = load_personal_best(&scenario);
+        self.state.par_days = None;
+        self.state.par_searched = false;
+        self.state.leaderboard.clear();
+        self.state.leaderboard_error = None;
+        self.state.ghost = None;
+        self.state.ghost_error = None;
+        self.state.seed_wanted = needs_issued_seed(&scenario.name);
+        self.state.seed_endpoint = None;
+        scenario.activate();
+        self.programmer_error = scenario_problems(&scenario);
+        self.state.scenario = scenario;
+        self.state.seed = seed;
+        self.refresh_view_cache();
+    }
+}
+
+// Switch between the facility and today's challenge, and say what is different today
+pub fn daily_view(app: &App, ctx: &Context<App>) -> Html {
+    match daily_date(&app.state.scenario.name) {
+        Some(date) => {
+            let banned = banned_job(&app.state.scenario)
+                .map(|job_id| fill(Text::DailyBanned, &[&job_name(&create_job(job_id))]));
+            let best = load_daily_results().get(date)
+                .map(|spent_days| fill(Text::DailyBest, &[&days(*spent_days)]));
+            html! {
+            <div class="flex flex-row flex-wrap gap-2 items-center p-2 border-2 border-sky-700 bg-sky-50">
+                <div class="font-bold">{fill(Text::DailyTitle, &[&date])}</div>
+                { for banned.into_iter().map(|banned| html! { <div>{banned}</div> }) }
+                { for best.into_iter().map(|best| html! { <div class="italic">{best}</div> }) }
+                <button class="border border-slate-900 p-1 rounded-md" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::StartFacility())}>
+                    {text(Text::BackToFacility)}
+                </button>
+            </div>
+            }
+        }
+        None => html! {
+            <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::StartDaily())}>
+                {text(Text::DailyChallenge)}
+            </button>
+        },
+    }
+}
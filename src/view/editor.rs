@@ -0,0 +1,431 @@
+use gloo::file::callbacks::read_as_text;
+use gloo::storage::{LocalStorage, Storage};
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+use crate::core::amount::Amount;
+use crate::core::editor::{amount_kind, amount_numbers, apply_edit, check_scenario_file, draft_resources, job_id_text, kind_numbers, modifier_change, modifier_kind, new_scenario_file, playtest_name, resource_id_text, DeltaOwner, ScenarioEdit, AMOUNT_KINDS, MODIFIER_KINDS};
+use crate::core::locale::{fill, text, Text};
+use crate::core::random::new_seed;
+use crate::core::resource::Resource;
+use crate::core::scenario::{Scenario, ScenarioFile};
+use crate::core::upgrade::JobModifier;
+use crate::view::app::{App, AppMessage};
+
+const EDITOR_DRAFT_KEY: &str = "strange-facility-editor-draft";
+// The editor is its own page, so that designers can link and bookmark it
+const EDITOR_ROUTE: &str = "#editor";
+
+pub fn editor_route() -> bool {
+    gloo::utils::window().location().hash().map(|hash| hash == EDITOR_ROUTE).unwrap_or(false)
+}
+
+fn set_editor_route(open: bool) {
+    let hash = if open { EDITOR_ROUTE } else { "" };
+    if let Err(error) = gloo::utils::window().location().set_hash(hash) {
+        log::warn!("Couldn't change the page address: {:?}", error);
+    }
+}
+
+// The draft is kept between visits, so that closing the tab doesn't lose an afternoon of work
+pub fn load_draft() -> Option<ScenarioFile> {
+    LocalStorage::get::<ScenarioFile>(EDITOR_DRAFT_KEY).ok()
+}
+
+fn save_draft(draft: &ScenarioFile) {
+    if let Err(error) = LocalStorage::set(EDITOR_DRAFT_KEY, draft) {
+        log::warn!("Couldn't save the scenario draft: {}", error);
+    }
+}
+
+impl App {
+    pub fn toggle_editor(&mut self) {
+        self.state.show_editor = !self.state.show_editor;
+        set_editor_route(self.state.show_editor);
+    }
+
+    fn set_draft(&mut self, draft: ScenarioFile) {
+        self.state.editor_problems = check_scenario_file(&draft);
+        save_draft(&draft);
+        self.state.editor_draft = draft;
+    }
+
+    pub fn edit_scenario(&mut self, edit: ScenarioEdit) {
+        // Active packs add alternatives to jobs by id, which the draft can't rewrite for them
+        if let ScenarioEdit::SetJobId(index, _id) = &edit {
+            if let Some(job) = self.state.editor_draft.jobs.get(*index) {
+                if let Some(pack) = self.state.active_packs.iter().find(|pack| pack.combinations.iter().any(|combination| combination.job == job.id)) {
+                    self.state.editor_error = Some(fill(Text::JobIdCombined, &[&job_id_text(&job.id), &pack.name]));
+                    return;
+                }
+            }
+        }
+        self.state.editor_error = None;
+        let mut draft = self.state.editor_draft.clone();
+        apply_edit(&mut draft, edit);
+        self.set_draft(draft);
+    }
+
+    pub fn new_draft(&mut self) {
+        self.state.editor_error = None;
+        self.set_draft(new_scenario_file());
+    }
+
+    pub fn read_scenario_file(&mut self, file: web_sys::File, ctx: &Context<App>) {
+        let link = ctx.link().clone();
+        let reader = read_as_text(&gloo::file::File::from(file), move |result| {
+            link.send_message(AppMessage::ScenarioFileRead(result.map_err(|error| error.to_string())));
+        });
+        self.file_reader = Some(reader);
+    }
+
+    // Anything that parses becomes the draft, even with problems, since fixing them is what the editor is for
+    pub fn import_scenario(&mut self, result: Result<String, String>) {
+        self.file_reader = None;
+        let parsed = result.and_then(|json| serde_json::from_str::<ScenarioFile>(&json).map_err(|error| error.to_string()));
+        match parsed {
+            Ok(draft) => {
+                self.state.editor_error = None;
+                self.set_draft(draft);
+            }
+            Err(error) => self.state.editor_error = Some(error),
+        }
+    }
+
+    pub fn playtest(&mut self) {
+        if !self.state.editor_problems.is_empty() {
+            return;
+        }
+        let mut file = self.state.editor_draft.clone();
+        file.name = playtest_name(&file.name);
+        let scenario = Scenario::from_file(&file);
+        self.start_scenario(scenario, new_seed(js_sys::Math::random()));
+        self.toggle_editor();
+    }
+}
+
+fn edit_callback<E: 'static, F: Fn(E) -> ScenarioEdit + 'static>(ctx: &Context<App>, edit: F) -> Callback<E> {
+    ctx.link().callback(move |event: E| AppMessage::EditScenario(edit(event)))
+}
+
+fn text_input(ctx: &Context<App>, label: Text, value: String, edit: impl Fn(String) -> ScenarioEdit + 'static) -> Html {
+    html! {
+    <label class="flex flex-col text-sm">
+        {text(label)}
+        <input class="border border-slate-900 p-1 rounded-md" value={value}
+            oninput={edit_callback(ctx, move |event: InputEvent| {
+                let input: HtmlInputElement = event.target_unchecked_into();
+                edit(input.value())
+            })}
+        />
+    </label>
+    }
+}
+
+// Ids are interned for good once they are set, so they only change when the field is left rather than on every key
+fn id_input(ctx: &Context<App>, value: String, edit: impl Fn(String) -> ScenarioEdit + 'static) -> Html {
+    html! {
+    <label class="flex flex-col text-sm">
+        {text(Text::Id)}
+        <input class="border border-slate-900 p-1 rounded-md" value={value}
+            onchange={edit_callback(ctx, move |event: Event| {
+                let input: HtmlInputElement = event.target_unchecked_into();
+                edit(input.value())
+            })}
+        />
+    </label>
+    }
+}
+
+fn number_input(ctx: &Context<App>, label: Text, value: String, edit: impl Fn(String) -> ScenarioEdit + 'static) -> Html {
+    html! {
+    <label class="flex flex-col text-sm">
+        {text(label)}
+        <input type="number" min="0" class="border border-slate-900 p-1 rounded-md w-20" value={value}
+            oninput={edit_callback(ctx, move |event: InputEvent| {
+                let input: HtmlInputElement = event.target_unchecked_into();
+                edit(input.value())
+            })}
+        />
+    </label>
+    }
+}
+
+fn button(ctx: &Context<App>, label: Text, edit: ScenarioEdit) -> Html {
+    html! {
+    <button class="border border-slate-900 p-1 rounded-md bg-white"
+        onclick={edit_callback(ctx, move |_event: MouseEvent| edit.clone())}>
+        {text(label)}
+    </button>
+    }
+}
+
+fn resource_label(resource: &Resource, draft: &ScenarioFile) -> String {
+    match draft.resources.iter().find(|definition| definition.id == *resource) {
+        Some(definition) if !definition.name.is_empty() => definition.name.clone(),
+        _ => resource_id_text(resource),
+    }
+}
+
+// One `(Resource, Amount)` entry of an alternative
+fn entry_view(app: &App, ctx: &Context<App>, owner: DeltaOwner, alternative: usize, entry: usize, resource: &Resource, amount: &Amount) -> Html {
+    let draft = &app.state.editor_draft;
+    let resources = draft_resources(draft);
+    let kind = amount_kind(amount);
+    let (first, second) = amount_numbers(amount);
+    let chosen_resources = resources.clone();
+    html! {
+    <div class="flex flex-row gap-1 items-center">
+        <select class="border border-slate-900 p-1 rounded-md"
+            onchange={ctx.link().batch_callback(move |event: Event| {
+                let select: HtmlSelectElement = event.target_unchecked_into();
+                usize::try_from(select.selected_index()).ok()
+                    .and_then(|index| chosen_resources.get(index).cloned())
+                    .map(|resource| AppMessage::EditScenario(ScenarioEdit::SetEntryResource(owner, alternative, entry, resource)))
+            })}>
+        { for resources.iter().map(|option| html! {
+            <option selected={option == resource}>{resource_label(option, draft)}</option>
+        })}
+        </select>
+        <select class="border border-slate-900 p-1 rounded-md"
+            onchange={edit_callback(ctx, move |event: Event| {
+                let select: HtmlSelectElement = event.target_unchecked_into();
+                ScenarioEdit::SetEntryKind(owner, alternative, entry, usize::try_from(select.selected_index()).unwrap_or(0))
+            })}>
+        { for AMOUNT_KINDS.iter().enumerate().map(|(index, name)| html! {
+            <option selected={index == kind}>{*name}</option>
+        })}
+        </select>
+        { for [first, second].into_iter().enumerate().take(kind_numbers(kind)).map(|(which, number)| html! {
+            <input type="number" class="border border-slate-900 p-1 rounded-md w-16" value={number.to_string()}
+                oninput={edit_callback(ctx, move |event: InputEvent| {
+                    let input: HtmlInputElement = event.target_unchecked_into();
+                    ScenarioEdit::SetEntryNumber(owner, alternative, entry, which, input.value())
+                })}
+            />
+        })}
+        <span class="text-slate-600 text-sm">{amount.to_string()}</span>
+        { button(ctx, Text::Remove, ScenarioEdit::RemoveEntry(owner, alternative, entry)) }
+    </div>
+    }
+}
+
+// Alternative delta rows, where the first one whose requirements are met is the one that applies
+fn deltas_view(app: &App, ctx: &Context<App>, owner: DeltaOwner, deltas: &[Vec<(Resource, Amount)>]) -> Html {
+    html! {
+    <div class="flex flex-col gap-1 pl-2 border-l-2 border-slate-400">
+        { for deltas.iter().enumerate().map(|(alternative, delta)| html! {
+            <div class="flex flex-col gap-1">
+                <div class="flex flex-row gap-2 items-center">
+                    <span class="text-sm font-bold">{fill(Text::AlternativeNumber, &[&(alternative + 1)])}</span>
+                    { button(ctx, Text::AddChange, ScenarioEdit::AddEntry(owner, alternative)) }
+                    { button(ctx, Text::Remove, ScenarioEdit::RemoveAlternative(owner, alternative)) }
+                </div>
+                { for delta.iter().enumerate().map(|(entry, (resource, amount))| entry_view(app, ctx, owner, alternative, entry, resource, amount)) }
+            </div>
+        })}
+        <div>{ button(ctx, Text::AddAlternative, ScenarioEdit::AddAlternative(owner)) }</div>
+    </div>
+    }
+}
+
+// One modifier of an upgrade: the job it changes, how, and by how much
+fn modifier_view(app: &App, ctx: &Context<App>, index: usize, modifier_index: usize, modifier: &JobModifier) -> Html {
+    let draft = &app.state.editor_draft;
+    let jobs = draft.jobs.iter().map(|job| job.id).collect::<Vec<_>>();
+    let resources = draft_resources(draft);
+    let kind = modifier_kind(modifier);
+    let chosen_resources = resources.clone();
+    html! {
+    <div class="flex flex-row gap-1 items-center">
+        <select class="border border-slate-900 p-1 rounded-md"
+            onchange={ctx.link().batch_callback(move |event: Event| {
+                let select: HtmlSelectElement = event.target_unchecked_into();
+                usize::try_from(select.selected_index()).ok()
+                    .and_then(|job| jobs.get(job).cloned())
+                    .map(|job| AppMessage::EditScenario(ScenarioEdit::SetModifierJob(index, modifier_index, job)))
+            })}>
+        { for draft.jobs.iter().map(|job| html! {
+            <option selected={job.id == modifier.job()}>{if job.name.is_empty() { job_id_text(&job.id) } else { job.name.clone() }}</option>
+        })}
+        </select>
+        <select class="border border-slate-900 p-1 rounded-md"
+            onchange={edit_callback(ctx, move |event: Event| {
+                let select: HtmlSelectElement = event.target_unchecked_into();
+                ScenarioEdit::SetModifierKind(index, modifier_index, usize::try_from(select.selected_index()).unwrap_or(0))
+            })}>
+        { for MODIFIER_KINDS.iter().enumerate().map(|(option, name)| html! {
+            <option selected={option == kind}>{*name}</option>
+        })}
+        </select>
+        { if let JobModifier::Amount(_job, resource, _change) = modifier {
+            html! {
+            <select class="border border-slate-900 p-1 rounded-md"
+                onchange={ctx.link().batch_callback(move |event: Event| {
+                    let select: HtmlSelectElement = event.target_unchecked_into();
+                    usize::try_from(select.selected_index()).ok()
+                        .and_then(|resource| chosen_resources.get(resource).cloned())
+                        .map(|resource| AppMessage::EditScenario(ScenarioEdit::SetModifierResource(index, modifier_index, resource)))
+                })}>
+            { for resources.iter().map(|option| html! {
+                <option selected={option == resource}>{resource_label(option, draft)}</option>
+            })}
+            </select>
+            }
+        } else {
+            html! { <></> }
+        }}
+        <input type="number" class="border border-slate-900 p-1 rounded-md w-16" value={modifier_change(modifier).to_string()}
+            oninput={edit_callback(ctx, move |event: InputEvent| {
+                let input: HtmlInputElement = event.target_unchecked_into();
+                ScenarioEdit::SetModifierChange(index, modifier_index, input.value())
+            })}
+        />
+        { button(ctx, Text::Remove, ScenarioEdit::RemoveModifier(index, modifier_index)) }
+    </div>
+    }
+}
+
+// The modifiers that make a job an upgrade, which only has to be done once
+fn modifiers_view(app: &App, ctx: &Context<App>, index: usize, modifiers: &[JobModifier]) -> Html {
+    html! {
+    <div class="flex flex-col gap-1 pl-2 border-l-2 border-slate-400">
+        <span class="text-sm">{text(Text::UpgradeEffects)}</span>
+        { for modifiers.iter().enumerate().map(|(modifier_index, modifier)| modifier_view(app, ctx, index, modifier_index, modifier)) }
+        <div>{ button(ctx, Text::AddModifier, ScenarioEdit::AddModifier(index)) }</div>
+    </div>
+    }
+}
+
+fn resources_view(app: &App, ctx: &Context<App>) -> Html {
+    html! {
+    <div class="flex flex-col gap-2">
+        <h2 class="text-lg font-bold">{text(Text::Resources)}</h2>
+        { for app.state.editor_draft.resources.iter().enumerate().map(|(index, definition)| html! {
+            <div class="flex flex-col gap-1 p-2 border border-slate-400 bg-white">
+                <div class="flex flex-row flex-wrap gap-2 items-end">
+                    { id_input(ctx, resource_id_text(&definition.id), move |value| ScenarioEdit::SetResourceId(index, value)) }
+                    { text_input(ctx, Text::Name, definition.name.clone(), move |value| ScenarioEdit::SetResourceName(index, value)) }
+                    { text_input(ctx, Text::PluralName, definition.plural_name.clone(), move |value| ScenarioEdit::SetResourcePluralName(index, value)) }
+                    <label class="flex flex-col text-sm">
+                        {text(Text::Visible)}
+                        <input type="checkbox" checked={definition.visible}
+                            onchange={edit_callback(ctx, move |event: Event| {
+                                let input: HtmlInputElement = event.target_unchecked_into();
+                                ScenarioEdit::SetResourceVisible(index, input.checked())
+                            })}
+                        />
+                    </label>
+                    { number_input(ctx, Text::Row, definition.row.to_string(), move |value| ScenarioEdit::SetResourceRow(index, value)) }
+                    { button(ctx, Text::Remove, ScenarioEdit::RemoveResource(index)) }
+                </div>
+                <div class="text-sm">{text(Text::Upkeep)}</div>
+                { deltas_view(app, ctx, DeltaOwner::Upkeep(index), &definition.upkeep) }
+            </div>
+        })}
+        <div>{ button(ctx, Text::AddResource, ScenarioEdit::AddResource()) }</div>
+    </div>
+    }
+}
+
+fn jobs_view(app: &App, ctx: &Context<App>) -> Html {
+    let draft = &app.state.editor_draft;
+    html! {
+    <div class="flex flex-col gap-2">
+        <h2 class="text-lg font-bold">{text(Text::Jobs)}</h2>
+        { for draft.jobs.iter().enumerate().map(|(index, job)| html! {
+            <div class="flex flex-col gap-1 p-2 border border-slate-400 bg-white">
+                <div class="flex flex-row flex-wrap gap-2 items-end">
+                    { id_input(ctx, job_id_text(&job.id), move |value| ScenarioEdit::SetJobId(index, value)) }
+                    { text_input(ctx, Text::Name, job.name.clone(), move |value| ScenarioEdit::SetJobName(index, value)) }
+                    { number_input(ctx, Text::TimeSlots, job.time_slots.to_string(), move |value| ScenarioEdit::SetJobTimeSlots(index, value)) }
+                    <label class="flex flex-col text-sm">
+                        {text(Text::WinsTheRun)}
+                        <input type="radio" name="win-job" checked={job.id == draft.win_job}
+                            onchange={edit_callback(ctx, move |_event: Event| ScenarioEdit::SetWinJob(index))}
+                        />
+                    </label>
+                    { button(ctx, Text::Remove, ScenarioEdit::RemoveJob(index)) }
+                </div>
+                <label class="flex flex-col text-sm">
+                    {text(Text::Description)}
+                    <textarea class="border border-slate-900 p-1 rounded-md" value={job.description.clone()}
+                        oninput={edit_callback(ctx, move |event: InputEvent| {
+                            let input: HtmlTextAreaElement = event.target_unchecked_into();
+                            ScenarioEdit::SetJobDescription(index, input.value())
+                        })}
+                    />
+                </label>
+                { deltas_view(app, ctx, DeltaOwner::Job(index), &job.end_deltas) }
+                { modifiers_view(app, ctx, index, &job.modifiers) }
+            </div>
+        })}
+        <div>{ button(ctx, Text::AddJob, ScenarioEdit::AddJob()) }</div>
+    </div>
+    }
+}
+
+pub fn editor_view(app: &App, ctx: &Context<App>) -> Html {
+    let draft = &app.state.editor_draft;
+    let problems = &app.state.editor_problems;
+    let export = serde_json::to_string_pretty(draft)
+        .map(|json| format!("data:application/json;charset=utf-8,{}", js_sys::encode_uri_component(&json)))
+        .unwrap_or_default();
+    html! {
+    <div class="p-2 flex flex-col gap-4 bg-blue-100">
+        <div class="flex flex-row flex-wrap gap-2 items-center">
+            <h1 class="text-xl font-bold">{text(Text::ScenarioEditor)}</h1>
+            <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::ToggleEditor())}>
+                {text(Text::BackToGame)}
+            </button>
+            <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::NewDraft())}>
+                {text(Text::NewScenario)}
+            </button>
+            <button
+                disabled={!problems.is_empty()}
+                title={if problems.is_empty() { "" } else { text(Text::FixProblemsFirst) }}
+                class={if problems.is_empty() {
+                    "border background-slate-100 p-2 rounded-md border-slate-900"
+                } else {
+                    "border background-slate-100 p-2 rounded-md border-slate-400 text-slate-400"
+                }}
+                onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::Playtest())}>
+                {text(Text::Playtest)}
+            </button>
+            <a class="border border-slate-900 p-2 rounded-md" href={export} download={format!("{}.json", draft.name)}>
+                {text(Text::ExportScenario)}
+            </a>
+            <label class="border border-slate-900 p-2 rounded-md cursor-pointer">
+                {text(Text::ImportScenario)}
+                <input type="file" accept=".json,application/json" class="hidden"
+                    onchange={ctx.link().batch_callback(|event: Event| {
+                        let input: HtmlInputElement = event.target_unchecked_into();
+                        input.files().and_then(|files| files.get(0)).map(AppMessage::ScenarioFileChosen)
+                    })}
+                />
+            </label>
+        </div>
+        { for app.state.editor_error.iter().map(|error| html! { <div class="text-red-700">{error}</div> }) }
+        // Live validation of the draft
+        <div class="p-2 border-2 border-slate-400 bg-white">
+        { if problems.is_empty() {
+            html! { <div class="text-green-700">{text(Text::NoProblems)}</div> }
+        } else {
+            html! {
+            <ul class="list-disc pl-4 text-red-700">
+                { for problems.iter().map(|problem| html! { <li>{problem}</li> }) }
+            </ul>
+            }
+        }}
+        </div>
+        <div class="flex flex-row gap-2 items-end">
+            { text_input(ctx, Text::ScenarioName, draft.name.clone(), ScenarioEdit::SetName) }
+        </div>
+        { resources_view(app, ctx) }
+        { jobs_view(app, ctx) }
+        <div class="flex flex-col gap-2">
+            <h2 class="text-lg font-bold">{text(Text::StartingResources)}</h2>
+            { deltas_view(app, ctx, DeltaOwner::StartingResources, &draft.starting_resources) }
+        </div>
+    </div>
+    }
+}
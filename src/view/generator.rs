@@ -0,0 +1,71 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use crate::core::generator::{difficulty, generate, generated_settings, GeneratorSettings, MAX_DEPTH, MAX_WIDTH};
+use crate::core::locale::{days, fill, text, Text};
+use crate::core::random::new_seed;
+use crate::view::app::{App, AppMessage};
+
+impl App {
+    // Start a new generated scenario with the chosen size. The solver already found par while generating it.
+    pub fn start_generated(&mut self) {
+        let settings = GeneratorSettings {
+            seed: new_seed(js_sys::Math::random()),
+            depth: self.state.generator_depth,
+            width: self.state.generator_width,
+        };
+        match generate(&settings) {
+            Ok(generated) => {
+                self.start_scenario(generated.scenario, settings.seed);
+                self.state.par_days = Some(generated.par);
+                self.state.par_searched = true;
+                self.state.generator_error = None;
+            }
+            Err(error) => self.state.generator_error = Some(error),
+        }
+    }
+}
+
+// Choose the size of a generated scenario, or say how hard the one being played is
+pub fn generator_view(app: &App, ctx: &Context<App>) -> Html {
+    match generated_settings(&app.state.scenario.name) {
+        Some(settings) => {
+            let rating = app.state.par_days
+                .map(|par| fill(Text::GeneratedPar, &[&difficulty(par).name(), &days(par)]));
+            html! {
+            <div class="flex flex-row flex-wrap gap-2 items-center p-2 border-2 border-emerald-700 bg-emerald-50">
+                <div class="font-bold">{fill(Text::GeneratedTitle, &[&settings.seed])}</div>
+                { for rating.into_iter().map(|rating| html! { <div>{rating}</div> }) }
+                <button class="border border-slate-900 p-1 rounded-md" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::StartFacility())}>
+                    {text(Text::BackToFacility)}
+                </button>
+            </div>
+            }
+        }
+        None => html! {
+            <div class="flex flex-row gap-2 items-center">
+                <label>{text(Text::GeneratorDepth)}</label>
+                <input type="number" min="1" max={MAX_DEPTH.to_string()}
+                    class="border border-slate-900 p-2 rounded-md w-16"
+                    value={app.state.generator_depth.to_string()}
+                    oninput={ctx.link().callback(|event: InputEvent| {
+                        let input: HtmlInputElement = event.target_unchecked_into();
+                        AppMessage::GeneratorDepthInput(input.value())
+                    })}
+                />
+                <label>{text(Text::GeneratorWidth)}</label>
+                <input type="number" min="1" max={MAX_WIDTH.to_string()}
+                    class="border border-slate-900 p-2 rounded-md w-16"
+                    value={app.state.generator_width.to_string()}
+                    oninput={ctx.link().callback(|event: InputEvent| {
+                        let input: HtmlInputElement = event.target_unchecked_into();
+                        AppMessage::GeneratorWidthInput(input.value())
+                    })}
+                />
+                <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::StartGenerated())}>
+                    {text(Text::GenerateScenario)}
+                </button>
+                { for app.state.generator_error.iter().map(|error| html! { <div class="text-red-700">{error}</div> }) }
+            </div>
+        },
+    }
+}
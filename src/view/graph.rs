@@ -0,0 +1,58 @@
+use std::f64::consts::PI;
+use yew::prelude::*;
+use crate::core::graph::{recipe_graph, GraphNode};
+use crate::view::app::App;
+
+const SIZE: f64 = 520.0;
+const RADIUS: f64 = 210.0;
+
+// Draw the recipes of the discovered jobs, with the nodes spread out on a circle
+pub fn recipe_graph_view(app: &App) -> Html {
+    let jobs = &app.state.discovered_jobs;
+    let graph = recipe_graph(jobs);
+    let center = SIZE / 2.0;
+    let position = |node: &GraphNode| {
+        let index = graph.nodes.iter().position(|other| other == node).unwrap_or(0);
+        let angle = 2.0 * PI * (index as f64) / (graph.nodes.len().max(1) as f64);
+        (center + RADIUS * angle.cos(), center + RADIUS * angle.sin())
+    };
+
+    html! {
+    <div class="border border-slate-900 p-2 my-2">
+        <svg width={SIZE.to_string()} height={SIZE.to_string()} viewBox={format!("0 0 {} {}", SIZE, SIZE)}>
+            <defs>
+                <marker id="arrow" viewBox="0 0 10 10" refX="18" refY="5" markerWidth="6" markerHeight="6" orient="auto-start-reverse">
+                    <path d="M 0 0 L 10 5 L 0 10 z" fill="#0f172a" />
+                </marker>
+            </defs>
+        { for graph.edges.iter().map(|edge| {
+            let (x1, y1) = position(&edge.from);
+            let (x2, y2) = position(&edge.to);
+            html! {
+            <g>
+                <line x1={x1.to_string()} y1={y1.to_string()} x2={x2.to_string()} y2={y2.to_string()} stroke="#64748b" marker-end="url(#arrow)" />
+                <text x={((x1 + x2) / 2.0).to_string()} y={((y1 + y2) / 2.0).to_string()} font-size="9" fill="#334155">{edge.label.clone()}</text>
+            </g>
+            }
+        })}
+        { for graph.nodes.iter().map(|node| {
+            let (x, y) = position(node);
+            let shape = match node {
+                GraphNode::Resource(_) => html! {
+                    <circle cx={x.to_string()} cy={y.to_string()} r="8" fill="#dbeafe" stroke="#0f172a" />
+                },
+                GraphNode::Job(_) => html! {
+                    <rect x={(x - 8.0).to_string()} y={(y - 8.0).to_string()} width="16" height="16" fill="#f1f5f9" stroke="#0f172a" />
+                },
+            };
+            html! {
+            <g>
+                { shape }
+                <text x={(x + 10.0).to_string()} y={(y - 10.0).to_string()} font-size="11">{node.label(jobs)}</text>
+            </g>
+            }
+        })}
+        </svg>
+    </div>
+    }
+}
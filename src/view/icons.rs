@@ -0,0 +1,72 @@
+use yew::prelude::*;
+use yew::virtual_dom::AttrValue;
+use crate::core::job::JobId;
+use crate::core::resource::Resource;
+use crate::view::view::class_string;
+
+// Icons are inline SVG files from assets/icons, compiled into the app so that they can take the text color.
+// Anything without an icon is shown by its name instead.
+
+pub fn resource_icon(resource: &Resource) -> Option<&'static str> {
+    match resource {
+        Resource::Coin => Some(include_str!("../../assets/icons/coin.svg")),
+        Resource::Fragment => Some(include_str!("../../assets/icons/fragment.svg")),
+        Resource::DayDream => Some(include_str!("../../assets/icons/day-dream.svg")),
+        Resource::GlassBottle => Some(include_str!("../../assets/icons/glass-bottle.svg")),
+        Resource::Dream => Some(include_str!("../../assets/icons/dream.svg")),
+        Resource::SoothingMemory => Some(include_str!("../../assets/icons/soothing-memory.svg")),
+        Resource::ComfortDream => Some(include_str!("../../assets/icons/comfort-dream.svg")),
+        Resource::ScaryFragment => Some(include_str!("../../assets/icons/scary-fragment.svg")),
+        Resource::Nightmare => Some(include_str!("../../assets/icons/nightmare.svg")),
+        Resource::Custom(_) => None,
+    }
+}
+
+pub fn job_icon(job_id: JobId) -> Option<&'static str> {
+    match job_id {
+        JobId::FragmentCatch => Some(include_str!("../../assets/icons/catch.svg")),
+        JobId::DayDreamCraft => Some(include_str!("../../assets/icons/craft.svg")),
+        JobId::DayDreamSell => Some(include_str!("../../assets/icons/sell.svg")),
+        JobId::BottleBuy => Some(include_str!("../../assets/icons/buy.svg")),
+        JobId::DreamCraft => Some(include_str!("../../assets/icons/craft.svg")),
+        JobId::DreamSell => Some(include_str!("../../assets/icons/sell.svg")),
+        JobId::DreamUse => Some(include_str!("../../assets/icons/sleep.svg")),
+        JobId::ComfortDreamCraft => Some(include_str!("../../assets/icons/craft.svg")),
+        JobId::ComfortDreamSell => Some(include_str!("../../assets/icons/sell.svg")),
+        JobId::ComfortDreamUse => Some(include_str!("../../assets/icons/sleep.svg")),
+        JobId::NightmareCraft => Some(include_str!("../../assets/icons/craft.svg")),
+        JobId::NightmareSell => Some(include_str!("../../assets/icons/sell.svg")),
+        JobId::BottleRepair => Some(include_str!("../../assets/icons/repair.svg")),
+        JobId::NetUpgrade => Some(include_str!("../../assets/icons/upgrade.svg")),
+        JobId::BottleRackUpgrade => Some(include_str!("../../assets/icons/upgrade.svg")),
+        JobId::Retire => Some(include_str!("../../assets/icons/retire.svg")),
+        JobId::StartingResources | JobId::Custom(_) => None,
+    }
+}
+
+// The icon with its name as alt text, or just the name if there is no icon
+fn icon(svg: Option<&'static str>, name: String, class: &'static str) -> Html {
+    match svg {
+        Some(svg) => html! {
+        <span class={class_string(class)} role="img" aria-label={name.clone()} title={name}>
+            { Html::from_html_unchecked(AttrValue::Static(svg)) }
+        </span>
+        },
+        None => html! {
+        <span>{name}</span>
+        },
+    }
+}
+
+pub fn resource_icon_view(resource: &Resource) -> Html {
+    icon(resource_icon(resource), resource.to_string(), "inline-block w-5 h-5 align-middle")
+}
+
+pub fn job_icon_view(job_id: JobId, name: &'static str) -> Html {
+    icon(job_icon(job_id), name.to_string(), "inline-block w-5 h-5 align-middle")
+}
+
+// Bigger version for the displayed job
+pub fn large_job_icon_view(job_id: JobId, name: &'static str) -> Html {
+    icon(job_icon(job_id), name.to_string(), "inline-block w-8 h-8 align-middle")
+}
@@ -0,0 +1,81 @@
+use gloo::storage::{LocalStorage, Storage};
+use yew::prelude::*;
+use crate::core::locale::{text, Text};
+use crate::core::story::{journal_entry, JournalEntryId};
+use crate::view::app::{App, AppMessage};
+
+const JOURNAL_KEY: &str = "strange-facility-journal";
+
+pub fn load_journal() -> Vec<JournalEntryId> {
+    LocalStorage::get::<Vec<JournalEntryId>>(JOURNAL_KEY).unwrap_or_default()
+}
+
+pub fn save_journal(journal: &Vec<JournalEntryId>) {
+    if let Err(error) = LocalStorage::set(JOURNAL_KEY, journal) {
+        log::warn!("Couldn't save the journal: {}", error);
+    }
+}
+
+impl App {
+    // Add what the run has unlocked to the journal. Entries stay unlocked even if the jobs that unlocked them are undone.
+    pub fn record_journal(&mut self) {
+        let mut changed = false;
+        for entry in self.view_cache.journal_unlocks.iter() {
+            if !self.state.journal.contains(entry) {
+                self.state.journal.push(entry.clone());
+                self.state.new_journal_entries.push(entry.clone());
+                changed = true;
+            }
+        }
+        if changed {
+            save_journal(&self.state.journal);
+        }
+    }
+}
+
+fn entry_view(id: &JournalEntryId) -> Html {
+    match journal_entry(id) {
+        Some(entry) => html! {
+        <div class="flex flex-col">
+            <div class="font-bold">{entry.title}</div>
+            <div class="italic">{entry.text}</div>
+        </div>
+        },
+        // The entry was removed from the story since it was unlocked
+        None => html! { <></> },
+    }
+}
+
+// Story text for what the latest change unlocked
+pub fn new_entries_view(app: &App, ctx: &Context<App>) -> Html {
+    if app.state.new_journal_entries.is_empty() {
+        return html! { <></> };
+    }
+    html! {
+    <div class="flex flex-col gap-y-2 p-2 border-2 border-amber-600 bg-amber-50 my-2">
+    { for app.state.new_journal_entries.iter().map(entry_view) }
+        <button class="border border-slate-900 p-1 rounded-md self-start" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::DismissJournalEntries())}>
+            {text(Text::Continue)}
+        </button>
+    </div>
+    }
+}
+
+pub fn journal_view(app: &App, ctx: &Context<App>) -> Html {
+    if !app.state.show_journal {
+        return html! { <></> };
+    }
+    html! {
+    <div class="fixed top-4 left-4 max-w-lg max-h-screen overflow-y-auto p-2 border-2 border-slate-900 bg-amber-50 flex flex-col gap-y-2">
+        <div class="text-xl">{text(Text::Journal)}</div>
+    { if app.state.journal.is_empty() {
+        html! { <div>{text(Text::NothingWritten)}</div> }
+    } else {
+        html! { for app.state.journal.iter().map(entry_view) }
+    }}
+        <button class="border border-slate-900 p-1 rounded-md" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::ToggleJournal())}>
+            {text(Text::Close)}
+        </button>
+    </div>
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use gloo::events::{EventListener, EventListenerOptions};
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, KeyboardEvent};
+use yew::prelude::*;
+use crate::core::locale::{fill, text, Text};
+use crate::view::app::{App, AppMessage};
+
+const BINDINGS_KEY: &str = "strange-facility-key-bindings";
+// Number keys pick the first ten discovered jobs
+const JOB_KEYS: usize = 10;
+
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+pub enum KeyAction {
+    // Add the discovered job at this position
+    Job(usize),
+    Undo,
+    Redo,
+    CursorUp,
+    CursorDown,
+    AddOne,
+    RemoveOne,
+    RemoveCluster,
+    ToggleShortcuts,
+}
+
+pub type KeyBindings = BTreeMap<KeyAction, String>;
+
+impl Display for KeyAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyAction::Job(index) => f.write_str(&fill(Text::KeyJob, &[&(index + 1)])),
+            KeyAction::Undo => f.write_str(text(Text::Undo)),
+            KeyAction::Redo => f.write_str(text(Text::Redo)),
+            KeyAction::CursorUp => f.write_str(text(Text::PreviousRow)),
+            KeyAction::CursorDown => f.write_str(text(Text::NextRow)),
+            KeyAction::AddOne => f.write_str(text(Text::AddOneToRow)),
+            KeyAction::RemoveOne => f.write_str(text(Text::RemoveOneFromRow)),
+            KeyAction::RemoveCluster => f.write_str(text(Text::RemoveRow)),
+            KeyAction::ToggleShortcuts => f.write_str(text(Text::ShowShortcuts)),
+        }
+    }
+}
+
+pub fn default_bindings() -> KeyBindings {
+    let mut bindings = BTreeMap::from([
+        (KeyAction::Undo, "Ctrl+z".to_string()),
+        (KeyAction::Redo, "Ctrl+y".to_string()),
+        (KeyAction::CursorUp, "ArrowUp".to_string()),
+        (KeyAction::CursorDown, "ArrowDown".to_string()),
+        (KeyAction::AddOne, "+".to_string()),
+        (KeyAction::RemoveOne, "-".to_string()),
+        (KeyAction::RemoveCluster, "Delete".to_string()),
+        (KeyAction::ToggleShortcuts, "?".to_string()),
+    ]);
+    for index in 0..JOB_KEYS {
+        bindings.insert(KeyAction::Job(index), ((index + 1) % 10).to_string());
+    }
+    bindings
+}
+
+pub fn load_bindings() -> KeyBindings {
+    LocalStorage::get::<KeyBindings>(BINDINGS_KEY).unwrap_or_else(|_| default_bindings())
+}
+
+pub fn save_bindings(bindings: &KeyBindings) {
+    if let Err(error) = LocalStorage::set(BINDINGS_KEY, bindings) {
+        log::warn!("Couldn't save the key bindings: {}", error);
+    }
+}
+
+// Bindings are saved the same whatever the language, and only shown in it
+const CTRL_PREFIX: &str = "Ctrl+";
+
+// Describe a key press the same way bindings are written, such as "Ctrl+z" or "ArrowUp"
+pub fn key_combination(event: &KeyboardEvent) -> String {
+    let key = event.key();
+    let key = if key.chars().count() == 1 { key.to_lowercase() } else { key };
+    if event.ctrl_key() || event.meta_key() {
+        format!("{}{}", CTRL_PREFIX, key)
+    } else {
+        key
+    }
+}
+
+fn combination_label(combination: &str) -> String {
+    match combination.strip_prefix(CTRL_PREFIX) {
+        Some(key) => fill(Text::CtrlKey, &[&key]),
+        None => combination.to_string(),
+    }
+}
+
+pub fn bound_action(bindings: &KeyBindings, combination: &str) -> Option<KeyAction> {
+    bindings.iter()
+        .find(|(_action, bound)| bound.as_str() == combination)
+        .map(|(action, _bound)| *action)
+}
+
+// Listen to key presses on the whole page, except while typing in a text field
+pub fn keyboard_listener(ctx: &Context<App>) -> EventListener {
+    let link = ctx.link().clone();
+    let options = EventListenerOptions::enable_prevent_default();
+    EventListener::new_with_options(&gloo::utils::window(), "keydown", options, move |event| {
+        let event = match event.dyn_ref::<KeyboardEvent>() {
+            Some(event) => event,
+            None => return,
+        };
+        let typing = event.target()
+            .and_then(|target| target.dyn_into::<Element>().ok())
+            .map(|element| matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT"))
+            .unwrap_or(false);
+        if typing {
+            return;
+        }
+        let combination = key_combination(event);
+        // Modifier keys on their own are never a binding
+        if matches!(event.key().as_str(), "Control" | "Shift" | "Alt" | "Meta") {
+            return;
+        }
+        if combination.starts_with("Arrow") {
+            event.prevent_default();
+        }
+        link.send_message(AppMessage::KeyPressed(combination));
+    })
+}
+
+impl App {
+    // Handle what only concerns the keyboard state, and hand back the message to send for everything else.
+    // The first value is true if anything changed that needs to be redrawn.
+    pub fn handle_key(&mut self, combination: String) -> (bool, Option<AppMessage>) {
+        if let Some(action) = self.state.rebinding.take() {
+            self.state.key_bindings.retain(|_action, bound| *bound != combination);
+            self.state.key_bindings.insert(action, combination);
+            save_bindings(&self.state.key_bindings);
+            return (true, None);
+        }
+        let action = match bound_action(&self.state.key_bindings, &combination) {
+            Some(action) => action,
+            None => return (false, None),
+        };
+        // Job index of the row under the cursor, leaving out the starting resources
+        let cursor_index = self.state.row_cursor
+            .and_then(|row| self.view_cache.job_rows.get(row))
+            .map(|job_row| job_row.index)
+            .filter(|index| *index > 0);
+        let last_row = self.view_cache.job_rows.len().saturating_sub(1);
+        let message = match action {
+            KeyAction::Job(index) => self.state.discovered_jobs.get(index).cloned().map(AppMessage::AddJob),
+            KeyAction::Undo => Some(AppMessage::Undo()),
+            KeyAction::Redo => Some(AppMessage::Redo()),
+            KeyAction::CursorUp => {
+                self.state.row_cursor = Some(self.state.row_cursor.map(|row| row.saturating_sub(1)).unwrap_or(last_row));
+                return (true, None);
+            }
+            KeyAction::CursorDown => {
+                self.state.row_cursor = Some(self.state.row_cursor.map(|row| (row + 1).min(last_row)).unwrap_or(0));
+                return (true, None);
+            }
+            KeyAction::AddOne => cursor_index.map(AppMessage::AddOne),
+            KeyAction::RemoveOne => cursor_index.map(AppMessage::RemoveOne),
+            KeyAction::RemoveCluster => cursor_index.map(AppMessage::RemoveCluster),
+            KeyAction::ToggleShortcuts => {
+                self.state.show_shortcuts = !self.state.show_shortcuts;
+                return (true, None);
+            }
+        };
+        (false, message)
+    }
+}
+
+pub fn shortcuts_view(app: &App, ctx: &Context<App>) -> Html {
+    if !app.state.show_shortcuts {
+        return html! { <></> };
+    }
+    html! {
+    <div class="fixed top-4 right-4 p-2 border-2 border-slate-900 bg-blue-50 flex flex-col gap-y-1">
+        <div class="text-xl">{text(Text::KeyboardShortcuts)}</div>
+    { for app.state.key_bindings.iter().map(|(action, combination)| {
+        let action = *action;
+        let rebinding = app.state.rebinding == Some(action);
+        html! {
+        <div class="flex flex-row gap-x-2">
+            <div class="w-40">{action.to_string()}</div>
+            <button class="border border-slate-900 px-2 rounded-md w-24" onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::StartRebinding(action))}>
+                {if rebinding { text(Text::PressAKey).to_string() } else { combination_label(combination) }}
+            </button>
+        </div>
+        }
+    })}
+        <button class="border border-slate-900 p-1 rounded-md" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::ResetBindings())}>
+            {text(Text::ResetToDefaults)}
+        </button>
+    </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use super::*;
+    use crate::core::job::JobId;
+    use crate::core::resource::Resource;
+    use crate::core::scenario::facility;
+
+    #[test]
+    fn number_keys_add_the_discovered_jobs_in_order() {
+        let bindings = default_bindings();
+        let held = BTreeSet::from([Resource::Fragment, Resource::Coin]);
+        let jobs = facility().discoverable_jobs(&held);
+        let job_for_key = |key: &str| match bound_action(&bindings, key) {
+            Some(KeyAction::Job(index)) => jobs.get(index).map(|job| job.id),
+            _ => None,
+        };
+        assert_eq!(job_for_key("1"), Some(JobId::FragmentCatch));
+        assert_eq!(job_for_key("2"), Some(JobId::DayDreamCraft));
+        assert_eq!(bound_action(&bindings, "0"), Some(KeyAction::Job(9)));
+        assert_eq!(bound_action(&bindings, "Ctrl+z"), Some(KeyAction::Undo));
+    }
+}
@@ -0,0 +1,41 @@
+use gloo::storage::{LocalStorage, Storage};
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+use crate::core::locale::{all_locales, set_locale, text, Locale, Text};
+use crate::view::app::{App, AppMessage};
+
+const LOCALE_KEY: &str = "strange-facility-locale";
+
+// The saved locale, which also becomes the current one
+pub fn load_locale() -> Locale {
+    let locale = LocalStorage::get::<Locale>(LOCALE_KEY).unwrap_or(Locale::English);
+    set_locale(locale);
+    locale
+}
+
+pub fn save_locale(locale: Locale) {
+    if let Err(error) = LocalStorage::set(LOCALE_KEY, locale) {
+        log::warn!("Couldn't save the language: {}", error);
+    }
+}
+
+pub fn locale_select(app: &App, ctx: &Context<App>) -> Html {
+    html! {
+    <select
+        class="border border-slate-900 p-2 rounded-md"
+        title={text(Text::Language)}
+        onchange={ctx.link().batch_callback(|event: Event| {
+            let select: HtmlSelectElement = event.target_unchecked_into();
+            let index = select.selected_index();
+            usize::try_from(index).ok()
+                .and_then(|index| all_locales().get(index).cloned())
+                .map(AppMessage::SetLocale)
+        })}>
+    { for all_locales().into_iter().map(|locale| {
+        html! {
+        <option selected={locale == app.state.locale}>{locale.to_string()}</option>
+        }
+    })}
+    </select>
+    }
+}
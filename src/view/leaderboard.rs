@@ -0,0 +1,90 @@
+use gloo::net::http::Request;
+use gloo::storage::{LocalStorage, Storage};
+use crate::core::leaderboard::{HttpBackend, HttpClient, HttpMethod, HttpRequest, HttpResponse, Leaderboard, ScoreBackend, ScoreFuture};
+use crate::core::random::new_seed;
+use crate::core::record::RunRecord;
+
+const LOCAL_RUNS_KEY: &str = "strange-facility-leaderboard";
+const ENDPOINT_KEY: &str = "strange-facility-leaderboard-endpoint";
+
+// Keeps the best runs of this browser in localStorage
+pub struct LocalBackend;
+
+// Sends the requests of an `HttpBackend` with the browser's fetch
+pub struct FetchClient;
+
+impl ScoreBackend for LocalBackend {
+    // Only this browser reads the runs back, so any seed will do
+    fn issue_seed(&self, _scenario: &str) -> ScoreFuture<u64> {
+        let seed = new_seed(js_sys::Math::random());
+        Box::pin(async move {
+            Ok(seed)
+        })
+    }
+    fn submit(&self, run: RunRecord) -> ScoreFuture<()> {
+        let result = save_local_run(run);
+        Box::pin(async move {
+            result
+        })
+    }
+    fn best_runs(&self, scenario: &str) -> ScoreFuture<Vec<RunRecord>> {
+        let runs = local_runs(scenario);
+        Box::pin(async move {
+            Ok(runs)
+        })
+    }
+}
+
+impl HttpClient for FetchClient {
+    fn send(&self, request: HttpRequest) -> ScoreFuture<HttpResponse> {
+        Box::pin(async move {
+            let query = request.query.iter().map(|(key, value)| (key.as_str(), value.as_str()));
+            let builder = match request.method {
+                HttpMethod::Get => Request::get(&request.url),
+                HttpMethod::Post => Request::post(&request.url),
+            }.query(query);
+            let sent = match request.body {
+                Some(body) => builder.header("Content-Type", "application/json").body(body).map_err(|error| error.to_string())?.send().await,
+                None => builder.send().await,
+            };
+            let response = sent.map_err(|error| error.to_string())?;
+            Ok(HttpResponse {
+                status: response.status(),
+                body: response.text().await.unwrap_or_default(),
+            })
+        })
+    }
+}
+
+// The runs kept in this browser for a scenario, fewest days first
+pub fn local_runs(scenario: &str) -> Vec<RunRecord> {
+    LocalStorage::get::<Leaderboard>(LOCAL_RUNS_KEY).unwrap_or_default().best_runs(scenario)
+}
+
+pub fn save_local_run(run: RunRecord) -> Result<(), String> {
+    let mut leaderboard = LocalStorage::get::<Leaderboard>(LOCAL_RUNS_KEY).unwrap_or_default();
+    leaderboard.add_run(run);
+    LocalStorage::set(LOCAL_RUNS_KEY, leaderboard).map_err(|error| error.to_string())
+}
+
+pub fn load_endpoint() -> String {
+    LocalStorage::get::<String>(ENDPOINT_KEY).unwrap_or_default()
+}
+
+pub fn save_endpoint(endpoint: &str) {
+    if let Err(error) = LocalStorage::set(ENDPOINT_KEY, endpoint) {
+        log::warn!("Couldn't save the leaderboard endpoint: {}", error);
+    }
+}
+
+// Use the server if one is configured, and this browser's storage otherwise
+pub fn backend(endpoint: &str) -> Box<dyn ScoreBackend> {
+    if endpoint.trim().is_empty() {
+        Box::new(LocalBackend)
+    } else {
+        Box::new(HttpBackend {
+            endpoint: endpoint.trim().to_string(),
+            client: FetchClient,
+        })
+    }
+}
@@ -1,3 +1,17 @@
 pub mod app;
+pub mod chart;
+pub mod daily;
+pub mod editor;
+pub mod generator;
+pub mod graph;
+pub mod icons;
+pub mod journal;
+pub mod keyboard;
+pub mod leaderboard;
+pub mod language;
+pub mod number_format;
+pub mod packs;
+pub mod summary;
+#[allow(clippy::module_inception)]
 pub mod view;
 pub mod view_logic;
\ No newline at end of file
@@ -0,0 +1,42 @@
+use gloo::storage::{LocalStorage, Storage};
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+use crate::core::locale::{text, Text};
+use crate::core::quantity::{all_number_formats, set_number_format, NumberFormat};
+use crate::view::app::{App, AppMessage};
+
+const NUMBER_FORMAT_KEY: &str = "strange-facility-number-format";
+
+// The saved number format, which also becomes the current one
+pub fn load_number_format() -> NumberFormat {
+    let format = LocalStorage::get::<NumberFormat>(NUMBER_FORMAT_KEY).unwrap_or(NumberFormat::Compact);
+    set_number_format(format);
+    format
+}
+
+pub fn save_number_format(format: NumberFormat) {
+    if let Err(error) = LocalStorage::set(NUMBER_FORMAT_KEY, format) {
+        log::warn!("Couldn't save the number format: {}", error);
+    }
+}
+
+pub fn number_format_select(app: &App, ctx: &Context<App>) -> Html {
+    html! {
+    <select
+        class="border border-slate-900 p-2 rounded-md"
+        title={text(Text::NumberFormat)}
+        onchange={ctx.link().batch_callback(|event: Event| {
+            let select: HtmlSelectElement = event.target_unchecked_into();
+            let index = select.selected_index();
+            usize::try_from(index).ok()
+                .and_then(|index| all_number_formats().get(index).cloned())
+                .map(AppMessage::SetNumberFormat)
+        })}>
+    { for all_number_formats().into_iter().map(|format| {
+        html! {
+        <option selected={format == app.state.number_format}>{format.to_string()}</option>
+        }
+    })}
+    </select>
+    }
+}
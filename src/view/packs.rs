@@ -0,0 +1,148 @@
+use gloo::file::callbacks::read_as_text;
+use gloo::net::http::Request;
+use gloo::storage::{LocalStorage, Storage};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use crate::core::locale::{fill, text, Text};
+use crate::core::pack::{apply_packs, pack_from_json, ContentPack};
+use crate::core::random::new_seed;
+use crate::core::scenario::{facility, Scenario};
+use crate::view::app::{App, AppMessage};
+
+const PACKS_KEY: &str = "strange-facility-packs";
+
+// The whole packs are kept, since a pack picked from a file can't be loaded again without the player
+pub fn load_packs() -> Vec<ContentPack> {
+    LocalStorage::get::<Vec<ContentPack>>(PACKS_KEY).unwrap_or_default()
+}
+
+fn save_packs(packs: &Vec<ContentPack>) {
+    if let Err(error) = LocalStorage::set(PACKS_KEY, packs) {
+        log::warn!("Couldn't save the content packs: {}", error);
+    }
+}
+
+// The facility with the packs added, or the plain facility and what was wrong if they don't fit together
+pub fn facility_with_packs(packs: &[ContentPack]) -> (Scenario, Vec<String>) {
+    match apply_packs(&facility(), packs) {
+        Ok(scenario) => (scenario, vec![]),
+        Err(problems) => (facility(), problems),
+    }
+}
+
+async fn fetch_pack(url: String) -> Result<String, String> {
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|error| error.to_string())?;
+    if !response.ok() {
+        return Err(fill(Text::PackFetchFailed, &[&url, &response.status()]));
+    }
+    response.text().await.map_err(|error| error.to_string())
+}
+
+impl App {
+    // Packs change which jobs there are, so the facility starts over with the new set
+    fn set_packs(&mut self, packs: Vec<ContentPack>) {
+        match apply_packs(&facility(), &packs) {
+            Ok(scenario) => {
+                self.state.pack_errors.clear();
+                save_packs(&packs);
+                self.state.active_packs = packs;
+                self.start_scenario(scenario, new_seed(js_sys::Math::random()));
+            }
+            Err(problems) => self.state.pack_errors = problems,
+        }
+    }
+
+    pub fn read_pack_file(&mut self, file: web_sys::File, ctx: &Context<App>) {
+        let link = ctx.link().clone();
+        let reader = read_as_text(&gloo::file::File::from(file), move |result| {
+            link.send_message(AppMessage::PackRead(result.map_err(|error| error.to_string())));
+        });
+        self.file_reader = Some(reader);
+    }
+
+    pub fn fetch_pack(&mut self, ctx: &Context<App>) {
+        let url = self.state.pack_url.trim().to_string();
+        ctx.link().send_future(async move { AppMessage::PackRead(fetch_pack(url).await) });
+    }
+
+    pub fn add_pack(&mut self, result: Result<String, String>) {
+        self.file_reader = None;
+        match result.and_then(|json| pack_from_json(&json)) {
+            Ok(pack) => {
+                let mut packs = self.state.active_packs.clone();
+                packs.push(pack);
+                self.set_packs(packs);
+            }
+            Err(error) => self.state.pack_errors = vec![error],
+        }
+    }
+
+    pub fn remove_pack(&mut self, index: usize) {
+        let mut packs = self.state.active_packs.clone();
+        if index < packs.len() {
+            packs.remove(index);
+            self.set_packs(packs);
+        }
+    }
+}
+
+pub fn packs_view(app: &App, ctx: &Context<App>) -> Html {
+    html! {
+    <div class="flex flex-col gap-2 p-2 border-2 border-slate-400 bg-white">
+        <div class="text-sm italic">{text(Text::PacksRestart)}</div>
+        { if app.state.active_packs.is_empty() {
+            html! { <div>{text(Text::NoPacks)}</div> }
+        } else {
+            html! {
+            <ul class="flex flex-col gap-1">
+            { for app.state.active_packs.iter().enumerate().map(|(index, pack)| html! {
+                <li class="flex flex-row gap-2 items-center">
+                    <span>{fill(Text::PackEntry, &[&pack.name, &pack.version])}</span>
+                    <span class="text-slate-600 text-sm">{&pack.id}</span>
+                    <button class="border border-slate-900 p-1 rounded-md" onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::RemovePack(index))}>
+                        {text(Text::Remove)}
+                    </button>
+                </li>
+            })}
+            </ul>
+            }
+        }}
+        <div class="flex flex-row flex-wrap gap-2 items-center">
+            <label class="border border-slate-900 p-2 rounded-md cursor-pointer">
+                {text(Text::LoadPackFile)}
+                <input type="file" accept=".json,application/json" class="hidden"
+                    onchange={ctx.link().batch_callback(|event: Event| {
+                        let input: HtmlInputElement = event.target_unchecked_into();
+                        input.files().and_then(|files| files.get(0)).map(AppMessage::PackFileChosen)
+                    })}
+                />
+            </label>
+            <input class="border border-slate-900 p-2 rounded-md w-80"
+                placeholder={text(Text::PackUrl)}
+                value={app.state.pack_url.clone()}
+                oninput={ctx.link().callback(|event: InputEvent| {
+                    let input: HtmlInputElement = event.target_unchecked_into();
+                    AppMessage::PackUrlInput(input.value())
+                })}
+            />
+            <button class="border border-slate-900 p-2 rounded-md background-slate-100"
+                disabled={app.state.pack_url.trim().is_empty()}
+                onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::LoadPackUrl())}>
+                {text(Text::LoadPackUrl)}
+            </button>
+        </div>
+        { if app.state.pack_errors.is_empty() {
+            html! { <></> }
+        } else {
+            html! {
+            <ul class="list-disc pl-4 text-red-700">
+                { for app.state.pack_errors.iter().map(|error| html! { <li>{error}</li> }) }
+            </ul>
+            }
+        }}
+    </div>
+    }
+}
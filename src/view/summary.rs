@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+use gloo::storage::{LocalStorage, Storage};
+use yew::prelude::*;
+use web_sys::HtmlInputElement;
+use crate::core::daily::daily_date;
+use crate::core::editor::is_playtest;
+use crate::core::catalogue::SCENARIO_NAME;
+use crate::core::job::JobId;
+use crate::core::locale::{days, fill, job_name, resource_name, text, Text};
+use crate::core::record::{JobRecord, RunRecord};
+use crate::core::quantity::{add, format_quantity, subtract, Quantity};
+use crate::core::resource::{Resource, ResourceSet};
+use crate::core::scenario::Scenario;
+use crate::core::solver::{solve, DEFAULT_MAX_STATES};
+use crate::view::leaderboard::save_local_run;
+use crate::view::app::{App, AppMessage, State};
+use crate::view::daily::{load_daily_results, save_daily_result};
+use crate::view::view_logic::{GameState, HistoryStep, ViewCache};
+
+const PERSONAL_BEST_KEY: &str = "strange-facility-personal-best";
+const SOLVER_PLANS_KEY: &str = "strange-facility-solver-plans";
+// Plans are kept for the scenarios played last, since every daily challenge is a new one
+const MAX_SOLVER_PLANS: usize = 8;
+
+pub struct RunSummary {
+    pub spent_days: usize,
+    // Job instances that were executed, successful or not
+    pub actions: usize,
+    pub edits: usize,
+    pub undos: usize,
+    pub jobs_executed: BTreeMap<JobId, usize>,
+    pub peak_resources: BTreeMap<Resource, Quantity>,
+    // Everything that was gained over the run, ignoring what was spent
+    pub total_gained: BTreeMap<Resource, Quantity>,
+    pub failed_attempts: usize,
+}
+
+// A new version of a scenario can play out differently, so each version keeps its own best
+fn personal_best_key(scenario: &Scenario) -> String {
+    format!("{}-{}@{}", PERSONAL_BEST_KEY, scenario.name, scenario.version)
+}
+
+// Daily challenges keep their results apart from normal play.
+// Generated, edited and modded scenarios are rarely played the same way twice, so only the facility keeps a best.
+// Playtests are named apart from the facility, see `core::editor::playtest_name`, so a copy of it doesn't keep one either.
+pub fn load_personal_best(scenario: &Scenario) -> Option<usize> {
+    match daily_date(&scenario.name) {
+        Some(date) => load_daily_results().get(date).cloned(),
+        None if scenario.name == SCENARIO_NAME => LocalStorage::get::<usize>(personal_best_key(scenario)).ok(),
+        None => None,
+    }
+}
+
+fn save_personal_best(scenario: &Scenario, spent_days: usize) {
+    match daily_date(&scenario.name) {
+        Some(date) => save_daily_result(date, spent_days),
+        None if scenario.name == SCENARIO_NAME => {
+            if let Err(error) = LocalStorage::set(personal_best_key(scenario), spent_days) {
+                log::warn!("Couldn't save the personal best: {}", error);
+            }
+        }
+        None => {}
+    }
+}
+
+// The solver's plan for the scenario. Searching can take seconds, so the plan is kept for the scenario's name, version and packs,
+// which only change along with its jobs. Playtests change without a new version, so their plans aren't kept.
+pub fn solver_plan(scenario: &Scenario) -> Result<Option<Vec<JobRecord>>, String> {
+    if is_playtest(&scenario.name) {
+        return solve(scenario, DEFAULT_MAX_STATES);
+    }
+    let key = format!("{}@{}{}", scenario.name, scenario.version, scenario.packs.iter().map(|pack| format!("+{}", pack)).collect::<String>());
+    let mut plans = LocalStorage::get::<Vec<(String, Option<Vec<JobRecord>>)>>(SOLVER_PLANS_KEY).unwrap_or_default();
+    if let Some((_key, plan)) = plans.iter().find(|(known, _plan)| *known == key) {
+        return Ok(plan.clone());
+    }
+    let plan = solve(scenario, DEFAULT_MAX_STATES)?;
+    plans.push((key, plan.clone()));
+    if plans.len() > MAX_SOLVER_PLANS {
+        plans.remove(0);
+    }
+    if let Err(error) = LocalStorage::set(SOLVER_PLANS_KEY, plans) {
+        log::warn!("Couldn't save the solver's plan: {}", error);
+    }
+    Ok(plan)
+}
+
+// The fewest days the solver can win in with the jobs of the scenario
+pub fn solver_par(scenario: &Scenario) -> Option<usize> {
+    solver_plan(scenario).ok().flatten().map(|plan| plan.len())
+}
+
+pub fn create_run_summary(state: &State, view_cache: &ViewCache) -> Option<RunSummary> {
+    let spent_days = match view_cache.game_state {
+        GameState::Won { spent_days } => spent_days,
+        GameState::Playing => return None,
+    };
+    let edits = state.history.iter()
+        .filter(|step| match step {
+            HistoryStep::AddOne(_) |
+            HistoryStep::RemoveOne(_) |
+            HistoryStep::RemoveCluster(_) |
+            HistoryStep::CycleAlternative(_) => true,
+            HistoryStep::Job(_) |
+            HistoryStep::Jobs(_) |
+            HistoryStep::Repeat(_, _) => false,
+        })
+        .count();
+
+    let mut actions = 0;
+    let mut failed_attempts = 0;
+    let mut jobs_executed = BTreeMap::new();
+    // The first row is the starting resources, which isn't something the player did
+    for job_row in view_cache.job_rows.iter().skip(1) {
+        actions += job_row.instances;
+        if job_row.output.is_ok() {
+            *jobs_executed.entry(job_row.job.id).or_insert(0) += job_row.instances;
+        } else {
+            failed_attempts += job_row.instances;
+        }
+    }
+
+    let mut peak_resources = BTreeMap::new();
+    let mut total_gained = BTreeMap::new();
+    let mut previous = ResourceSet::new();
+    for snapshot in view_cache.snapshots.iter() {
+        for (resource, amount) in snapshot.resources.iter() {
+            let peak = peak_resources.entry(*resource).or_default();
+            if *amount > *peak {
+                *peak = amount.clone();
+            }
+            let before = previous.get(resource).cloned().unwrap_or_default();
+            if *amount > before {
+                let gained = total_gained.entry(*resource).or_default();
+                *gained = add(gained, &subtract(amount, &before));
+            }
+        }
+        previous = snapshot.resources.clone();
+    }
+
+    Some(RunSummary {
+        spent_days,
+        actions,
+        edits,
+        undos: state.undo_count,
+        jobs_executed,
+        peak_resources,
+        total_gained,
+        failed_attempts,
+    })
+}
+
+// Generated and modded jobs aren't in the catalogue, so names come from the scenario the run was played in
+fn executed_job_name(scenario: &Scenario, job_id: JobId) -> String {
+    scenario.job(job_id)
+        .map(|job| job_name(&job).to_string())
+        .unwrap_or_else(|| format!("{:?}", job_id))
+}
+
+impl RunSummary {
+    pub fn to_text(&self, scenario: &Scenario, personal_best: Option<usize>, par: Option<usize>) -> String {
+        let mut lines = vec![
+            fill(Text::DaysSpent, &[&self.spent_days]),
+            fill(Text::Actions, &[&self.actions]),
+            fill(Text::Edits, &[&self.edits]),
+            fill(Text::Undos, &[&self.undos]),
+            fill(Text::FailedAttempts, &[&self.failed_attempts]),
+        ];
+        if let Some(personal_best) = personal_best {
+            lines.push(fill(Text::PersonalBest, &[&days(personal_best)]));
+        }
+        if let Some(par) = par {
+            lines.push(fill(Text::Par, &[&days(par)]));
+        }
+        for (job_id, count) in self.jobs_executed.iter() {
+            lines.push(format!("{}: {}x", executed_job_name(scenario, *job_id), count));
+        }
+        for (resource, peak) in self.peak_resources.iter() {
+            let gained = self.total_gained.get(resource).cloned().unwrap_or_default();
+            lines.push(fill(Text::PeakAndGained, &[&resource_name(resource, 2), &format_quantity(peak), &format_quantity(&gained)]));
+        }
+        lines.join("\n")
+    }
+}
+
+impl App {
+    // The current run, ready to be submitted to a leaderboard. None until the game is won, and for playtests,
+    // since their drafts keep changing under the same name.
+    pub fn create_run_record(&self) -> Option<RunRecord> {
+        match self.view_cache.game_state {
+            GameState::Won { spent_days } if !is_playtest(&self.state.scenario.name) => Some(RunRecord {
+                scenario: self.state.scenario.name.clone(),
+                scenario_version: self.state.scenario.version,
+                player: self.state.player_name.trim().to_string(),
+                days: spent_days,
+                history: self.state.history.iter().map(|step| step.to_record()).collect(),
+                seed: self.state.seed,
+                packs: self.state.scenario.packs.clone(),
+            }),
+            GameState::Won { .. } | GameState::Playing => None,
+        }
+    }
+
+    // Remember the result of a won run, and look up the par the first time it's needed
+    pub fn record_win(&mut self) {
+        if let GameState::Won { spent_days } = self.view_cache.game_state {
+            if !self.state.par_searched {
+                self.state.par_days = solver_par(&self.state.scenario);
+                self.state.par_searched = true;
+            }
+            let stored_best = load_personal_best(&self.state.scenario);
+            if stored_best.map(|best| spent_days < best).unwrap_or(true) {
+                save_personal_best(&self.state.scenario, spent_days);
+                // Keep the whole run too, so that it can be raced as a ghost
+                if let Some(run) = self.create_run_record() {
+                    if let Err(error) = save_local_run(run) {
+                        log::warn!("Couldn't save the run: {}", error);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn summary_view(app: &App, ctx: &Context<App>) -> Html {
+    let summary = match create_run_summary(&app.state, &app.view_cache) {
+        Some(summary) => summary,
+        None => return html! { <></> },
+    };
+    let personal_best = app.state.personal_best;
+    let comparison = match personal_best {
+        Some(best) if summary.spent_days < best => fill(Text::NewPersonalBest, &[&days(best - summary.spent_days)]),
+        Some(best) if summary.spent_days == best => text(Text::MatchedPersonalBest).to_string(),
+        Some(best) => fill(Text::BehindPersonalBest, &[&days(summary.spent_days - best)]),
+        None => text(Text::FirstEscape).to_string(),
+    };
+    let export = format!("data:text/plain;charset=utf-8,{}", js_sys::encode_uri_component(&summary.to_text(&app.state.scenario, personal_best, app.state.par_days)));
+    html! {
+    <div class="p-2 border-2 border-green-600 my-2 flex flex-col gap-y-1">
+        <div class="text-xl">{fill(Text::EscapedIn, &[&days(summary.spent_days)])}</div>
+        <div>{comparison}</div>
+    { if let Some(par) = app.state.par_days {
+        html! { <div>{fill(Text::ParIs, &[&days(par)])}</div> }
+    } else {
+        html! { <></> }
+    }}
+        <div>{fill(Text::RunStatistics, &[&summary.actions, &summary.edits, &summary.undos, &summary.failed_attempts])}</div>
+        <div class="flex flex-row flex-wrap gap-2">
+        { for summary.jobs_executed.iter().map(|(job_id, count)| {
+            html! {
+            <div class="px-2 border border-slate-900">
+                {format!("{}: {}x", executed_job_name(&app.state.scenario, *job_id), count)}
+            </div>
+            }
+        })}
+        </div>
+        <div class="flex flex-row flex-wrap gap-2">
+        { for summary.peak_resources.iter().map(|(resource, peak)| {
+            html! {
+            <div class="px-2 border border-slate-900">
+                {fill(Text::PeakAndGained, &[&resource_name(resource, 2), &format_quantity(peak), &format_quantity(&summary.total_gained.get(resource).cloned().unwrap_or_default())])}
+            </div>
+            }
+        })}
+        </div>
+        <a class="border border-slate-900 p-2 rounded-md w-fit" href={export} download="strange-facility-run.txt">
+            {text(Text::ExportSummary)}
+        </a>
+    { if is_playtest(&app.state.scenario.name) {
+        html! { <></> }
+    } else {
+        leaderboard_view(app, ctx)
+    }}
+    </div>
+    }
+}
+
+fn leaderboard_view(app: &App, ctx: &Context<App>) -> Html {
+    html! {
+    <div class="flex flex-col gap-y-1 mt-2">
+        <div class="flex flex-row flex-wrap gap-2">
+            <input
+                class="border border-slate-900 p-2"
+                placeholder={text(Text::YourName)}
+                value={app.state.player_name.clone()}
+                oninput={ctx.link().callback(|event: InputEvent| {
+                    let input: HtmlInputElement = event.target_unchecked_into();
+                    AppMessage::PlayerNameInput(input.value())
+                })}
+            />
+            <input
+                class="border border-slate-900 p-2"
+                placeholder={text(Text::LeaderboardServer)}
+                value={app.state.leaderboard_endpoint.clone()}
+                onchange={ctx.link().callback(|event: Event| {
+                    let input: HtmlInputElement = event.target_unchecked_into();
+                    AppMessage::EndpointInput(input.value())
+                })}
+            />
+            <button class="border border-slate-900 p-2 rounded-md" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::SubmitRun())}>
+                {text(Text::SubmitToLeaderboard)}
+            </button>
+        </div>
+    { if let Some(error) = &app.state.leaderboard_error {
+        html! { <div class="p-2 border-2 border-red-600"> {error} </div> }
+    } else {
+        html! { <></> }
+    }}
+    { for app.state.leaderboard.iter().enumerate().map(|(place, run)| {
+        html! {
+        <div>
+            {fill(Text::LeaderboardEntry, &[&(place + 1), &if run.player.is_empty() { text(Text::Anonymous) } else { run.player.as_str() }, &days(run.days)])}
+        </div>
+        }
+    })}
+    </div>
+    }
+}
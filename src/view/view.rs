@@ -1,11 +1,22 @@
 use yew::prelude::*;
-use crate::app::*;
-use crate::jobs::*;
+use crate::view::app::*;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+use crate::core::ghost::GhostStatus;
+use crate::core::quantity::{format_expected, format_number, format_quantity, quantity, to_count};
+use crate::core::locale::{count_of, days, effect_name, fill, job_description, job_name, resource_name, text, Text};
+use crate::view::language::locale_select;
+use crate::view::number_format::number_format_select;
+use crate::view::icons::{job_icon_view, large_job_icon_view, resource_icon_view};
+use crate::view::view_logic::{JobRow, ResourceToolStatus};
+use crate::core::amount::Amount;
+use crate::core::effect::{effect_rule, EffectId, EffectSet};
+use crate::core::job::Job;
+use crate::core::upgrade::JobModifier;
 
 pub fn class_string(text: &'static str) -> Classes{
     let mut split = text.split(" ");
     if let Some(first) = split.next() {
-        split.into_iter().fold(classes!(first), |mut class, substring| {
+        split.fold(classes!(first), |mut class, substring| {
             class.extend(classes!(substring));
             class
         })
@@ -21,43 +32,201 @@ pub fn class_merge(base: &'static str, additional: Classes) -> Classes {
     base
 }
 
+// How the run compares to the ghost at the end of the row's last day
+fn ghost_column(app: &App, job_row: &JobRow) -> Html {
+    let last_job_index = job_row.index + job_row.instances - 1;
+    let day = app.view_cache.snapshots.get(last_job_index).map(|snapshot| snapshot.day);
+    let ghost_day = day.and_then(|day| app.view_cache.ghost_days.get(day));
+    match ghost_day {
+        Some(ghost_day) => {
+            let class = match ghost_day.status {
+                GhostStatus::Even => "w-48 px-1 border border-slate-400 text-slate-500",
+                GhostStatus::Ahead => "w-48 px-1 border border-green-600 text-green-700",
+                GhostStatus::Behind => "w-48 px-1 border border-red-600 text-red-700",
+                GhostStatus::Mixed => "w-48 px-1 border border-yellow-600 text-yellow-700",
+            };
+            let differences = ghost_day.differences.iter()
+                .map(|(resource, difference)| format!("{}{} {}", if *difference > quantity(0) { "+" } else { "" }, format_quantity(difference), resource_name(resource, to_count(difference).saturating_abs())))
+                .collect::<Vec<_>>();
+            html! {
+            <div class={class} title={fill(Text::ComparedToGhost, &[&ghost_day.day])}>
+                {if differences.is_empty() { text(Text::EvenWithGhost).to_string() } else { differences.join(", ") }}
+            </div>
+            }
+        }
+        None => html! {
+            <></>
+        },
+    }
+}
+
+// Rolled amounts show what to expect rather than a single number
+fn amount_description(amount: &Amount) -> String {
+    match amount.spread() {
+        Some((expected, min, max)) => fill(Text::Expected, &[&format_expected(expected), &format_number(min), &format_number(max)]),
+        None => amount.to_string(),
+    }
+}
+
+fn modifier_description(app: &App, modifier: &JobModifier) -> String {
+    let target = app.state.scenario.job(modifier.job())
+        .map(|job| job_name(&job).to_string())
+        .unwrap_or_else(|| format!("{:?}", modifier.job()));
+    match modifier {
+        JobModifier::Amount(_job, resource, change) if *change >= 0 => fill(Text::ModifierMore, &[&target, change, &resource_name(resource, *change)]),
+        JobModifier::Amount(_job, resource, change) => fill(Text::ModifierFewer, &[&target, &change.abs(), &resource_name(resource, change.abs())]),
+        JobModifier::TimeSlots(_job, change) if *change >= 0 => fill(Text::ModifierSlower, &[&target, change]),
+        JobModifier::TimeSlots(_job, change) => fill(Text::ModifierFaster, &[&target, &change.abs()]),
+    }
+}
+
+// What an upgrade job changes, and whether this run has done it already
+fn upgrade_effects_view(app: &App, job: &Job) -> Html {
+    if !job.is_upgrade() {
+        return html! {
+            <></>
+        };
+    }
+    html! {
+    <div class="p-1 flex flex-col">
+        <div class="flex flex-row gap-2">
+            <span>{text(Text::UpgradeEffects)}</span>
+            { if app.view_cache.upgrades.is_done(job) {
+                html! { <span class="px-1 border border-green-700 text-green-700">{text(Text::UpgradeDone)}</span> }
+            } else {
+                html! { <></> }
+            }}
+        </div>
+        <ul class="list-disc pl-5">
+        { for job.modifiers.iter().map(|modifier| html! { <li>{modifier_description(app, modifier)}</li> }) }
+        </ul>
+    </div>
+    }
+}
+
+// What the effect does, for hovering over it
+fn effect_rule_description(app: &App, effect: EffectId) -> String {
+    let (job_id, resource, percent) = effect_rule(effect);
+    let target = app.state.scenario.job(job_id)
+        .map(|job| job_name(&job).to_string())
+        .unwrap_or_else(|| format!("{:?}", job_id));
+    fill(Text::EffectRule, &[&target, &percent, &resource_name(&resource, 2)])
+}
+
+// One badge per active effect, with the days it has left
+fn effect_badges_view(app: &App, effects: &EffectSet) -> Html {
+    html! {
+    <>
+    { for effects.iter().map(|(effect, remaining)| html! {
+        <div class="px-1 rounded-full border border-purple-600 text-purple-700" title={effect_rule_description(app, *effect)}>
+            {fill(Text::EffectLeft, &[&effect_name(*effect), &days(*remaining)])}
+        </div>
+    })}
+    </>
+    }
+}
+
+// What is held at the end of the plan, a row per shelf, and the effects still active
+fn resource_panel_view(app: &App) -> Html {
+    html! {
+    <div class="flex flex-col gap-y-1 p-2 border border-slate-900 my-2">
+    { for app.view_cache.current_resources.iter().map(|row| html! {
+        <div class="flex flex-row flex-wrap gap-x-2">
+        { for row.iter().map(|current| html! {
+            <div class={classes!("px-1", "border", current.classes.clone())} title={current.amount.to_string()}>
+                { resource_icon_view(&current.resource) }
+                {format!(" {}", count_of(&current.resource, &current.amount))}
+            </div>
+        })}
+        </div>
+    })}
+        <div class="flex flex-row flex-wrap gap-x-2">
+            { effect_badges_view(app, &app.view_cache.final_effects) }
+        </div>
+    </div>
+    }
+}
+
 pub fn view(app: &App, ctx: &Context<App>) -> Html {
+    if app.state.show_editor {
+        return crate::view::editor::editor_view(app, ctx);
+    }
     html! {
         <div class="flex flex-row">
+            { crate::view::keyboard::shortcuts_view(app, ctx) }
+            { crate::view::journal::journal_view(app, ctx) }
             <div class="p-2 border border-slate-800 bg-blue-100 flex-col gap-y-2">
                 <div class="md:flex md:flex-row">
                     // List available jobs
                     <div class="flex flex-row flex-wrap gap-y-2 md:w-3/5">
                     { for app.state.discovered_jobs.iter().map(|job| {
                         let callback_job = job.clone();
+                        let repeat_count = app.state.repeat_count;
+                        // Shift-click repeats the job until it fails
+                        let onclick = ctx.link().callback(move |event: MouseEvent| {
+                            if event.shift_key() {
+                                AppMessage::AddJobUntilFailure(callback_job.clone())
+                            } else if repeat_count > 1 {
+                                AppMessage::AddRepeatedJob(callback_job.clone(), repeat_count)
+                            } else {
+                                AppMessage::AddJob(callback_job.clone())
+                            }
+                        });
+                        html! {
+                        <button class="border border-slate-900 background-slate-100 p-2 rounded-md mr-1 mt-2" title={text(Text::RepeatHint)} onclick={onclick}>
+                            { job_icon_view(job.id, job_name(job)) }
+                            {" "}
+                            {job_name(job)}
+                        </button>
+                        }
+                    })}
+                    // Saved macros, each expanding into its jobs
+                    { for app.state.macros.iter().map(|job_macro| {
+                        let callback_macro = job_macro.clone();
                         html! {
-                        <button class="border border-slate-900 background-slate-100 p-2 rounded-md mr-1 mt-2" onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::AddJob(callback_job.clone()))}>
-                            {job.short_text}
+                        <button class="border border-blue-500 background-slate-100 p-2 rounded-md mr-1 mt-2" onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::AddMacro(callback_macro.clone()))}>
+                            {format!("{} ({})", job_macro.name, job_macro.jobs.len())}
                         </button>
                         }
                     })}
                     </div>
                 { if let Some(job) = &app.state.displayed_job {
+                    let upgraded_job = app.view_cache.upgrades.apply(job);
                     html! {
                     <div class="p-1 border-2 border-slate-900 mt-2 md:w-2/5">
                         <div class="flex flex-row gap-2">
                             <div class="p-1 border border-slate-900">
-                                {"Icon"}
+                                { large_job_icon_view(job.id, job_name(job)) }
                             </div>
                             <div class="py-1 text-xl">
-                                {job.short_text}
+                                {job_name(job)}
                             </div>
                         </div>
-                        <div class="p-1">
-                            {"Subtitle"}
+                        <div class="p-1 italic">
+                            {job_description(job)}
                         </div>
-                    { for job.end_deltas.iter().map(|delta_row|{
+                    { upgrade_effects_view(app, job) }
+                    { for job.effects.iter().map(|(effect, time_slots)| html! {
+                        <div class="p-1" title={effect_rule_description(app, *effect)}>
+                            {fill(Text::EffectAfterwards, &[&effect_name(*effect), &days(*time_slots)])}
+                        </div>
+                    })}
+                    { for job.end_deltas.iter().enumerate().map(|(alternative, delta_row)|{
+                        let upgraded = upgraded_job.end_deltas.get(alternative);
                         html! {
                         <div class="flex flex-row flex-wrap gap-2">
                         { for delta_row.iter().map(|(resource, amount)| {
+                            // Upgrades done so far change the amounts, so show what they were before too
+                            let upgraded_amount = upgraded.and_then(|delta| delta.iter().find(|(upgraded_resource, _amount)| upgraded_resource == resource));
+                            let description = match upgraded_amount {
+                                Some((_resource, upgraded_amount)) if upgraded_amount == amount => amount_description(amount),
+                                Some((_resource, upgraded_amount)) => fill(Text::UpgradedFrom, &[&amount_description(upgraded_amount), &amount_description(amount)]),
+                                None => fill(Text::NoLongerNeeded, &[&amount_description(amount)]),
+                            };
                             html! {
-                            <div class="p-1 px-2 border border-slate-900">
-                                {format!("{}: {}", resource, amount)}
+                            <div class="p-1 px-2 border border-slate-900" title={amount.to_string()}>
+                                { resource_icon_view(resource) }
+                                {format!(" {}: {}", resource_name(resource, 2), description)}
                             </div>
                             }
                         })}
@@ -72,14 +241,12 @@ pub fn view(app: &App, ctx: &Context<App>) -> Html {
                     }
                 }}
                 </div>
+                { crate::view::journal::new_entries_view(app, ctx) }
+                { resource_panel_view(app) }
                 // Current error
                 {
                     if app.programmer_error.is_some() {
                         html!{ <div class="flex flex-row gap-y-2 p-2 border-2 border-purple-600 my-2"> {&app.programmer_error} </div> }
-                    // } else if let CombinationResult::Text(text) = &app.state.last_combination {
-                    //     html!{ <div class="flex flex-row gap-y-2 p-2 border-2 border-blue-500 my-2"> {text} </div> }
-                    // } else if let CombinationResult::Job(_, Some(text)) = &app.state.last_combination {
-                    //     html!{ <div class="flex flex-row gap-y-2 p-2 border-2 border-blue-500 my-2"> {text} </div> }
                     } else if app.view_cache.user_error.is_some() {
                         html!{ <div class="flex flex-row gap-y-2 p-2 border-2 border-red-600 my-2"> {&app.view_cache.user_error} </div> }
                     } else {
@@ -89,8 +256,19 @@ pub fn view(app: &App, ctx: &Context<App>) -> Html {
                 <div class="flex gap-x-2">
                     // Total jobs
                     <div class="border border-slate-900 background-slate-100 p-2">
-                        {format!("Total days spent: {}", app.view_cache.total_days)}
+                        {fill(Text::TotalDaysSpent, &[&app.view_cache.total_days])}
                     </div>
+                    <input
+                        class="border border-slate-900 p-2 w-20"
+                        type="number"
+                        min="1"
+                        title={text(Text::RepeatCountHint)}
+                        value={app.state.repeat_count.to_string()}
+                        oninput={ctx.link().callback(|event: InputEvent| {
+                            let input: HtmlInputElement = event.target_unchecked_into();
+                            AppMessage::RepeatCountInput(input.value())
+                        })}
+                    />
                     <button
                         disabled={app.state.history.is_empty()}
                         class={if app.state.history.is_empty() {
@@ -99,7 +277,7 @@ pub fn view(app: &App, ctx: &Context<App>) -> Html {
                             "border background-slate-100 p-2 rounded-md border-slate-900"
                         }}
                         onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::Undo())}>
-                        {"Undo"}
+                        {text(Text::Undo)}
                     </button>
                     <button
                         disabled={app.state.redo_queue.is_empty()}
@@ -110,12 +288,232 @@ pub fn view(app: &App, ctx: &Context<App>) -> Html {
                         }}
         //http://127.0.0.1:1420/strange-facility-4/
                         onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::Redo())}>
-                        {"Redo"}
+                        {text(Text::Redo)}
+                    </button>
+                    <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::ToggleRecipes())}>
+                        {if app.state.show_recipes { text(Text::HideRecipes) } else { text(Text::Recipes) }}
+                    </button>
+                    <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::ToggleJournal())}>
+                        {fill(Text::JournalButton, &[&app.state.journal.len()])}
+                    </button>
+                    <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::KeyPressed("?".to_string()))}>
+                        {text(Text::Shortcuts)}
+                    </button>
+                    { locale_select(app, ctx) }
+                    { number_format_select(app, ctx) }
+                    { crate::view::daily::daily_view(app, ctx) }
+                    { crate::view::generator::generator_view(app, ctx) }
+                    <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::ToggleEditor())}>
+                        {text(Text::ScenarioEditor)}
+                    </button>
+                    <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::ToggleContentPacks())}>
+                        {fill(Text::ContentPacks, &[&app.state.active_packs.len()])}
+                    </button>
+                </div>
+                { if app.state.show_packs {
+                    crate::view::packs::packs_view(app, ctx)
+                } else {
+                    html! {
+                        <></>
+                    }
+                }}
+                { if app.state.show_recipes {
+                    crate::view::graph::recipe_graph_view(app)
+                } else {
+                    html! {
+                        <></>
+                    }
+                }}
+                // Saving the selected rows as a macro
+                { if app.state.selected_rows.is_some() {
+                    html! {
+                    <div class="flex gap-x-2 mt-2">
+                        <input
+                            class="border border-slate-900 p-2"
+                            placeholder={text(Text::MacroName)}
+                            value={app.state.macro_name.clone()}
+                            oninput={ctx.link().callback(|event: InputEvent| {
+                                let input: HtmlInputElement = event.target_unchecked_into();
+                                AppMessage::MacroNameInput(input.value())
+                            })}
+                        />
+                        <button class="border border-slate-900 background-slate-100 p-2 rounded-md" onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::SaveMacro())}>
+                            {text(Text::SaveMacro)}
+                        </button>
+                    { if let Some(macro_error) = &app.state.macro_error {
+                        html! { <div class="p-2 border-2 border-red-600"> {macro_error} </div> }
+                    } else {
+                        html! {
+                            <></>
+                        }
+                    }}
+                    </div>
+                    }
+                } else {
+                    html! {
+                        <></>
+                    }
+                }}
+                // Plans written as text, for sharing routes
+                <div class="flex flex-col gap-y-1 my-2">
+                    <textarea
+                        class="border border-slate-900 p-2"
+                        rows="2"
+                        placeholder={text(Text::PlanPlaceholder)}
+                        value={app.state.plan_text.clone()}
+                        oninput={ctx.link().callback(|event: InputEvent| {
+                            let input: HtmlTextAreaElement = event.target_unchecked_into();
+                            AppMessage::PlanInput(input.value())
+                        })}
+                    />
+                    <div class="flex gap-x-2">
+                        <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::ExportPlan())}>
+                            {text(Text::ExportPlan)}
+                        </button>
+                        <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::ImportPlan())}>
+                            {text(Text::ImportPlan)}
+                        </button>
+                    </div>
+                { for app.state.plan_errors.iter().map(|error| {
+                    html! { <div class="p-1 border-2 border-red-600"> {error} </div> }
+                })}
+                </div>
+                // End of game summary, only shown once the game is won
+                { crate::view::summary::summary_view(app, ctx) }
+                // Resources over the whole run
+                { crate::view::chart::chart(app, ctx) }
+                // Ghost run to race against
+                <div class="flex flex-row flex-wrap gap-2 my-2">
+                    <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::GhostPersonalBest())}>
+                        {text(Text::GhostBestRun)}
+                    </button>
+                    <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::GhostSolver())}>
+                        {text(Text::GhostSolver)}
                     </button>
+                { if let Some(ghost) = &app.state.ghost {
+                    html! {
+                    <>
+                        <div class="p-2">{fill(Text::Racing, &[&ghost.name])}</div>
+                        <button class="border border-slate-900 p-2 rounded-md background-slate-100" onclick={ctx.link().callback(|_event: MouseEvent| AppMessage::ClearGhost())}>
+                            {text(Text::NoGhost)}
+                        </button>
+                    </>
+                    }
+                } else {
+                    html! {
+                        <></>
+                    }
+                }}
+                { if let Some(ghost_error) = &app.state.ghost_error {
+                    html! { <div class="p-2 border-2 border-red-600"> {ghost_error} </div> }
+                } else {
+                    html! {
+                        <></>
+                    }
+                }}
+                </div>
+                // Jobs done so far, with consecutive identical jobs merged into one row
+                <div class="flex flex-col gap-y-1 my-2">
+                { for app.view_cache.job_rows.iter().enumerate().map(|(row_position, job_row)| {
+                    let index = job_row.index;
+                    let selected = app.state.selected_rows
+                        .map(|(first, last)| index >= first && index <= last)
+                        .unwrap_or(false);
+                    let highlighted = app.state.highlighted_job
+                        .map(|job_index| job_row.contains_job_index(job_index))
+                        .unwrap_or(false) ||
+                        app.state.row_cursor == Some(row_position);
+                    html! {
+                    <div class={match (job_row.output.is_ok(), selected || highlighted) {
+                        (true, false) => "flex flex-row flex-wrap gap-x-2 p-1 border border-slate-900",
+                        (false, false) => "flex flex-row flex-wrap gap-x-2 p-1 border border-red-600",
+                        (true, true) => "flex flex-row flex-wrap gap-x-2 p-1 border border-slate-900 bg-blue-200",
+                        (false, true) => "flex flex-row flex-wrap gap-x-2 p-1 border border-red-600 bg-blue-200",
+                    }}>
+                        <div class="w-48" onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::SelectRow(index))}>
+                            { job_icon_view(job_row.job.id, job_name(&job_row.job)) }
+                            {" "}
+                            {if job_row.instances > 1 {
+                                format!("{}x {}", job_row.instances, job_name(&job_row.job))
+                            } else {
+                                job_name(&job_row.job).to_string()
+                            }}
+                        </div>
+                    { for job_row.resource_list.iter().map(|(resource, amount)| {
+                        html! {
+                        <div class="w-28" title={format!("{} {}", amount, resource_name(resource, to_count(amount)))}>
+                            { resource_icon_view(resource) }
+                            {format!(" {}", format_quantity(amount))}
+                        </div>
+                        }
+                    })}
+                    { ghost_column(app, job_row) }
+                    { for job_row.resource_tool_list.iter().map(|tool| {
+                        let (resource, amount) = &tool.resource_pair;
+                        let class = match tool.status {
+                            ResourceToolStatus::Standard => "px-1 border border-slate-900",
+                            ResourceToolStatus::Changed => "px-1 border border-blue-500",
+                            ResourceToolStatus::Removed => "px-1 border border-slate-400 text-slate-400 line-through",
+                        };
+                        let uses = tool.durability.iter().map(|uses| uses.to_string()).collect::<Vec<_>>();
+                        html! {
+                        <div class={class}>
+                            { resource_icon_view(resource) }
+                            {if uses.is_empty() {
+                                format!(" {}", count_of(resource, amount))
+                            } else {
+                                format!(" {} {}", count_of(resource, amount), fill(Text::Uses, &[&uses.join(", ")]))
+                            }}
+                        </div>
+                        }
+                    })}
+                    { if index > 0 {
+                        html! {
+                        <>
+                            <button class="border border-slate-900 px-2 rounded-md" onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::AddOne(index))}>
+                                {"+"}
+                            </button>
+                            <button class="border border-slate-900 px-2 rounded-md" onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::RemoveOne(index))}>
+                                {"-"}
+                            </button>
+                            <button class="border border-slate-900 px-2 rounded-md" onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::RemoveCluster(index))}>
+                                {text(Text::Remove)}
+                            </button>
+                        { if job_row.job.end_deltas.len() > 1 {
+                            html! {
+                            <button class="border border-slate-900 px-2 rounded-md" onclick={ctx.link().callback(move |_event: MouseEvent| AppMessage::CycleAlternative(index))}>
+                                {match job_row.job.chosen_alternative {
+                                    Some(chosen_index) => fill(Text::AlternativeNumber, &[&(chosen_index + 1)]),
+                                    None => text(Text::AlternativeAuto).to_string(),
+                                }}
+                            </button>
+                            }
+                        } else {
+                            html! {
+                                <></>
+                            }
+                        }}
+                        </>
+                        }
+                    } else {
+                        html! {
+                            <></>
+                        }
+                    }}
+                    </div>
+                    }
+                })}
                 </div>
                 <div class="border grid grid-flow-row grid-cols-[repeat(20_,20px)] grid-rows-4 gap-[2px]">
-                    <div class="border border-slate-400 p-1">{"1"}</div>
-                    <div class="border border-slate-400 p-1 col-span-3">{"5"}</div>
+                // One cell for each row of jobs, as wide as the number of jobs in it
+                { for app.view_cache.job_rows.iter().filter(|job_row| job_row.index > 0).map(|job_row| {
+                    let class = if job_row.output.is_ok() { "border border-slate-400" } else { "border border-red-600" };
+                    html! {
+                    <div class={class} style={format!("grid-column: span {} / span {};", job_row.instances.min(20), job_row.instances.min(20))}>
+                        { job_icon_view(job_row.job.id, job_name(&job_row.job)) }
+                    </div>
+                    }
+                })}
                 </div>
             </div>
         </div>
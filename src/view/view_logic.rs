@@ -1,12 +1,21 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use yew::Classes;
-use crate::app::*;
-use crate::core::job::{Job, JobOutput, starting_resources};
+use crate::core::effect::EffectSet;
+use crate::core::inventory::DurabilitySet;
+use crate::core::record::{JobRecord, StepRecord};
+use crate::core::job::{apply_job, Job, JobId, JobOutput};
+use crate::view::leaderboard::local_runs;
+use crate::view::summary::solver_plan;
+use crate::core::ghost::{compare_runs, plan_to_steps, GhostDay};
+use crate::core::plan::{format_plan, parse_plan};
+use crate::core::replay::{count_days, expand_history, replay_jobs, Replay};
+use crate::core::upgrade::Upgrades;
+use crate::core::quantity::{quantity, Quantity};
+use crate::core::random::Roller;
+use crate::core::locale::{days, fill, text, Text};
+use crate::core::story::{unlocked_entries, JournalEntryId};
 use crate::core::resource::{attributes, Resource, ResourceAttributes, ResourceSet};
-use crate::game::*;
-use crate::jobs::*;
 use crate::view::app::{App, State};
-use crate::view::class_string;
 use crate::view::view::class_string;
 
 pub struct ViewCache {
@@ -16,19 +25,47 @@ pub struct ViewCache {
     pub total_days: usize,
     pub user_error: Option<String>,
     pub game_state: GameState,
+    // Resources after the last successful job, used when planning ahead
+    pub final_resources: ResourceSet,
+    pub final_durability: DurabilitySet,
+    pub final_effects: EffectSet,
+    // Resources after every executed job, for plotting the run over time
+    pub snapshots: Vec<ResourceSnapshot>,
+    // Day by day comparison against the ghost run, if one is chosen
+    pub ghost_days: Vec<GhostDay>,
+    // Journal entries this run has unlocked, in order
+    pub journal_unlocks: Vec<JournalEntryId>,
+    // Upgrades done by the end of the run, which change the jobs added next
+    pub upgrades: Upgrades,
+    // How many times each job succeeded, which decides how the jobs added next roll
+    pub times_done: BTreeMap<JobId, usize>,
 }
 
+pub struct ResourceSnapshot {
+    pub job_index: usize,
+    // Days spent up to and including this job
+    pub day: usize,
+    pub resources: ResourceSet,
+}
+
+// Upper bound for "repeat until it fails", in case a job can never fail
+pub const MAX_REPEATS: usize = 1000;
+
 pub struct JobRow {
     pub job: Job,
     pub output: JobOutput,
-    pub resource_list: Vec<(Resource, i64)>,
+    pub resource_list: Vec<(Resource, Quantity)>,
     pub resource_tool_list: Vec<ResourceTool>,
     pub index: usize,
+    // How many consecutive job instances were merged into this row
+    pub instances: usize,
 }
 
 pub struct ResourceTool {
-    pub resource_pair: (Resource, i64),
+    pub resource_pair: (Resource, Quantity),
     pub status: ResourceToolStatus,
+    // Remaining uses of each held unit, empty for resources that don't wear out
+    pub durability: Vec<i64>,
 }
 
 pub enum ResourceToolStatus {
@@ -39,17 +76,29 @@ pub enum ResourceToolStatus {
 
 pub struct CurrentResource {
     pub resource: Resource,
-    pub amount: i64,
+    pub amount: Quantity,
     #[allow(dead_code)]
     pub row: usize,
     pub classes: Classes,
 }
 
+#[derive(Clone, Debug)]
+pub struct JobMacro {
+    pub name: String,
+    // The jobs this macro expands into, in order
+    pub jobs: Vec<Job>,
+}
+
 pub enum HistoryStep {
     Job(Job),
+    // Several jobs added at once, such as an expanded macro
+    Jobs(Vec<Job>),
+    // The same job added several times in a row
+    Repeat(Job, usize),
     AddOne(usize),
     RemoveOne(usize),
     RemoveCluster(usize),
+    CycleAlternative(usize),
 }
 
 pub enum GameState {
@@ -60,6 +109,38 @@ pub enum GameState {
 }
 
 
+impl HistoryStep {
+    pub fn to_record(&self) -> StepRecord {
+        let job_record = |job: &Job| JobRecord {
+            id: job.id,
+            chosen_alternative: job.chosen_alternative,
+        };
+        match self {
+            HistoryStep::Job(job) => StepRecord::Job(job_record(job)),
+            HistoryStep::Jobs(jobs) => StepRecord::Jobs(jobs.iter().map(job_record).collect()),
+            HistoryStep::Repeat(job, times) => StepRecord::Repeat(job_record(job), *times),
+            HistoryStep::AddOne(index) => StepRecord::AddOne(*index),
+            HistoryStep::RemoveOne(index) => StepRecord::RemoveOne(*index),
+            HistoryStep::RemoveCluster(index) => StepRecord::RemoveCluster(*index),
+            HistoryStep::CycleAlternative(index) => StepRecord::CycleAlternative(*index),
+        }
+    }
+}
+
+// A reference run shown next to the current one
+pub struct Ghost {
+    pub name: String,
+    pub history: Vec<StepRecord>,
+    pub seed: u64,
+}
+
+impl JobRow {
+    // Does this row, with all of its merged instances, cover the job at this index?
+    pub fn contains_job_index(&self, job_index: usize) -> bool {
+        job_index >= self.index && job_index < self.index + self.instances
+    }
+}
+
 impl App {
 
     pub fn add_job(&mut self, job: Job) {
@@ -69,10 +150,163 @@ impl App {
         self.refresh_view_cache();
     }
 
-    fn normalize(resource_set: &ResourceSet, seen_resources: &Vec<Resource>) -> Vec<(Resource, i64)> {
+    pub fn add_repeated_job(&mut self, job: Job, times: usize) {
+        if times == 0 {
+            return;
+        }
+        self.state.displayed_job = Some(job.clone());
+        self.state.history.push(HistoryStep::Repeat(job, times));
+        self.state.redo_queue.clear();
+        self.refresh_view_cache();
+    }
+
+    // Add the job as many times as it can be done in a row from the current resources.
+    // The rolls are the ones the replay will make, since they only depend on the seed, the job and how many times it was done before.
+    pub fn add_job_until_failure(&mut self, job: Job) -> Result<(), String> {
+        let mut resources = self.view_cache.final_resources.clone();
+        let mut durability = self.view_cache.final_durability.clone();
+        let mut effects = self.view_cache.final_effects.clone();
+        let times_done_before = *self.view_cache.times_done.get(&job.id).unwrap_or(&0);
+        let mut upgrades = self.view_cache.upgrades.clone();
+        let mut times = 0;
+        while times < MAX_REPEATS {
+            let upgraded_job = upgrades.apply(&job);
+            if upgrades.is_done(&upgraded_job) {
+                break;
+            }
+            let output = apply_job(resources, durability, effects, &upgraded_job, &mut Roller::for_job(self.state.seed, job.id, times_done_before + times))?;
+            if !output.is_ok() {
+                break;
+            }
+            resources = output.resources_after;
+            durability = output.durability_after;
+            effects = output.effects_after;
+            upgrades.record(&upgraded_job);
+            times += 1;
+        }
+        self.add_repeated_job(job, times);
+        Ok(())
+    }
+
+    // The jobs the history expands into, written in the plan notation
+    pub fn export_plan(&self) -> String {
+        let steps = self.state.history.iter().map(|step| step.to_record()).collect::<Vec<_>>();
+        // The first job is the starting resources, which isn't part of a plan
+        format_plan(&expand_history(&steps, &self.state.scenario).jobs[1..])
+    }
+
+    // Add the jobs of a pasted plan as a single step, or keep the errors to show them
+    pub fn import_plan(&mut self) {
+        match parse_plan(&self.state.plan_text, &self.state.scenario.jobs) {
+            Ok(jobs) => {
+                self.state.plan_errors.clear();
+                if !jobs.is_empty() {
+                    self.state.displayed_job = jobs.last().cloned();
+                    self.state.history.push(HistoryStep::Jobs(jobs));
+                    self.state.redo_queue.clear();
+                    self.refresh_view_cache();
+                }
+            }
+            Err(errors) => {
+                self.state.plan_errors = errors;
+            }
+        }
+    }
+
+    // Compare against the best run kept in this browser
+    pub fn ghost_personal_best(&mut self) {
+        let scenario = &self.state.scenario;
+        match local_runs(&scenario.name).into_iter().find(|run| run.scenario_version == scenario.version) {
+            Some(run) => {
+                self.state.ghost = Some(Ghost {
+                    name: fill(Text::BestRunName, &[&days(run.days)]),
+                    history: run.history,
+                    seed: run.seed,
+                });
+                self.state.ghost_error = None;
+            }
+            None => {
+                self.state.ghost_error = Some(text(Text::NoFinishedRun).to_string());
+            }
+        }
+        self.refresh_view_cache();
+    }
+
+    // Compare against the fastest run the solver can find, which is the plan its par came from
+    pub fn ghost_solver(&mut self) {
+        match solver_plan(&self.state.scenario) {
+            Ok(Some(plan)) => {
+                self.state.ghost = Some(Ghost {
+                    name: fill(Text::SolverName, &[&days(plan.len())]),
+                    history: plan_to_steps(&plan),
+                    // The plan works whatever the rolls, so it races with the same luck as the player
+                    seed: self.state.seed,
+                });
+                self.state.ghost_error = None;
+            }
+            Ok(None) => {
+                self.state.ghost_error = Some(text(Text::SolverFoundNothing).to_string());
+            }
+            Err(programmer_error) => {
+                self.programmer_error = Some(programmer_error);
+            }
+        }
+        self.refresh_view_cache();
+    }
+
+    pub fn add_macro(&mut self, job_macro: JobMacro) {
+        self.state.displayed_job = job_macro.jobs.last().cloned();
+        self.state.history.push(HistoryStep::Jobs(job_macro.jobs));
+        self.state.redo_queue.clear();
+        self.refresh_view_cache();
+    }
+
+    // Extend the selection so that it covers the row starting at this job index
+    pub fn select_row(&mut self, index: usize) {
+        self.state.selected_rows = match self.state.selected_rows {
+            None => Some((index, index)),
+            Some((first, last)) if first == index && last == index => None,
+            Some((first, last)) => Some((first.min(index), last.max(index))),
+        };
+    }
+
+    // Save the selected rows as a macro, with every merged instance expanded
+    pub fn save_macro(&mut self) -> Result<(), String> {
+        let (first, last) = match self.state.selected_rows {
+            Some(range) => range,
+            None => return Err(text(Text::SelectRowsFirst).to_string()),
+        };
+        let name = self.state.macro_name.trim().to_string();
+        if name.is_empty() {
+            return Err(text(Text::MacroNeedsName).to_string());
+        }
+        if self.state.macros.iter().any(|job_macro| job_macro.name == name) {
+            return Err(fill(Text::MacroExists, &[&name]));
+        }
+        let mut jobs = Vec::new();
+        for job_row in self.view_cache.job_rows.iter() {
+            if job_row.index >= first && job_row.index <= last && job_row.index > 0 {
+                for _ in 0..job_row.instances {
+                    jobs.push(job_row.job.clone());
+                }
+            }
+        }
+        if jobs.is_empty() {
+            return Err(text(Text::EmptySelection).to_string());
+        }
+        self.state.macros.push(JobMacro {
+            name,
+            jobs,
+        });
+        self.state.selected_rows = None;
+        self.state.macro_name.clear();
+        Ok(())
+    }
+
+    fn normalize(resource_set: &ResourceSet, seen_resources: &[Resource]) -> Vec<(Resource, Quantity)> {
         let mut result = Vec::new();
         for resource in seen_resources.iter() {
-            result.push((resource.clone(), resource_set.get(resource).map(|number| number.clone()).unwrap_or(0)));
+            result.push((*resource, resource_set.get(resource).cloned().unwrap_or_default()));
         }
         result
     }
@@ -84,7 +318,7 @@ impl App {
         });
     }
 
-    fn create_resource_tool_list(resources: &ResourceSet, changed: Option<&Vec<Resource>>) -> Vec<ResourceTool> {
+    fn create_resource_tool_list(resources: &ResourceSet, durability: &DurabilitySet, changed: Option<&Vec<Resource>>) -> Vec<ResourceTool> {
         let attributes = attributes();
         let mut resource_tool_list = Vec::new();
         for (resource, amount) in resources.iter() {
@@ -92,24 +326,28 @@ impl App {
                 .map(|attribute| (attribute.display_as_name, attribute.visible))
                 .unwrap_or((false, false));
             let changed = changed.map(|changed| changed.contains(resource)).unwrap_or(false);
+            let unit_durability = durability.get(resource).cloned().unwrap_or_default();
             if display_as_name && visible {
                 if changed {
-                    if *amount > 0 {
+                    if *amount > quantity(0) {
                         resource_tool_list.push(ResourceTool {
-                            resource_pair: (resource.clone(), *amount),
+                            resource_pair: (*resource, amount.clone()),
                             status: ResourceToolStatus::Changed,
+                            durability: unit_durability.clone(),
                         });
                     } else {
                         resource_tool_list.push(ResourceTool {
-                            resource_pair: (resource.clone(), *amount),
+                            resource_pair: (*resource, amount.clone()),
                             status: ResourceToolStatus::Removed,
+                            durability: unit_durability.clone(),
                         });
                     }
                 } else {
-                    if *amount > 0 {
+                    if *amount > quantity(0) {
                         resource_tool_list.push(ResourceTool {
-                            resource_pair: (resource.clone(), *amount),
+                            resource_pair: (*resource, amount.clone()),
                             status: ResourceToolStatus::Standard,
+                            durability: unit_durability.clone(),
                         });
                     }
                 }
@@ -118,24 +356,24 @@ impl App {
         resource_tool_list
     }
 
-    pub fn create_resource_view(state: &State, newest_row_of_resources: ResourceSet) -> Vec<Vec<CurrentResource>> {
+    pub fn create_resource_view(newest_row_of_resources: ResourceSet) -> Vec<Vec<CurrentResource>> {
         let attributes = attributes();
         let mut max_row = 0;
         let current_resources = newest_row_of_resources.iter()
             .filter_map(|(resource, amount)| {
-                if *amount > 0 {
+                if *amount > quantity(0) {
                     if let Some(att) = attributes.get(resource) {
                         if att.row > max_row {
                             max_row = att.row;
                         }
-                        Some((resource, *amount, Some(att)))
+                        Some((resource, amount.clone(), Some(att)))
                     } else {
-                        Some((resource, *amount, None))
+                        Some((resource, amount.clone(), None))
                     }
                 } else {
                     None
                 }
-            }).collect::<Vec<(&Resource, i64, Option<&ResourceAttributes>)>>();
+            }).collect::<Vec<(&Resource, Quantity, Option<&ResourceAttributes>)>>();
 
         let mut current_resource_rows = Vec::new();
         for row_number in (0..=max_row).rev() {
@@ -149,7 +387,7 @@ impl App {
                     current_row == row_number && visible
                 })
                 .map(|(current_resource, amount, att)| {
-                    let resource = (*current_resource).clone();
+                    let resource = **current_resource;
                     // let selected = state.selected_resource.map(|selected| selected == resource).unwrap_or(false);
                     let selected = false;
                     let show_blue_background =  selected;
@@ -169,8 +407,8 @@ impl App {
                         class_string("border-slate-900 active:bg-blue-500 active:text-slate-100")
                     };
                     CurrentResource {
-                        resource: resource.clone(),
-                        amount: *amount,
+                        resource,
+                        amount: amount.clone(),
                         row,
                         classes: class,
                     }
@@ -181,11 +419,28 @@ impl App {
         current_resource_rows
     }
 
+    // Add the jobs the run has found so far. Jobs stay found when the run is undone, so the buttons don't move around.
+    pub fn discover_jobs(&mut self) {
+        let held = self.view_cache.snapshots.iter()
+            .flat_map(|snapshot| snapshot.resources.iter())
+            .filter(|(_resource, amount)| **amount > quantity(0))
+            .map(|(resource, _amount)| *resource)
+            .collect::<BTreeSet<_>>();
+        for job in self.state.scenario.discoverable_jobs(&held) {
+            if !self.state.discovered_jobs.iter().any(|discovered| discovered.id == job.id) {
+                self.state.discovered_jobs.push(job);
+            }
+        }
+    }
+
     pub fn refresh_view_cache(&mut self) {
         let result = Self::create_view_cache(&self.state);
         match result {
             Ok(view_cache) => {
                 self.view_cache = view_cache;
+                self.discover_jobs();
+                self.record_win();
+                self.record_journal();
             }
             Err(programmer_error) => {
                 self.programmer_error = Some(programmer_error);
@@ -197,142 +452,114 @@ impl App {
     pub fn create_view_cache(state: &State) -> Result<ViewCache, String> {
         let mut user_error = None;
         let mut seen_resources = Vec::new();
-        let mut jobs_to_execute = vec![starting_resources()];
-        // Apply history to create job application order
-        for step in state.history.iter() {
-            match step {
-                HistoryStep::Job(job) => {
-                    jobs_to_execute.push(job.clone());
-                }
-                HistoryStep::AddOne(index) => {
-                    let job = jobs_to_execute.get(*index);
-                    if let Some(job) = job {
-                        jobs_to_execute.insert(*index, job.clone())
+        // Apply history to create job application order. This goes through the same records that
+        // are submitted to leaderboards, so that a verifier replays exactly what the player saw.
+        let steps = state.history.iter().map(|step| step.to_record()).collect::<Vec<_>>();
+        let expanded_jobs = expand_history(&steps, &state.scenario).jobs;
+        // Apply jobs. The rows show the jobs as they were done, with the upgrades before them.
+        let Replay { jobs: jobs_to_execute, outputs, upgrades, times_done } = replay_jobs(&expanded_jobs, state.seed)?;
+        let day_count = count_days(&jobs_to_execute, &outputs, state.scenario.win_job);
+        let journal_unlocks = unlocked_entries(&jobs_to_execute, &outputs);
+        let mut resources = BTreeMap::new();
+        let mut durability = BTreeMap::new();
+        let mut effects = BTreeMap::new();
+        let mut snapshots = Vec::new();
+        let mut job_and_output = Vec::new();
+        for (job_index, (job, job_output)) in jobs_to_execute.into_iter().zip(outputs).enumerate() {
+            for resource in job_output.resources_after.keys() {
+                let amount = job_output.resources_after.get(resource);
+                if let Some(amount) = amount {
+                    if !seen_resources.contains(resource) && *amount != quantity(0) {
+                        seen_resources.push(*resource);
                     }
                 }
-                HistoryStep::RemoveOne(index) => {
-                    if *index < jobs_to_execute.len() {
-                        jobs_to_execute.remove(*index);
-                    }
+            }
+
+            if job_output.is_ok() {
+                resources = job_output.resources_after.clone();
+                durability = job_output.durability_after.clone();
+                effects = job_output.effects_after.clone();
+            }
+            match job_output.user_message() {
+                None => {
+                    user_error = None;
                 }
-                HistoryStep::RemoveCluster(index) => {
-                    if *index < jobs_to_execute.len() {
-                        let first_job = jobs_to_execute.remove(*index);
-                        // The latter elements have moved over now, so we can keep checking this
-                        // slot to find all consecutive similar jobs
-                        loop {
-                            match jobs_to_execute.get(*index) {
-                                Some(job) => {
-                                    if first_job.id == job.id {
-                                        jobs_to_execute.remove(*index);
-                                    } else {
-                                        break;
-                                    }
-                                }
-                                None => {
-                                    break;
-                                }
-                            }
-                        }
-                    }
+                Some(error_message) => {
+                    user_error = Some(error_message.to_string());
+                }
+            }
+            snapshots.push(ResourceSnapshot {
+                job_index,
+                day: day_count.days_after[job_index],
+                resources: resources.clone(),
+            });
+            job_and_output.push((job, job_output));
+        };
+
+        let ghost_days = match &state.ghost {
+            Some(ghost) => compare_runs(&steps, state.seed, &ghost.history, ghost.seed, &state.scenario)?,
+            None => Vec::new(),
+        };
+
+        let total_days = day_count.total_days;
+        let game_state = match day_count.won_after {
+            Some(spent_days) => GameState::Won {
+                spent_days,
+            },
+            None => GameState::Playing,
+        };
+
+        // Prepare the complete list of resources that should be represented on each row of the table
+        Self::remove_invisible(&mut seen_resources);
+        seen_resources.sort();
+
+        // Merge jobs
+        let mut job_rows: Vec<JobRow> = Vec::new();
+        for (index, (this_job, this_output)) in job_and_output.into_iter().enumerate() {
+            let resource_list = Self::normalize(&this_output.resources_after, &seen_resources);
+            match job_rows.last_mut() {
+                Some(last_row) if this_job.id == last_row.job.id &&
+                    this_job.chosen_alternative == last_row.job.chosen_alternative &&
+                    this_output.is_mergeable(&last_row.output) => {
+                    let changed = &this_output.get_changed_resources().into_iter().chain(last_row.output.get_changed_resources()).collect::<Vec<_>>();
+                    let resource_tool_list = Self::create_resource_tool_list(&this_output.resources_after, &this_output.durability_after, Some(changed));
+                    last_row.instances += 1;
+                    last_row.resource_list = resource_list;
+                    last_row.resource_tool_list = resource_tool_list;
+                    last_row.output.main_output.changed_resources.extend(this_output.get_changed_resources());
+                }
+                _ => {
+                    let resource_tool_list = Self::create_resource_tool_list(&this_output.resources_after, &this_output.durability_after, Some(&this_output.get_changed_resources()));
+                    job_rows.push(JobRow {
+                        job: this_job,
+                        output: this_output,
+                        resource_list,
+                        resource_tool_list,
+                        index,
+                        instances: 1,
+                    });
                 }
             }
         }
-        // // Apply jobs
-        // let mut resources = BTreeMap::new();
-        // let mut job_and_output = Vec::new();
-        // for job in jobs_to_execute.into_iter() {
-        //     let job_output = apply_job(resources.clone(), &job)?;
-        //     for resource in job_output.resources_after.keys() {
-        //         let amount = job_output.resources_after.get(resource);
-        //         if let Some(amount) = amount {
-        //             if !seen_resources.contains(resource) && *amount != 0 {
-        //                 seen_resources.push(resource.clone());
-        //             }
-        //         }
-        //     }
-        //
-        //     if job_output.is_ok() {
-        //         resources = job_output.resources_after.clone();
-        //     }
-        //     match job_output.user_message() {
-        //         None => {
-        //             user_error = None;
-        //         }
-        //         Some(error_message) => {
-        //             user_error = Some(error_message.to_string());
-        //         }
-        //     }
-        //     job_and_output.push((job, job_output));
-        // };
-        //
-        // let mut total_days = 0;
-        // let mut game_state = GameState::Playing;
-        // for (job, output) in job_and_output.iter() {
-        //     if output.is_ok() {
-        //         // total_days += job.instances;
-        //         if job.id == WIN_JOB_ID {
-        //             game_state = GameState::Won {
-        //                 spent_days: total_days,
-        //             }
-        //         }
-        //     }
-        // }
-        //
-        // // Prepare the complete list of resources that should be represented on each row of the table
-        // Self::remove_invisible(&mut seen_resources);
-        // seen_resources.sort();
-        //
-        // // Merge jobs
-        // let mut job_rows = Vec::new();
-        // for (index, (this_job, this_output)) in job_and_output.into_iter().enumerate() {
-        //     let resource_list = Self::normalize(&this_output.resources_after, &seen_resources);
-        //     match job_rows.last_mut() {
-        //         None => {
-        //             let resource_by_name_list = Self::create_resource_tool_list(&this_output.resources_after, Some(&this_output.get_changed_resources()));
-        //             job_rows.push(JobRow {
-        //                 job: this_job,
-        //                 output: this_output,
-        //                 resource_list,
-        //                 resource_tool_list: resource_by_name_list,
-        //                 index,
-        //             });
-        //         }
-        //         Some(last_row) => {
-        //             if this_job.id == last_row.job.id &&
-        //                 this_output.is_mergeable(&last_row.output)
-        //             {
-        //                 let changed = &this_output.get_changed_resources().into_iter().chain(last_row.output.get_changed_resources()).collect::<Vec<_>>();
-        //                 let resource_tool_list = Self::create_resource_tool_list(&this_output.resources_after, Some(changed));
-        //                 last_row.job.instances += this_job.instances;
-        //                 last_row.resource_list = resource_list;
-        //                 last_row.resource_tool_list = resource_tool_list;
-        //                 last_row.output.main_output.changed_resources.extend(this_output.get_changed_resources().into_iter());
-        //             } else {
-        //                 let resource_by_name_list = Self::create_resource_tool_list(&this_output.resources_after, Some(&this_output.get_changed_resources()));
-        //                 job_rows.push(JobRow {
-        //                     job: this_job,
-        //                     output: this_output,
-        //                     resource_list,
-        //                     resource_tool_list: resource_by_name_list,
-        //                     index,
-        //                 });
-        //             }
-        //         }
-        //     }
-        // }
 
         // Process selectable resources for display
-        // let _attributes = attributes();
-        // let current_resources = App::create_resource_view(state, resources);
+        let current_resources = App::create_resource_view(resources.clone());
 
         Ok(ViewCache {
-            current_resources: Vec::new(),
-            job_rows: Vec::new(),
+            current_resources,
+            job_rows,
             resource_headings: seen_resources,
-            total_days: 0,
+            total_days,
             user_error,
-            game_state: GameState::Playing,
+            game_state,
+            final_resources: resources,
+            final_durability: durability,
+            final_effects: effects,
+            snapshots,
+            ghost_days,
+            journal_unlocks,
+            upgrades,
+            times_done,
         })
     }
 }